@@ -0,0 +1,222 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use std::collections::VecDeque;
+use std::hint::black_box;
+
+use lists::traits::{Queue, Stack};
+use lists::{fifth, first, fourth, second, third};
+
+// Every list against each other, and against Vec and VecDeque, on the same elements.
+// It's mostly about how much the nodes cost - an allocation per element, and chasing
+// pointers all over the heap - compared to one contiguous buffer.
+//
+// cargo bench --bench lists
+const COUNT: u64 = 100_000;
+
+// Same "random" numbers every run, so runs can be compared
+fn shuffled() -> Vec<u64> {
+    let mut x = 0x2545_f491_4f6c_dd1d_u64;
+    (0..COUNT)
+        .map(|_| {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x
+        })
+        .collect()
+}
+
+// Same loop for every list with the Stack (or Queue) trait
+fn push_pop_stack<T, S: Stack<T>>(mut stack: S, elems: impl Iterator<Item = T>) {
+    for elem in elems {
+        stack.push(elem);
+    }
+    while let Some(elem) = stack.pop() {
+        black_box(elem);
+    }
+}
+
+fn push_pop_queue<T, Q: Queue<T>>(mut queue: Q, elems: impl Iterator<Item = T>) {
+    for elem in elems {
+        queue.enqueue(elem);
+    }
+    while let Some(elem) = queue.dequeue() {
+        black_box(elem);
+    }
+}
+
+// Push COUNT elements, then pop them all, on whichever end the list is fastest at
+fn push_pop(c: &mut Criterion) {
+    let mut group = c.benchmark_group("push_pop");
+
+    group.bench_function("first", |b| {
+        b.iter(|| push_pop_stack(first::List::new(), 0..COUNT as i32))
+    });
+
+    group.bench_function("second", |b| {
+        b.iter(|| push_pop_stack(second::List::new(), 0..COUNT))
+    });
+
+    group.bench_function("third", |b| {
+        b.iter(|| push_pop_stack(third::List::new(), 0..COUNT))
+    });
+
+    group.bench_function("fourth", |b| {
+        b.iter(|| {
+            let mut list = fourth::List::new();
+            for i in 0..COUNT {
+                list.push_front(i);
+            }
+            while let Some(elem) = list.pop_front() {
+                black_box(elem);
+            }
+        })
+    });
+
+    group.bench_function("fifth", |b| {
+        b.iter(|| push_pop_queue(fifth::List::new(), 0..COUNT))
+    });
+
+    group.bench_function("vec", |b| {
+        b.iter(|| {
+            let mut list = Vec::new();
+            for i in 0..COUNT {
+                list.push(i);
+            }
+            while let Some(elem) = list.pop() {
+                black_box(elem);
+            }
+        })
+    });
+
+    group.bench_function("vec_deque", |b| {
+        b.iter(|| {
+            let mut list = VecDeque::new();
+            for i in 0..COUNT {
+                list.push_back(i);
+            }
+            while let Some(elem) = list.pop_front() {
+                black_box(elem);
+            }
+        })
+    });
+
+    group.finish();
+}
+
+// fourth has no iter (it can't hand out plain references through RefCell),
+// and first has no iterators at all, so they are left out
+fn iterate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("iterate");
+
+    let list: second::List<u64> = (0..COUNT).collect();
+    group.bench_function("second", |b| {
+        b.iter(|| black_box(&list).iter().sum::<u64>())
+    });
+
+    let list = (0..COUNT)
+        .rev()
+        .fold(third::List::new(), |list, elem| list.prepend(elem));
+    group.bench_function("third", |b| b.iter(|| black_box(&list).iter().sum::<u64>()));
+
+    let list: fifth::List<u64> = (0..COUNT).collect();
+    group.bench_function("fifth", |b| b.iter(|| black_box(&list).iter().sum::<u64>()));
+
+    let list: Vec<u64> = (0..COUNT).collect();
+    group.bench_function("vec", |b| b.iter(|| black_box(&list).iter().sum::<u64>()));
+
+    let list: VecDeque<u64> = (0..COUNT).collect();
+    group.bench_function("vec_deque", |b| {
+        b.iter(|| black_box(&list).iter().sum::<u64>())
+    });
+
+    group.finish();
+}
+
+// Building from an iterator in one go, FromIterator where there is one
+fn collect(c: &mut Criterion) {
+    let mut group = c.benchmark_group("collect");
+
+    group.bench_function("second", |b| {
+        b.iter(|| (0..COUNT).collect::<second::List<u64>>())
+    });
+    group.bench_function("fourth", |b| {
+        b.iter(|| (0..COUNT).collect::<fourth::List<u64>>())
+    });
+    group.bench_function("fifth", |b| {
+        b.iter(|| (0..COUNT).collect::<fifth::List<u64>>())
+    });
+    group.bench_function("vec", |b| b.iter(|| (0..COUNT).collect::<Vec<u64>>()));
+    group.bench_function("vec_deque", |b| {
+        b.iter(|| (0..COUNT).collect::<VecDeque<u64>>())
+    });
+
+    group.finish();
+}
+
+// Only second::List can sort. Input is rebuilt for every run, outside of the measurement.
+fn sort(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sort");
+    let input = shuffled();
+
+    group.bench_function("second", |b| {
+        b.iter_batched(
+            || input.iter().copied().collect::<second::List<u64>>(),
+            |mut list| {
+                list.sort();
+                list
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.bench_function("vec", |b| {
+        b.iter_batched(
+            || input.clone(),
+            |mut list| {
+                list.sort();
+                list
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.bench_function("vec_deque", |b| {
+        b.iter_batched(
+            || input.iter().copied().collect::<VecDeque<u64>>(),
+            |mut list| {
+                list.make_contiguous().sort();
+                list
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.finish();
+}
+
+// Pushing and popping a few elements over and over, the list never grows, but without the pool
+// every push allocates a node, and every pop frees one
+fn pool(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pool");
+
+    for (name, mut list) in [
+        ("none", second::List::new()),
+        ("16", second::List::with_pool_capacity(16)),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                for i in 0..16 {
+                    list.push(i);
+                }
+                while let Some(elem) = list.pop() {
+                    black_box(elem);
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, push_pop, iterate, collect, sort, pool);
+criterion_main!(benches);
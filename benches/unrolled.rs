@@ -0,0 +1,63 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::hint::black_box;
+
+use lists::{second, unrolled};
+
+// Iteration over unrolled::List vs second::List - same elements, same order,
+// the unrolled one reads them in chunks, from fewer places in memory
+const COUNT: u64 = 100_000;
+
+fn iterate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("iterate");
+
+    let list: second::List<u64> = (0..COUNT).collect();
+    group.bench_function("second", |b| {
+        b.iter(|| black_box(&list).iter().sum::<u64>())
+    });
+
+    let mut list: unrolled::List<u64> = unrolled::List::new();
+    for i in 0..COUNT {
+        list.push(i);
+    }
+    group.bench_function("unrolled", |b| {
+        b.iter(|| black_box(&list).iter().sum::<u64>())
+    });
+
+    let list: Vec<u64> = (0..COUNT).collect();
+    group.bench_function("vec", |b| b.iter(|| black_box(&list).iter().sum::<u64>()));
+
+    group.finish();
+}
+
+fn push_pop(c: &mut Criterion) {
+    let mut group = c.benchmark_group("push_pop");
+
+    group.bench_function("second", |b| {
+        b.iter(|| {
+            let mut list = second::List::new();
+            for i in 0..COUNT {
+                list.push(i);
+            }
+            while let Some(elem) = list.pop() {
+                black_box(elem);
+            }
+        })
+    });
+
+    group.bench_function("unrolled", |b| {
+        b.iter(|| {
+            let mut list: unrolled::List<u64> = unrolled::List::new();
+            for i in 0..COUNT {
+                list.push(i);
+            }
+            while let Some(elem) = list.pop() {
+                black_box(elem);
+            }
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, iterate, push_pop);
+criterion_main!(benches);
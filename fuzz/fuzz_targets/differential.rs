@@ -0,0 +1,103 @@
+#![no_main]
+
+use std::collections::VecDeque;
+
+use libfuzzer_sys::fuzz_target;
+use lists::{fifth, second};
+
+// Differential fuzzing: the same random operations go to a list and to a VecDeque,
+// and after every one of them both have to hold the same elements. VecDeque is the model,
+// if they disagree, the list is wrong.
+//
+// cargo +nightly fuzz run differential
+//
+// Input is read two bytes at a time - operation and its argument. fifth::List is unsafe
+// inside, so it's also checked with debug_validate after every step, and the fuzzer's
+// address sanitizer catches use after free, double free, or leaks right where they happen.
+fuzz_target!(|data: &[u8]| {
+    second_list(data);
+    fifth_list(data);
+});
+
+fn second_list(data: &[u8]) {
+    let mut list = second::List::new();
+    let mut model = VecDeque::new();
+
+    for op in data.chunks_exact(2) {
+        let (op, arg) = (op[0], op[1]);
+        // Goes one past the end as well, to hit out of bounds too
+        let index = arg as usize % (model.len() + 2);
+
+        match op % 8 {
+            0 => {
+                list.push(arg);
+                model.push_front(arg);
+            }
+            1 => assert_eq!(list.pop(), model.pop_front()),
+            2 => {
+                list.push_back(arg);
+                model.push_back(arg);
+            }
+            3 => assert_eq!(list.pop_back(), model.pop_back()),
+            4 => {
+                if index <= model.len() {
+                    assert_eq!(list.insert(index, arg), Ok(()));
+                    model.insert(index, arg);
+                } else {
+                    assert_eq!(list.insert(index, arg), Err(arg));
+                }
+            }
+            5 => assert_eq!(list.remove(index), model.remove(index)),
+            6 => {
+                assert_eq!(list.peek(), model.front());
+                assert_eq!(list.peek_back(), model.back());
+            }
+            _ => {
+                list.iter_mut().for_each(|elem| *elem = elem.wrapping_add(arg));
+                model.iter_mut().for_each(|elem| *elem = elem.wrapping_add(arg));
+            }
+        }
+
+        assert_eq!(list.len(), model.len());
+        assert!(list.iter().eq(model.iter()));
+    }
+
+    assert!(list.into_iter().eq(model));
+}
+
+fn fifth_list(data: &[u8]) {
+    let mut list = fifth::List::new();
+    let mut model = VecDeque::new();
+
+    for op in data.chunks_exact(2) {
+        let (op, arg) = (op[0], op[1]);
+
+        match op % 5 {
+            0 => {
+                list.push(arg);
+                model.push_back(arg);
+            }
+            1 => assert_eq!(list.pop(), model.pop_front()),
+            2 => assert_eq!(list.peek(), model.front()),
+            3 => {
+                // Appends a list of arg % 4 elements, empty ones included
+                let mut other = fifth::List::new();
+                for i in 0..arg % 4 {
+                    other.push(i);
+                    model.push_back(i);
+                }
+                list.append(other);
+            }
+            _ => {
+                list.iter_mut().for_each(|elem| *elem = elem.wrapping_add(arg));
+                model.iter_mut().for_each(|elem| *elem = elem.wrapping_add(arg));
+            }
+        }
+
+        list.debug_validate();
+        assert_eq!(list.len(), model.len());
+        assert!(list.iter().eq(model.iter()));
+    }
+
+    assert!(list.into_iter().eq(model));
+}
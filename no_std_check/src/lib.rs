@@ -0,0 +1,93 @@
+// Compiles the lists with core + alloc only. If some list pulls in std, this crate
+// fails to build. Tests link std as usual (the test harness needs it), but lists is still
+// built without the "std" feature.
+#![cfg_attr(not(test), no_std)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use lists::{arena, fifth, first, fourth, persistent_queue, second, skip_list, third};
+use lists::{ms_queue, treiber, unrolled, xor_list};
+
+// Pushes 0..n through every list, and returns what came out of each one
+pub fn roundtrip(n: i32) -> Vec<Vec<i32>> {
+    let mut out = Vec::new();
+
+    let mut list = first::List::new();
+    (0..n).for_each(|i| list.push(i));
+    out.push(core::iter::from_fn(|| list.pop()).collect());
+
+    let list: second::List<i32> = (0..n).collect();
+    out.push(list.into_iter().collect());
+
+    let mut list = third::List::new();
+    for i in 0..n {
+        list = list.prepend(i);
+    }
+    out.push(list.iter().copied().collect());
+
+    let mut list = fourth::List::new();
+    (0..n).for_each(|i| list.push_front(i));
+    out.push(core::iter::from_fn(|| list.pop_front()).collect());
+
+    let mut list = fifth::List::new();
+    (0..n).for_each(|i| list.push(i));
+    out.push(core::iter::from_fn(|| list.pop()).collect());
+
+    let mut queue = persistent_queue::Queue::new();
+    for i in 0..n {
+        queue = queue.enqueue(i);
+    }
+    out.push(
+        core::iter::from_fn(|| {
+            let elem = queue.peek().copied();
+            queue = queue.dequeue();
+            elem
+        })
+        .collect(),
+    );
+
+    let stack = treiber::Stack::new();
+    (0..n).for_each(|i| stack.push(i));
+    out.push(core::iter::from_fn(|| stack.pop()).collect());
+
+    let queue = ms_queue::Queue::new();
+    (0..n).for_each(|i| queue.push(i));
+    out.push(core::iter::from_fn(|| queue.pop()).collect());
+
+    let mut list = skip_list::SkipList::new();
+    (0..n).for_each(|i| {
+        list.insert(i);
+    });
+    out.push(list.iter().copied().collect());
+
+    let mut list: unrolled::List<i32, 4> = unrolled::List::new();
+    (0..n).for_each(|i| list.push(i));
+    out.push(list.into_iter().collect());
+
+    let mut list = xor_list::List::new();
+    (0..n).for_each(|i| list.push_back(i));
+    out.push(list.into_iter().collect());
+
+    let mut list = arena::List::new();
+    (0..n).for_each(|i| list.push_back(i));
+    out.push(list.into_iter().collect());
+
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::roundtrip;
+
+    #[test]
+    fn lists_work_without_std() {
+        // Stacks and queues give elements back in different orders, the point is
+        // that nothing got lost on the way
+        for mut out in roundtrip(10) {
+            out.sort_unstable();
+            assert_eq!(out, (0..10).collect::<Vec<_>>());
+        }
+    }
+}
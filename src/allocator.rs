@@ -0,0 +1,57 @@
+// Allocator support for second::List and fifth::List. Both take an allocator type
+// parameter A, and allocate their nodes with it (List::new_in(alloc)), so nodes can come
+// from a bump arena, a pool, etc.
+//
+// std::alloc::Allocator is nightly only, so it's behind the "allocator_api" feature:
+// cargo +nightly test --features allocator_api
+// Without it, A is still there, but the only allocator is Global (the default one),
+// so stable code does not notice anything.
+#[cfg(feature = "allocator_api")]
+pub use alloc::alloc::{Allocator, Global};
+
+#[cfg(not(feature = "allocator_api"))]
+pub use self::stable::{Allocator, Global};
+
+#[cfg(not(feature = "allocator_api"))]
+mod stable {
+    // Stand-ins for std::alloc ones
+    pub trait Allocator {}
+
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct Global;
+
+    impl Allocator for Global {}
+}
+
+use alloc::boxed::Box;
+use core::ptr::NonNull;
+
+// Raw pointer API, for the lists that keep raw pointers to nodes (fifth). Box is used only
+// to allocate and free, same as with Box::into_raw/from_raw.
+pub(crate) fn into_raw<T, A: Allocator>(value: T, alloc: &A) -> NonNull<T> {
+    #[cfg(feature = "allocator_api")]
+    let ptr = Box::into_raw_with_allocator(Box::new_in(value, alloc)).0;
+
+    #[cfg(not(feature = "allocator_api"))]
+    let ptr = {
+        let _ = alloc;
+        Box::into_raw(Box::new(value))
+    };
+
+    // Box is never null
+    unsafe { NonNull::new_unchecked(ptr) }
+}
+
+// ptr has to come from into_raw, with the same allocator
+pub(crate) unsafe fn from_raw<T, A: Allocator>(ptr: NonNull<T>, alloc: &A) -> T {
+    #[cfg(feature = "allocator_api")]
+    let boxed = Box::from_raw_in(ptr.as_ptr(), alloc);
+
+    #[cfg(not(feature = "allocator_api"))]
+    let boxed = {
+        let _ = alloc;
+        Box::from_raw(ptr.as_ptr())
+    };
+
+    *boxed
+}
@@ -0,0 +1,26 @@
+// proptest support, turned on with the "proptest" feature. Every list implements Arbitrary,
+// so property tests can take lists as inputs:
+//
+// proptest! {
+//     fn reverse_twice(list: second::List<i32>) { ... }
+// }
+//
+// A list is generated as a Vec of arbitrary elements first, and then built out of it in
+// the same order. Shrinking works on the Vec - proptest removes and shrinks elements,
+// and the list is rebuilt from what's left - so no list has to know how to shrink itself.
+// Parameters are the same as for Vec: (size range, parameters of elements).
+use alloc::vec::Vec;
+use core::fmt::Debug;
+
+use proptest::arbitrary::{any_with, Arbitrary};
+use proptest::collection::{vec, SizeRange, VecStrategy};
+use proptest::strategy::{Map, Strategy};
+
+pub(crate) type ListStrategy<T, L> = Map<VecStrategy<<T as Arbitrary>::Strategy>, fn(Vec<T>) -> L>;
+
+pub(crate) fn list_strategy<T: Arbitrary, L: Debug>(
+    (size, params): (SizeRange, T::Parameters),
+    build: fn(Vec<T>) -> L,
+) -> ListStrategy<T, L> {
+    vec(any_with::<T>(params), size).prop_map(build)
+}
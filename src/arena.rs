@@ -0,0 +1,645 @@
+use alloc::vec::Vec;
+use core::iter::FusedIterator;
+use core::mem;
+
+use crate::traits::Queue;
+
+// Doubly linked list, where all nodes live in one Vec (the arena), and link to each
+// other by index, instead of by pointer:
+//
+// nodes: [ 0: {a, prev: NIL, next: 2}, 1: vacant, 2: {b, prev: 0, next: NIL} ]
+// head: 0, tail: 2, free: 1
+//
+// - no allocation per node, the Vec grows now and then, like any other Vec
+// - no unsafe, no Rc/RefCell - an index can't dangle in a way that is UB, at worst
+//   it points to the wrong slot
+// - indices stay valid when the Vec reallocates, or when the whole list is moved,
+//   cloned, sent to another thread, or written to disk
+// - u32 is half of a pointer on 64-bit, 4 billion nodes should be enough
+//
+// Removed nodes leave vacant slots behind, they are linked into a free list,
+// and reused by the next push.
+#[derive(Clone, Debug)]
+pub struct List<T> {
+    nodes: Vec<Slot<T>>,
+    head: u32,
+    tail: u32,
+    // First vacant slot
+    free: u32,
+    len: usize,
+}
+
+// "null" index
+const NIL: u32 = u32::MAX;
+
+#[derive(Clone, Debug)]
+enum Slot<T> {
+    Occupied { elem: T, prev: u32, next: u32 },
+    Vacant { next_free: u32 },
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> List<T> {
+    pub const fn new() -> Self {
+        List {
+            nodes: Vec::new(),
+            head: NIL,
+            tail: NIL,
+            free: NIL,
+            len: 0,
+        }
+    }
+
+    // Room for capacity elements, without growing the arena
+    pub fn with_capacity(capacity: usize) -> Self {
+        List {
+            nodes: Vec::with_capacity(capacity),
+            head: NIL,
+            tail: NIL,
+            free: NIL,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // The whole arena, vacant slots and spare capacity too - that's what is allocated,
+    // see heap_size.rs
+    pub fn heap_size_in_bytes(&self) -> usize {
+        self.nodes.capacity() * core::mem::size_of::<Slot<T>>()
+    }
+
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.head = NIL;
+        self.tail = NIL;
+        self.free = NIL;
+        self.len = 0;
+    }
+
+    pub fn push_front(&mut self, elem: T) {
+        let index = self.alloc(elem, NIL, self.head);
+
+        match self.head {
+            NIL => self.tail = index,
+            head => *self.prev_mut(head) = index,
+        }
+
+        self.head = index;
+    }
+
+    pub fn push_back(&mut self, elem: T) {
+        let index = self.alloc(elem, self.tail, NIL);
+
+        match self.tail {
+            NIL => self.head = index,
+            tail => *self.next_mut(tail) = index,
+        }
+
+        self.tail = index;
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        match self.head {
+            NIL => None,
+            head => Some(self.unlink(head)),
+        }
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        match self.tail {
+            NIL => None,
+            tail => Some(self.unlink(tail)),
+        }
+    }
+
+    pub fn peek_front(&self) -> Option<&T> {
+        self.node(self.head).map(|(elem, _, _)| elem)
+    }
+
+    pub fn peek_back(&self) -> Option<&T> {
+        self.node(self.tail).map(|(elem, _, _)| elem)
+    }
+
+    pub fn peek_front_mut(&mut self) -> Option<&mut T> {
+        self.node_mut(self.head)
+    }
+
+    pub fn peek_back_mut(&mut self) -> Option<&mut T> {
+        self.node_mut(self.tail)
+    }
+
+    // Takes a vacant slot if there is one, grows the arena otherwise
+    fn alloc(&mut self, elem: T, prev: u32, next: u32) -> u32 {
+        let slot = Slot::Occupied { elem, prev, next };
+        self.len += 1;
+
+        match self.free {
+            NIL => {
+                let index = self.nodes.len();
+                assert!(index < NIL as usize, "arena is full");
+
+                self.nodes.push(slot);
+                index as u32
+            }
+            free => {
+                let vacant = mem::replace(&mut self.nodes[free as usize], slot);
+
+                match vacant {
+                    Slot::Vacant { next_free } => self.free = next_free,
+                    Slot::Occupied { .. } => unreachable!("free list points to an occupied slot"),
+                }
+
+                free
+            }
+        }
+    }
+
+    // Takes the node out of the list, its slot goes to the free list
+    fn unlink(&mut self, index: u32) -> T {
+        self.detach(index);
+
+        let vacant = Slot::Vacant {
+            next_free: self.free,
+        };
+
+        let elem = match mem::replace(&mut self.nodes[index as usize], vacant) {
+            Slot::Occupied { elem, .. } => elem,
+            Slot::Vacant { .. } => unreachable!("unlinking a vacant slot"),
+        };
+
+        self.free = index;
+        self.len -= 1;
+        elem
+    }
+
+    // Links the neighbours of the node to each other, the node itself stays in its slot,
+    // with stale prev and next
+    fn detach(&mut self, index: u32) {
+        let (prev, next) = match &self.nodes[index as usize] {
+            Slot::Occupied { prev, next, .. } => (*prev, *next),
+            Slot::Vacant { .. } => unreachable!("detaching a vacant slot"),
+        };
+
+        match prev {
+            NIL => self.head = next,
+            prev => *self.next_mut(prev) = next,
+        }
+
+        match next {
+            NIL => self.tail = prev,
+            next => *self.prev_mut(next) = prev,
+        }
+    }
+
+    // None for NIL
+    fn node(&self, index: u32) -> Option<(&T, u32, u32)> {
+        match self.nodes.get(index as usize)? {
+            Slot::Occupied { elem, prev, next } => Some((elem, *prev, *next)),
+            Slot::Vacant { .. } => unreachable!("link points to a vacant slot"),
+        }
+    }
+
+    fn node_mut(&mut self, index: u32) -> Option<&mut T> {
+        match self.nodes.get_mut(index as usize)? {
+            Slot::Occupied { elem, .. } => Some(elem),
+            Slot::Vacant { .. } => unreachable!("link points to a vacant slot"),
+        }
+    }
+
+    fn prev_mut(&mut self, index: u32) -> &mut u32 {
+        match &mut self.nodes[index as usize] {
+            Slot::Occupied { prev, .. } => prev,
+            Slot::Vacant { .. } => unreachable!("link points to a vacant slot"),
+        }
+    }
+
+    fn next_mut(&mut self, index: u32) -> &mut u32 {
+        match &mut self.nodes[index as usize] {
+            Slot::Occupied { next, .. } => next,
+            Slot::Vacant { .. } => unreachable!("link points to a vacant slot"),
+        }
+    }
+}
+
+// Handles - indices of nodes, given out so a node can be found again in O(1), without
+// walking the list. That's what an LRU cache needs (see lru.rs): a map from a key
+// to the node, and moving that node to the front on every access.
+//
+// A handle is good until its element is removed (or the list cleared). After that it
+// points to a vacant slot, and get/remove return None, or, once the slot is reused,
+// to some other element - wrong, but not UB, same as an out of date Vec index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Handle(u32);
+
+impl<T> List<T> {
+    pub fn push_front_handle(&mut self, elem: T) -> Handle {
+        self.push_front(elem);
+        Handle(self.head)
+    }
+
+    pub fn push_back_handle(&mut self, elem: T) -> Handle {
+        self.push_back(elem);
+        Handle(self.tail)
+    }
+
+    fn is_occupied(&self, handle: Handle) -> bool {
+        matches!(
+            self.nodes.get(handle.0 as usize),
+            Some(Slot::Occupied { .. })
+        )
+    }
+
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        match self.nodes.get(handle.0 as usize)? {
+            Slot::Occupied { elem, .. } => Some(elem),
+            Slot::Vacant { .. } => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, handle: Handle) -> Option<&mut T> {
+        match self.nodes.get_mut(handle.0 as usize)? {
+            Slot::Occupied { elem, .. } => Some(elem),
+            Slot::Vacant { .. } => None,
+        }
+    }
+
+    pub fn remove(&mut self, handle: Handle) -> Option<T> {
+        if !self.is_occupied(handle) {
+            return None;
+        }
+
+        Some(self.unlink(handle.0))
+    }
+
+    // Splices the node out, and back in at the front - only links change,
+    // the element stays in its slot, so the handle is still good
+    pub fn move_to_front(&mut self, handle: Handle) {
+        assert!(
+            self.is_occupied(handle),
+            "handle points to a removed element"
+        );
+
+        let index = handle.0;
+        if index == self.head {
+            return;
+        }
+
+        self.detach(index);
+
+        *self.prev_mut(index) = NIL;
+        *self.next_mut(index) = self.head;
+        match self.head {
+            NIL => self.tail = index,
+            head => *self.prev_mut(head) = index,
+        }
+        self.head = index;
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T: proptest::arbitrary::Arbitrary> proptest::arbitrary::Arbitrary for List<T> {
+    type Parameters = (proptest::collection::SizeRange, T::Parameters);
+    type Strategy = crate::arbitrary::ListStrategy<T, Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::arbitrary::list_strategy(args, |elems| {
+            let mut list = List::new();
+            for elem in elems {
+                list.push_back(elem);
+            }
+            list
+        })
+    }
+}
+
+impl<T> Queue<T> for List<T> {
+    fn enqueue(&mut self, elem: T) {
+        self.push_back(elem);
+    }
+
+    fn dequeue(&mut self) -> Option<T> {
+        self.pop_front()
+    }
+
+    fn front(&self) -> Option<&T> {
+        self.peek_front()
+    }
+}
+
+pub struct IntoIter<T>(List<T>);
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
+pub struct Iter<'a, T> {
+    list: &'a List<T>,
+    front: u32,
+    back: u32,
+    // Ends meet when it drops to 0
+    len: usize,
+}
+
+impl<T> List<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            list: self,
+            front: self.head,
+            back: self.tail,
+            len: self.len,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let (elem, _, next) = self.list.node(self.front)?;
+        self.front = next;
+        self.len -= 1;
+
+        Some(elem)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let (elem, prev, _) = self.list.node(self.back)?;
+        self.back = prev;
+        self.len -= 1;
+
+        Some(elem)
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> FusedIterator for Iter<'a, T> {}
+
+impl<'a, T> Iter<'a, T> {
+    // Elements with their handles, to find the ones to remove (or move) in one walk,
+    // and remove them later in O(1) each - the way a timer wheel drops expired timers
+    pub fn enumerate_nodes(self) -> EnumerateNodes<'a, T> {
+        EnumerateNodes(self)
+    }
+}
+
+pub struct EnumerateNodes<'a, T>(Iter<'a, T>);
+
+impl<'a, T> Iterator for EnumerateNodes<'a, T> {
+    type Item = (Handle, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.0.front;
+        self.0.next().map(|elem| (Handle(index), elem))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for EnumerateNodes<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let index = self.0.back;
+        self.0.next_back().map(|elem| (Handle(index), elem))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for EnumerateNodes<'a, T> {}
+
+impl<'a, T> FusedIterator for EnumerateNodes<'a, T> {}
+
+#[cfg(test)]
+mod test {
+    use super::List;
+
+    #[test]
+    fn basics() {
+        let mut list = List::new();
+
+        // Check empty list behaves right
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.pop_back(), None);
+        assert_eq!(list.peek_front(), None);
+
+        // Populate list
+        list.push_front(2);
+        list.push_front(1);
+        list.push_back(3);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.peek_front(), Some(&1));
+        assert_eq!(list.peek_back(), Some(&3));
+
+        // Check normal removal
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pop_back(), Some(3));
+
+        // Push some more just to make sure nothing's corrupted
+        list.push_back(4);
+        if let Some(elem) = list.peek_back_mut() {
+            *elem = 40;
+        }
+        assert_eq!(list.pop_back(), Some(40));
+        assert_eq!(list.pop_back(), Some(2));
+
+        // Check exhaustion
+        assert_eq!(list.pop_front(), None);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn reuses_slots() {
+        let mut list = List::with_capacity(4);
+        for i in 0..4 {
+            list.push_back(i);
+        }
+
+        list.pop_front();
+        list.pop_back();
+        list.push_front(10);
+        list.push_back(20);
+
+        // Vacant slots were taken, the arena did not grow
+        assert_eq!(list.nodes.len(), 4);
+        assert!(list.iter().copied().eq(vec![10, 1, 2, 20]));
+
+        list.push_back(30);
+        assert_eq!(list.nodes.len(), 5);
+    }
+
+    #[test]
+    fn iter() {
+        let mut list = List::new();
+        for i in 0..10 {
+            list.push_back(i);
+        }
+
+        assert!(list.iter().copied().eq(0..10));
+        assert!(list.iter().rev().copied().eq((0..10).rev()));
+
+        let mut iter = list.iter();
+        assert_eq!(iter.next(), Some(&0));
+        assert_eq!(iter.next_back(), Some(&9));
+        assert_eq!(iter.len(), 8);
+
+        assert!(list.into_iter().rev().eq((0..10).rev()));
+    }
+
+    #[test]
+    fn send_and_clone() {
+        let mut list = List::new();
+        for i in 0..10 {
+            list.push_back(i.to_string());
+        }
+        let copy = list.clone();
+
+        // No pointers inside, the list moves to another thread as is
+        let list = std::thread::spawn(move || list).join().unwrap();
+        assert!(list.iter().eq(copy.iter()));
+    }
+
+    #[test]
+    fn handles() {
+        let mut list = List::new();
+        let one = list.push_back_handle(1);
+        let two = list.push_back_handle(2);
+        let zero = list.push_front_handle(0);
+        list.push_back(3);
+
+        assert_eq!(list.get(one), Some(&1));
+        *list.get_mut(two).unwrap() = 20;
+
+        list.move_to_front(two);
+        assert!(list.iter().copied().eq(vec![20, 0, 1, 3]));
+        // Already there
+        list.move_to_front(two);
+        assert!(list.iter().copied().eq(vec![20, 0, 1, 3]));
+        list.move_to_front(one);
+        assert!(list.iter().rev().copied().eq(vec![3, 0, 20, 1]));
+
+        assert_eq!(list.remove(zero), Some(0));
+        assert_eq!(list.remove(zero), None);
+        assert_eq!(list.get(zero), None);
+        assert!(list.iter().copied().eq(vec![1, 20, 3]));
+
+        // Moving the tail updates it
+        let three = list.push_back_handle(30);
+        list.move_to_front(three);
+        assert_eq!(list.peek_front(), Some(&30));
+        assert_eq!(list.peek_back(), Some(&3));
+        assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(list.pop_back(), Some(20));
+        assert_eq!(list.pop_back(), Some(1));
+        assert_eq!(list.pop_back(), Some(30));
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "removed element")]
+    fn move_removed_to_front() {
+        let mut list = List::new();
+        let handle = list.push_back_handle(1);
+        list.push_back(2);
+        list.remove(handle);
+        list.move_to_front(handle);
+    }
+
+    #[test]
+    fn enumerate_nodes() {
+        let mut list = List::new();
+        for i in 0..10 {
+            list.push_back(i);
+        }
+
+        let odd: Vec<_> = list
+            .iter()
+            .enumerate_nodes()
+            .filter(|(_, elem)| *elem % 2 == 1)
+            .map(|(handle, _)| handle)
+            .collect();
+        for handle in odd {
+            assert!(list.remove(handle).is_some());
+        }
+        assert!(list.iter().copied().eq(vec![0, 2, 4, 6, 8]));
+
+        // Handles match the ones push gave out, from both ends
+        let handle = list.push_back_handle(10);
+        assert_eq!(
+            list.iter().enumerate_nodes().next_back(),
+            Some((handle, &10))
+        );
+        assert_eq!(list.iter().enumerate_nodes().len(), 6);
+
+        let (first, _) = list.iter().enumerate_nodes().next().unwrap();
+        list.move_to_front(handle);
+        assert_eq!(list.remove(first), Some(0));
+        assert!(list.iter().copied().eq(vec![10, 2, 4, 6, 8]));
+    }
+
+    #[test]
+    fn heap_size() {
+        let slot = std::mem::size_of::<super::Slot<u32>>();
+
+        let mut list = List::with_capacity(4);
+        assert_eq!(list.heap_size_in_bytes(), 4 * slot);
+
+        list.push_back(1u32);
+        list.push_back(2);
+        list.pop_front();
+        // Vacant slots stay allocated
+        assert_eq!(list.heap_size_in_bytes(), 4 * slot);
+    }
+}
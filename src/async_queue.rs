@@ -0,0 +1,343 @@
+use core::cell::Cell;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::fifth::List;
+use crate::intrusive::{IntrusiveList, Link, Linked};
+
+// SyncQueue for async code: pop_async() gives a future, which waits for an element
+// without blocking the thread. No executor and no runtime needed, any will do.
+//
+// Waiting futures are kept in an intrusive list, oldest first. A future which has
+// to wait links itself in (its Waiter is a field of the future, no allocation per
+// wait), and push takes the first one out and wakes it up.
+pub struct AsyncQueue<T> {
+    inner: Mutex<Inner<T>>,
+}
+
+struct Inner<T> {
+    list: List<T>,
+    // 'static is not true, Waiters live in the futures. But a future unlinks its Waiter
+    // when it's dropped (see Drop for PopFuture), and it's pinned while linked -
+    // pinned memory can't go away without drop, so the list never sees a dead one.
+    waiters: IntrusiveList<'static, Waiter>,
+}
+
+// Cells are read and written only with the queue locked
+struct Waiter {
+    waker: Cell<Option<Waker>>,
+    // Taken out of the list by push, the element is meant for this future
+    notified: Cell<bool>,
+    link: Link<Waiter>,
+}
+
+unsafe impl Linked for Waiter {
+    fn link(&self) -> &Link<Self> {
+        &self.link
+    }
+}
+
+impl Waiter {
+    // See Inner::waiters
+    unsafe fn extend(self: Pin<&Self>) -> Pin<&'static Self> {
+        Pin::new_unchecked(&*(self.get_ref() as *const Waiter))
+    }
+}
+
+// Waiters are !Send and !Sync (raw pointers and Cells), but they are touched only
+// with the lock held, so the queue is as thread safe as Mutex<List<T>> would be
+unsafe impl<T: Send> Send for AsyncQueue<T> {}
+unsafe impl<T: Send> Sync for AsyncQueue<T> {}
+
+impl<T> Inner<T> {
+    // Waker of the oldest waiter, to be woken once the lock is released
+    fn notify_one(&mut self) -> Option<Waker> {
+        let waiter = self.waiters.pop_front()?;
+        waiter.notified.set(true);
+        waiter.waker.take()
+    }
+}
+
+impl<T> Default for AsyncQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> AsyncQueue<T> {
+    pub fn new() -> Self {
+        AsyncQueue {
+            inner: Mutex::new(Inner {
+                list: List::new(),
+                waiters: IntrusiveList::new(),
+            }),
+        }
+    }
+
+    // Same as in SyncQueue, nothing here leaves the lists half modified
+    fn lock(&self) -> MutexGuard<'_, Inner<T>> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().list.is_empty()
+    }
+
+    pub fn push(&self, elem: T) {
+        let mut inner = self.lock();
+        inner.list.push(elem);
+        let waker = inner.notify_one();
+        drop(inner);
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    pub fn try_pop(&self) -> Option<T> {
+        self.lock().list.pop()
+    }
+
+    // Resolves to the next element, in the order pop_async was first polled
+    // (roughly - a future polled when there is an element just takes it)
+    pub fn pop_async(&self) -> PopFuture<'_, T> {
+        PopFuture {
+            queue: self,
+            waiter: Waiter {
+                waker: Cell::new(None),
+                notified: Cell::new(false),
+                link: Link::new(),
+            },
+        }
+    }
+}
+
+// Link is !Unpin, so the future is too, the waiter doesn't move once it's polled
+pub struct PopFuture<'q, T> {
+    queue: &'q AsyncQueue<T>,
+    waiter: Waiter,
+}
+
+// Waiter is touched only with the queue locked, see AsyncQueue
+unsafe impl<'q, T: Send> Send for PopFuture<'q, T> {}
+
+impl<'q, T> Future for PopFuture<'q, T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let queue = self.queue;
+        // waiter is never moved out of the future, so it's pinned as long as the future is
+        let waiter = unsafe { self.as_ref().map_unchecked(|future| &future.waiter) };
+        let mut inner = queue.lock();
+
+        if let Some(elem) = inner.list.pop() {
+            if waiter.link.is_linked() {
+                inner.waiters.remove(unsafe { waiter.extend() });
+            }
+            waiter.notified.set(false);
+            return Poll::Ready(elem);
+        }
+
+        // Nothing there, someone else took it, or it's the first poll - wait (again)
+        waiter.waker.replace(Some(cx.waker().clone()));
+        if !waiter.link.is_linked() {
+            waiter.notified.set(false);
+            inner.waiters.push_back(unsafe { waiter.extend() });
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<'q, T> Drop for PopFuture<'q, T> {
+    fn drop(&mut self) {
+        // Same as in poll, drop is the last thing that happens to a pinned future
+        let waiter = unsafe { Pin::new_unchecked(&self.waiter) };
+        let mut inner = self.queue.lock();
+
+        let waker = if waiter.link.is_linked() {
+            inner.waiters.remove(unsafe { waiter.extend() });
+            None
+        } else if waiter.notified.get() && !inner.list.is_empty() {
+            // Woken up for an element, but gone without taking it - pass it on,
+            // otherwise it would wait for the next push with the others sleeping
+            inner.notify_one()
+        } else {
+            None
+        };
+        drop(inner);
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::AsyncQueue;
+    use std::future::Future;
+    use std::pin::pin;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+    use std::thread::{self, Thread};
+
+    // Simplest executor there is: poll, park the thread until woken up, repeat
+    struct Unpark(Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let waker = Waker::from(Arc::new(Unpark(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            thread::park();
+        }
+    }
+
+    #[derive(Default)]
+    struct Count(AtomicUsize);
+
+    impl Wake for Count {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    impl Count {
+        fn get(&self) -> usize {
+            self.0.load(Ordering::SeqCst)
+        }
+    }
+
+    #[test]
+    fn basics() {
+        let queue = AsyncQueue::new();
+        assert!(queue.is_empty());
+        assert_eq!(queue.try_pop(), None);
+
+        queue.push(1);
+        queue.push(2);
+        assert_eq!(queue.len(), 2);
+
+        assert_eq!(block_on(queue.pop_async()), 1);
+        assert_eq!(queue.try_pop(), Some(2));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn waits_for_push() {
+        let queue = AsyncQueue::new();
+        let count = Arc::new(Count::default());
+        let waker = Waker::from(count.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        let mut future = pin!(queue.pop_async());
+        assert_eq!(future.as_mut().poll(&mut cx), Poll::Pending);
+        // Polling again doesn't link it twice
+        assert_eq!(future.as_mut().poll(&mut cx), Poll::Pending);
+        assert_eq!(queue.lock().waiters.len(), 1);
+
+        queue.push(1);
+        assert_eq!(count.get(), 1);
+        assert_eq!(queue.lock().waiters.len(), 0);
+        assert_eq!(future.as_mut().poll(&mut cx), Poll::Ready(1));
+    }
+
+    #[test]
+    fn dropped_future_unlinks() {
+        let queue = AsyncQueue::<i32>::new();
+        let count = Arc::new(Count::default());
+        let waker = Waker::from(count.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        {
+            let mut first = pin!(queue.pop_async());
+            let mut second = pin!(queue.pop_async());
+            assert_eq!(first.as_mut().poll(&mut cx), Poll::Pending);
+            assert_eq!(second.as_mut().poll(&mut cx), Poll::Pending);
+            assert_eq!(queue.lock().waiters.len(), 2);
+        }
+
+        assert_eq!(queue.lock().waiters.len(), 0);
+        queue.push(1);
+        assert_eq!(count.get(), 0);
+    }
+
+    #[test]
+    fn dropped_notified_future_passes_it_on() {
+        let queue = AsyncQueue::new();
+        let first_count = Arc::new(Count::default());
+        let first_waker = Waker::from(first_count.clone());
+        let second_count = Arc::new(Count::default());
+        let second_waker = Waker::from(second_count.clone());
+
+        let mut second = pin!(queue.pop_async());
+        {
+            let mut first = pin!(queue.pop_async());
+            let _ = first.as_mut().poll(&mut Context::from_waker(&first_waker));
+            let _ = second
+                .as_mut()
+                .poll(&mut Context::from_waker(&second_waker));
+
+            queue.push(1);
+            assert_eq!((first_count.get(), second_count.get()), (1, 0));
+        }
+
+        assert_eq!(second_count.get(), 1);
+        assert_eq!(
+            second
+                .as_mut()
+                .poll(&mut Context::from_waker(&second_waker)),
+            Poll::Ready(1)
+        );
+    }
+
+    #[test]
+    fn threads() {
+        const CONSUMERS: usize = 4;
+        const N: usize = 4000;
+
+        let queue = Arc::new(AsyncQueue::new());
+
+        let consumers = (0..CONSUMERS)
+            .map(|_| {
+                let queue = queue.clone();
+                thread::spawn(move || {
+                    let mut sum = 0;
+                    // None is the stop signal
+                    while let Some(elem) = block_on(queue.pop_async()) {
+                        sum += elem;
+                    }
+                    sum
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for i in 0..N {
+            queue.push(Some(i));
+        }
+        for _ in 0..CONSUMERS {
+            queue.push(None);
+        }
+
+        let sum: usize = consumers.into_iter().map(|c| c.join().unwrap()).sum();
+        assert_eq!(sum, N * (N - 1) / 2);
+    }
+}
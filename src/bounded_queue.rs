@@ -0,0 +1,278 @@
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+
+use crate::fifth::List;
+
+// SyncQueue with a limit: a bounded fifth::List (see fifth::List::bounded) behind a Mutex.
+// Producers wait on not_full while the queue holds max_len elements, so a slow consumer
+// slows the producers down, instead of letting the queue grow without end (backpressure).
+// Consumers wait on not_empty, like in SyncQueue.
+//
+// Any number of threads can send and receive, the usual setup is many producers and
+// one consumer (MPSC). close() is the way to stop it: no more sends are accepted,
+// everyone waiting is woken up, and consumers get what's left, then None.
+pub struct BoundedQueue<T> {
+    inner: Mutex<Inner<T>>,
+    not_empty: Condvar,
+    not_full: Condvar,
+}
+
+struct Inner<T> {
+    list: List<T>,
+    closed: bool,
+}
+
+// Element comes back in both cases, the caller decides what to do with it
+#[derive(Debug, PartialEq, Eq)]
+pub enum TrySendError<T> {
+    Full(T),
+    Closed(T),
+}
+
+impl<T> BoundedQueue<T> {
+    // With max_len 0 nothing could ever be sent
+    pub fn new(max_len: usize) -> Self {
+        assert!(max_len > 0, "queue has to fit at least one element");
+
+        BoundedQueue {
+            inner: Mutex::new(Inner {
+                list: List::bounded(max_len),
+                closed: false,
+            }),
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+        }
+    }
+
+    // Same as in SyncQueue, nothing here leaves the list half modified
+    fn lock(&self) -> MutexGuard<'_, Inner<T>> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().list.is_empty()
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.lock().closed
+    }
+
+    pub fn max_len(&self) -> usize {
+        self.lock().list.max_len().unwrap()
+    }
+
+    // Blocks while the queue is full. Err(elem) if the queue is closed, before or
+    // while waiting.
+    pub fn send(&self, elem: T) -> Result<(), T> {
+        let mut inner = self
+            .not_full
+            .wait_while(self.lock(), |inner| !inner.closed && inner.list.is_full())
+            .unwrap_or_else(PoisonError::into_inner);
+
+        if inner.closed {
+            return Err(elem);
+        }
+
+        inner.list.push(elem);
+        drop(inner);
+
+        self.not_empty.notify_one();
+        Ok(())
+    }
+
+    pub fn try_send(&self, elem: T) -> Result<(), TrySendError<T>> {
+        let mut inner = self.lock();
+        if inner.closed {
+            return Err(TrySendError::Closed(elem));
+        }
+
+        inner.list.try_push(elem).map_err(TrySendError::Full)?;
+        drop(inner);
+
+        self.not_empty.notify_one();
+        Ok(())
+    }
+
+    // Blocks while the queue is empty. None once it's closed and everything sent
+    // before that was received.
+    pub fn recv(&self) -> Option<T> {
+        let mut inner = self
+            .not_empty
+            .wait_while(self.lock(), |inner| !inner.closed && inner.list.is_empty())
+            .unwrap_or_else(PoisonError::into_inner);
+
+        let elem = inner.list.pop();
+        drop(inner);
+
+        if elem.is_some() {
+            self.not_full.notify_one();
+        }
+        elem
+    }
+
+    pub fn try_recv(&self) -> Option<T> {
+        let elem = self.lock().list.pop();
+
+        if elem.is_some() {
+            self.not_full.notify_one();
+        }
+        elem
+    }
+
+    // Stops all sends, wakes up all waiting producers (their send fails) and consumers
+    // (they drain the queue and get None). Closing twice does nothing.
+    pub fn close(&self) {
+        self.lock().closed = true;
+
+        self.not_full.notify_all();
+        self.not_empty.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BoundedQueue, TrySendError};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn basics() {
+        let queue = BoundedQueue::new(2);
+        assert_eq!(queue.max_len(), 2);
+        assert!(queue.is_empty());
+        assert_eq!(queue.try_recv(), None);
+
+        assert_eq!(queue.send(1), Ok(()));
+        assert_eq!(queue.try_send(2), Ok(()));
+        assert_eq!(queue.try_send(3), Err(TrySendError::Full(3)));
+        assert_eq!(queue.len(), 2);
+
+        assert_eq!(queue.recv(), Some(1));
+        assert_eq!(queue.try_send(3), Ok(()));
+
+        queue.close();
+        assert!(queue.is_closed());
+        assert_eq!(queue.send(4), Err(4));
+        assert_eq!(queue.try_send(4), Err(TrySendError::Closed(4)));
+
+        // What was sent before close is still there
+        assert_eq!(queue.recv(), Some(2));
+        assert_eq!(queue.try_recv(), Some(3));
+        assert_eq!(queue.recv(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one element")]
+    fn zero_max_len() {
+        BoundedQueue::<i32>::new(0);
+    }
+
+    #[test]
+    fn send_blocks_when_full() {
+        let queue = Arc::new(BoundedQueue::new(1));
+        queue.send(1).unwrap();
+
+        let producer = {
+            let queue = queue.clone();
+            thread::spawn(move || queue.send(2))
+        };
+
+        thread::sleep(Duration::from_millis(20));
+        // Producer is still waiting for a free slot
+        assert_eq!(queue.len(), 1);
+
+        assert_eq!(queue.recv(), Some(1));
+        assert_eq!(producer.join().unwrap(), Ok(()));
+        assert_eq!(queue.recv(), Some(2));
+    }
+
+    #[test]
+    fn close_wakes_everyone() {
+        let full = Arc::new(BoundedQueue::new(1));
+        full.send(0).unwrap();
+        let empty = Arc::new(BoundedQueue::<i32>::new(1));
+
+        let producers = (1..=3)
+            .map(|i| {
+                let full = full.clone();
+                thread::spawn(move || full.send(i))
+            })
+            .collect::<Vec<_>>();
+        let consumers = (0..3)
+            .map(|_| {
+                let empty = empty.clone();
+                thread::spawn(move || empty.recv())
+            })
+            .collect::<Vec<_>>();
+
+        thread::sleep(Duration::from_millis(20));
+        full.close();
+        empty.close();
+
+        let mut failed = producers
+            .into_iter()
+            .map(|p| p.join().unwrap().unwrap_err())
+            .collect::<Vec<_>>();
+        failed.sort();
+        assert_eq!(failed, [1, 2, 3]);
+
+        for consumer in consumers {
+            assert_eq!(consumer.join().unwrap(), None);
+        }
+    }
+
+    #[test]
+    fn mpsc() {
+        const PRODUCERS: usize = 4;
+        const PER_PRODUCER: usize = 1000;
+        const MAX_LEN: usize = 8;
+
+        let queue = Arc::new(BoundedQueue::new(MAX_LEN));
+
+        let producers = (0..PRODUCERS)
+            .map(|p| {
+                let queue = queue.clone();
+                thread::spawn(move || {
+                    for i in 0..PER_PRODUCER {
+                        queue.send(p * PER_PRODUCER + i).unwrap();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let consumer = {
+            let queue = queue.clone();
+            thread::spawn(move || {
+                let mut received = Vec::new();
+                while let Some(elem) = queue.recv() {
+                    assert!(queue.len() <= MAX_LEN);
+                    received.push(elem);
+                }
+                received
+            })
+        };
+
+        for producer in producers {
+            producer.join().unwrap();
+        }
+        queue.close();
+
+        let mut received = consumer.join().unwrap();
+        // Every producer's elements come in the order they were sent
+        for p in 0..PRODUCERS {
+            let range = p * PER_PRODUCER..(p + 1) * PER_PRODUCER;
+            assert!(received
+                .iter()
+                .filter(|elem| range.contains(elem))
+                .copied()
+                .eq(range.clone()));
+        }
+
+        received.sort();
+        assert!(received.into_iter().eq(0..PRODUCERS * PER_PRODUCER));
+    }
+}
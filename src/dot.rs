@@ -0,0 +1,99 @@
+// Graphviz export, to see how the nodes are actually linked, not just the elements:
+// std::fs::write("list.dot", list.to_dot()), then `dot -Tsvg list.dot > list.svg`
+//
+// Graph nodes are named after the addresses of list nodes, so a node shows up once, no matter
+// how many links point at it. That's what makes sharing in third.rs visible - two lists
+// with a common tail are drawn as two arrows into the same chain.
+use alloc::string::String;
+use core::fmt::{self, Debug, Write};
+
+pub(crate) struct Dot {
+    out: String,
+}
+
+impl Dot {
+    pub(crate) fn new() -> Self {
+        Dot {
+            out: String::from("digraph list {\n    rankdir=LR;\n    node [shape=box];\n"),
+        }
+    }
+
+    // Something that points into the list, but is not a node: head, tail
+    pub(crate) fn root<N>(&mut self, name: &str, node: Option<*const N>) {
+        self.write(format_args!("    \"{}\" [shape=plaintext];\n", name));
+        if let Some(node) = node {
+            self.write(format_args!("    \"{}\" -> {};\n", name, Id(node)));
+        }
+    }
+
+    // List node with elem as its label, note goes in the second line (reference count etc.)
+    pub(crate) fn node<N>(&mut self, node: *const N, elem: &dyn Debug, note: &str) {
+        self.write(format_args!("    {} [label=\"", Id(node)));
+        write!(Escape(&mut self.out), "{:?}", elem).expect("Debug impl returned an error");
+        if !note.is_empty() {
+            self.out.push_str("\\n");
+            self.out.push_str(note);
+        }
+        self.out.push_str("\"];\n");
+    }
+
+    // attrs are Graphviz edge attributes, like style=dashed, or empty
+    pub(crate) fn edge<N>(&mut self, from: *const N, to: *const N, attrs: &str) {
+        self.write(format_args!("    {} -> {}", Id(from), Id(to)));
+        if !attrs.is_empty() {
+            self.write(format_args!(" [{}]", attrs));
+        }
+        self.out.push_str(";\n");
+    }
+
+    pub(crate) fn finish(mut self) -> String {
+        self.out.push_str("}\n");
+        self.out
+    }
+
+    fn write(&mut self, args: fmt::Arguments) {
+        // Writing to a String never fails
+        self.out.write_fmt(args).unwrap();
+    }
+}
+
+struct Id<N>(*const N);
+
+impl<N> fmt::Display for Id<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "n{:p}", self.0)
+    }
+}
+
+// Labels are in quotes, so quotes (and backslashes) coming from elements need escaping
+struct Escape<'a>(&'a mut String);
+
+impl Write for Escape<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            if c == '"' || c == '\\' {
+                self.0.push('\\');
+            }
+            self.0.push(c);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Dot;
+
+    #[test]
+    fn labels_are_escaped() {
+        let node = 1;
+        let mut dot = Dot::new();
+        dot.root("head", Some(&node as *const i32));
+        dot.node(&node, &"say \"hi\"", "");
+
+        let out = dot.finish();
+        assert!(out.starts_with("digraph list {"));
+        assert!(out.ends_with("}\n"));
+        assert!(out.contains(r#"[label="\"say \\\"hi\\\"\""];"#));
+    }
+}
@@ -1,26 +1,325 @@
-use std::ptr::{self, null_mut};
+use alloc::string::String;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::iter::{FromIterator, FusedIterator};
+use core::marker::PhantomData;
+use core::ptr::NonNull;
 
-pub struct List<T> {
+use crate::allocator::{self, Allocator, Global};
+use crate::dot::Dot;
+use crate::traits::Queue;
+
+// First version mixed Box links with a raw tail pointer:
+// head: Option<Box<Node<T>>>,
+// tail: *mut Node<T>,
+// That is broken under stacked borrows (run `cargo +nightly miri test`) - Box asserts
+// unique ownership of the node, so each time we touched the head through the Box
+// (pop, peek, iter), the raw tail pointer derived earlier got invalidated, and
+// writing through it later was UB, even though it "worked".
+//
+// Rule of thumb: once you start using raw pointers, use ONLY raw pointers.
+// So all links are raw now, Box is used only to allocate (Box::into_raw) and
+// free (Box::from_raw) a node.
+//
+// Safety argument:
+// - every node is allocated by push via Box::into_raw, and freed exactly once,
+//   by pop via Box::from_raw, after it was unlinked from the list (both with alloc,
+//   see allocator.rs)
+// - head and tail are both None, or both Some, tail is the last node reachable from head
+// - references (&/&mut) to nodes are created only for as long as the borrow of
+//   the list they came from (peek, iter), and never while we write through raw pointers
+pub struct List<T, A: Allocator = Global> {
     head: Link<T>,
-    tail: *mut Node<T>, // NEW!
+    tail: Link<T>,
+    len: usize,
+    alloc: A,
+    // Most elements try_push lets in, None for no limit, see bounded_in
+    max_len: Option<usize>,
+    // The list owns Ts, but only behind raw pointers, which say nothing about ownership.
+    // The marker tells the compiler that dropping the list drops Ts (drop check: a list
+    // of &'a X can't outlive the X), and keeps List covariant in T, like Box<T> and Vec<T>
+    // (NonNull is covariant too, a *mut tail would have made the list invariant).
+    _marker: PhantomData<T>,
 }
 
-type Link<T> = Option<Box<Node<T>>>;
+// NonNull is *mut T, which is never null, so Option<NonNull<T>> has the same
+// size as a raw pointer, None plays the role of null
+type Link<T> = Option<NonNull<Node<T>>>;
 
 struct Node<T> {
     elem: T,
     next: Link<T>,
 }
 
+// Raw pointers are not Send, so the list isn't either, but all nodes are owned by the list
+// and are never shared with anyone, moving the whole list to another thread is fine,
+// as long as elements can be moved (same reasoning as for Box<T>)
+unsafe impl<T: Send, A: Allocator + Send> Send for List<T, A> {}
+
+// &List gives out only &T (peek, iter), so sharing it between threads is fine
+// when sharing &T is, again like Box<T>
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for List<T, A> {}
+
+// What the compiler checks for us is in tests/ui, see tests/compile_fail.rs
+
+impl<T, A: Allocator + Default> Default for List<T, A> {
+    fn default() -> Self {
+        Self::new_in(A::default())
+    }
+}
+
+// Elements only, [1, 2, 3], same as second::List
+impl<T: fmt::Debug, A: Allocator> fmt::Debug for List<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T: proptest::arbitrary::Arbitrary> proptest::arbitrary::Arbitrary for List<T> {
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <T as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = crate::arbitrary::ListStrategy<T, Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::arbitrary::list_strategy(args, |elems| {
+            let mut list = List::new();
+            for elem in elems {
+                list.push(elem);
+            }
+            list
+        })
+    }
+}
+
+impl<T, A: Allocator> Extend<T> for List<T, A> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push(elem);
+        }
+    }
+}
+
+impl<T, A: Allocator + Default> FromIterator<T> for List<T, A> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = List::new_in(A::default());
+        list.extend(iter);
+        list
+    }
+}
+
+// From references to Copy elements, same as second::List
+impl<'a, T: Copy + 'a, A: Allocator> Extend<&'a T> for List<T, A> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<'a, T: Copy + 'a, A: Allocator + Default> FromIterator<&'a T> for List<T, A> {
+    fn from_iter<I: IntoIterator<Item = &'a T>>(iter: I) -> Self {
+        iter.into_iter().copied().collect()
+    }
+}
+
+impl<T: fmt::Debug, A: Allocator> List<T, A> {
+    // Graphviz graph of the nodes, see dot.rs
+    pub fn to_dot(&self) -> String {
+        let mut dot = Dot::new();
+        let mut cur = self.head;
+
+        dot.root(
+            "head",
+            self.head.map(|node| node.as_ptr() as *const Node<T>),
+        );
+        dot.root(
+            "tail",
+            self.tail.map(|node| node.as_ptr() as *const Node<T>),
+        );
+        while let Some(node) = cur {
+            // Nodes live as long as the list, and &self keeps it from changing
+            let node = unsafe { node.as_ref() };
+            dot.node(node, &node.elem, "");
+            if let Some(next) = node.next {
+                dot.edge(node, next.as_ptr(), "");
+            }
+            cur = node.next;
+        }
+
+        dot.finish()
+    }
+}
+
+// Comparisons and hash go over elements with iter, same as in second.rs
+impl<T: PartialEq, A: Allocator> PartialEq for List<T, A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq, A: Allocator> Eq for List<T, A> {}
+
+impl<T: PartialOrd, A: Allocator> PartialOrd for List<T, A> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T: Ord, A: Allocator> Ord for List<T, A> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<T: Hash, A: Allocator> Hash for List<T, A> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        for elem in self.iter() {
+            elem.hash(state);
+        }
+    }
+}
+
 impl<T> List<T> {
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
+        List::new_in(Global)
+    }
+
+    pub const fn bounded(max_len: usize) -> Self {
+        List::bounded_in(max_len, Global)
+    }
+
+    // Nodes of other were allocated by other's allocator, and we are going to free them
+    // with ours. For Global it's the same thing, for any other allocator we can't tell
+    // (two bump arenas have the same type), so append is only for Global lists.
+    //
+    // Since we know where the tail is, other list can be hooked up in O(1)
+    pub fn append(&mut self, mut other: List<T>) {
+        // Steal nodes from the other list, so it does not own them anymore
+        let (other_head, other_tail) = match (other.head.take(), other.tail.take()) {
+            (Some(head), Some(tail)) => (head, tail),
+            // Nothing to append, don't touch our tail
+            _ => return,
+        };
+
+        match self.tail {
+            Some(tail) => unsafe {
+                (*tail.as_ptr()).next = Some(other_head);
+            },
+            None => self.head = Some(other_head),
+        }
+
+        self.tail = Some(other_tail);
+        self.len += other.len;
+    }
+}
+
+impl<T, A: Allocator> List<T, A> {
+    pub const fn new_in(alloc: A) -> Self {
         List {
             head: None,
-            tail: null_mut(),
+            tail: None,
+            len: 0,
+            alloc,
+            max_len: None,
+            _marker: PhantomData,
+        }
+    }
+
+    // Queue used as a bounded buffer, same as second::List::bounded_in: try_push gives
+    // the element back once there are max_len of them, push and extend don't check
+    pub const fn bounded_in(max_len: usize, alloc: A) -> Self {
+        List {
+            head: None,
+            tail: None,
+            len: 0,
+            alloc,
+            max_len: Some(max_len),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    // Node per element, see heap_size.rs
+    pub fn heap_size_in_bytes(&self) -> usize {
+        self.len * core::mem::size_of::<Node<T>>()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn max_len(&self) -> Option<usize> {
+        self.max_len
+    }
+
+    pub fn is_full(&self) -> bool {
+        matches!(self.max_len, Some(max_len) if self.len >= max_len)
+    }
+
+    // Floyd's tortoise and hare: hare goes two nodes for every one of the tortoise. If nodes
+    // form a cycle, the hare comes round and meets the tortoise, otherwise it falls off
+    // the end. O(n), O(1) memory, doesn't trust len (debug_validate does, it counts nodes
+    // up to len). A healthy list never has one, it's for tests of code that relinks nodes.
+    pub fn has_cycle(&self) -> bool {
+        // Nodes are alive as long as the list, cycle or not
+        let next = |node: NonNull<Node<T>>| unsafe { (*node.as_ptr()).next };
+        let mut slow = self.head;
+        let mut fast = self.head;
+
+        while let Some(node) = fast {
+            fast = match next(node) {
+                Some(node) => next(node),
+                None => return false,
+            };
+            slow = slow.and_then(next);
+
+            if fast.is_some() && fast == slow {
+                return true;
+            }
         }
+
+        false
     }
 
+    // Walks the whole list and panics if it's broken: head and tail disagree, tail is not
+    // the last node, nodes form a cycle, or len is off. O(n), it's meant for tests and fuzzers -
+    // call it after every operation, and the first failing one is the one which broke the list.
+    // Does nothing in release builds.
+    pub fn debug_validate(&self) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
 
+        assert_eq!(
+            self.head.is_none(),
+            self.tail.is_none(),
+            "only one of head and tail is set"
+        );
+
+        let mut count = 0;
+        let mut last = None;
+        let mut cur = self.head;
+        while let Some(node) = cur {
+            count += 1;
+            // A cycle would go on forever, stop as soon as there are more nodes than len
+            assert!(
+                count <= self.len,
+                "more than len = {} nodes, cycle?",
+                self.len
+            );
+            last = Some(node);
+            cur = unsafe { (*node.as_ptr()).next };
+        }
+
+        assert_eq!(count, self.len, "len doesn't match the number of nodes");
+        assert_eq!(last, self.tail, "tail is not the last node");
+    }
 
     // Lets have push implementation like this:
     // pub fn push(&'a mut self, elem: T) {
@@ -44,7 +343,7 @@ impl<T> List<T> {
     // In impl declaration, but not in push method:
     // tail: Option<&'a mut Node<T>>,
     // impl<'a, T> List<'a, T> {
-    // pub fn push(& mut self, elem: T)  
+    // pub fn push(& mut self, elem: T)
     // Then:
     // Compiler is unable to infer lifetime for this line:
     // old_tail.next.as_deref_mut()
@@ -64,76 +363,734 @@ impl<T> List<T> {
     // so we have two mutable references with lifetime of &self - that's where compiler explodes, you cannot have two
     // I think ref cells could help here, since they check borrowing at runtime
     pub fn push(&mut self, elem: T) {
-        let mut new_tail = Box::new(Node {
-            elem: elem,
-            next: None,
-        });
-    
-        // Type needs to be specified, otherwise compiler understands:
-        // &mut *new_tail;
-        // as: * -> deref a box, returns T
-        // &mut do a mut borrow to T
-        // but we want a pointer!
-        let raw_tail: *mut _ = &mut *new_tail;
-    
-        // .is_null checks for null, equivalent to checking for None
-        if !self.tail.is_null() {
+        // Box is used only to allocate memory, into_raw gives up ownership, so
+        // no one claims that node is uniquely owned anymore
+        let new_tail = allocator::into_raw(Node { elem, next: None }, &self.alloc);
+
+        match self.tail {
             // If the old tail existed, update it to point to the new tail
-            unsafe {
-                (*self.tail).next = Some(new_tail);
-            }
-        } else {
+            Some(old_tail) => unsafe {
+                (*old_tail.as_ptr()).next = Some(new_tail);
+            },
             // Otherwise, update the head to point to it
-            self.head = Some(new_tail);
+            None => self.head = Some(new_tail),
+        }
+
+        self.tail = Some(new_tail);
+        self.len += 1;
+    }
+
+    pub fn try_push(&mut self, elem: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(elem);
         }
-    
-        self.tail = raw_tail;
+
+        self.push(elem);
+        Ok(())
     }
-    
 
     pub fn pop(&mut self) -> Option<T> {
-        self.head.take().map(|head| {
-            let head = *head;
+        self.head.map(|head| unsafe {
+            // Take back the ownership, memory is freed right away
+            let head = allocator::from_raw(head, &self.alloc);
             self.head = head.next;
 
             if self.head.is_none() {
-                self.tail = ptr::null_mut();
+                self.tail = None;
             }
 
+            self.len -= 1;
             head.elem
         })
     }
 }
 
+impl<T, A: Allocator> List<T, A> {
+    // Reference is bound to the borrow of self, so no one can push or pop
+    // while it's alive
+    pub fn peek(&self) -> Option<&T> {
+        self.head.map(|node| unsafe { &(*node.as_ptr()).elem })
+    }
+
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self.head.map(|node| unsafe { &mut (*node.as_ptr()).elem })
+    }
+}
+
+impl<T, A: Allocator> Queue<T> for List<T, A> {
+    fn enqueue(&mut self, elem: T) {
+        self.push(elem);
+    }
+
+    fn dequeue(&mut self) -> Option<T> {
+        self.pop()
+    }
+
+    fn front(&self) -> Option<&T> {
+        self.peek()
+    }
+}
+
+impl<T, A: Allocator> Drop for List<T, A> {
+    fn drop(&mut self) {
+        // pop unlinks nodes one by one, so there is no recursive drop of the next links
+        while self.pop().is_some() {}
+    }
+}
+
+pub struct IntoIter<T, A: Allocator = Global>(List<T, A>);
+
+impl<T, A: Allocator> IntoIterator for List<T, A> {
+    type Item = T;
+    type IntoIter = IntoIter<T, A>;
+
+    fn into_iter(self) -> IntoIter<T, A> {
+        IntoIter(self)
+    }
+}
+
+impl<T, A: Allocator> Iterator for IntoIter<T, A> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T, A: Allocator> ExactSizeIterator for IntoIter<T, A> {}
+
+impl<T, A: Allocator> FusedIterator for IntoIter<T, A> {}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize, A: Allocator> serde::Serialize for List<T, A> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::serde_seq::serialize_seq(serializer, self.len, self.iter())
+    }
+}
+
+// push appends at the tail already, O(1)
+#[cfg(feature = "serde")]
+impl<'de, T, A> serde::Deserialize<'de> for List<T, A>
+where
+    T: serde::Deserialize<'de>,
+    A: Allocator + Default,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_seq::deserialize_seq(deserializer, "a queue", |elems| {
+            let mut list = List::new_in(A::default());
+            elems.for_each(|elem| list.push(elem));
+            list
+        })
+    }
+}
+
+// Rayon support, done the same way as for second::List, see there
+#[cfg(feature = "rayon")]
+impl<'a, T: Sync + 'a, A: Allocator> rayon::iter::IntoParallelIterator for &'a List<T, A> {
+    type Item = &'a T;
+    type Iter = rayon::vec::IntoIter<&'a T>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        self.iter().collect::<alloc::vec::Vec<_>>().into_par_iter()
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<'a, T: Send + 'a, A: Allocator> rayon::iter::IntoParallelIterator for &'a mut List<T, A> {
+    type Item = &'a mut T;
+    type Iter = rayon::vec::IntoIter<&'a mut T>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        self.iter_mut()
+            .collect::<alloc::vec::Vec<_>>()
+            .into_par_iter()
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T: Send, A: Allocator> rayon::iter::IntoParallelIterator for List<T, A> {
+    type Item = T;
+    type Iter = rayon::vec::IntoIter<T>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        self.into_iter()
+            .collect::<alloc::vec::Vec<_>>()
+            .into_par_iter()
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T: Send, A: Allocator + Default> rayon::iter::FromParallelIterator<T> for List<T, A> {
+    fn from_par_iter<I>(par_iter: I) -> Self
+    where
+        I: rayon::iter::IntoParallelIterator<Item = T>,
+    {
+        let elems: alloc::vec::Vec<T> = rayon::iter::FromParallelIterator::from_par_iter(par_iter);
+        let mut list = List::new_in(A::default());
+        elems.into_iter().for_each(|elem| list.push(elem));
+        list
+    }
+}
+
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+    // Nodes left
+    len: usize,
+}
+
+impl<T, A: Allocator> List<T, A> {
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.map(|node| unsafe { &*node.as_ptr() }),
+            len: self.len,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| {
+            self.next = node.next.map(|node| unsafe { &*node.as_ptr() });
+            self.len -= 1;
+            &node.elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> FusedIterator for Iter<'a, T> {}
+
+pub struct IterMut<'a, T> {
+    next: Option<&'a mut Node<T>>,
+    len: usize,
+}
+
+impl<T, A: Allocator> List<T, A> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            next: self.head.map(|node| unsafe { &mut *node.as_ptr() }),
+            len: self.len,
+        }
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.take().map(|node| {
+            self.next = node.next.map(|node| unsafe { &mut *node.as_ptr() });
+            self.len -= 1;
+            &mut node.elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+
+impl<'a, T> FusedIterator for IterMut<'a, T> {}
+
 #[cfg(test)]
 mod test {
     use super::List;
+
     #[test]
     fn basics() {
         let mut list = List::new();
 
         // Check empty list behaves right
-        // assert_eq!(list.pop(), None);
+        assert_eq!(list.pop(), None);
 
         // Populate list
         list.push(1);
         list.push(2);
-        // list.push(3);
+        list.push(3);
+
+        // Check normal removal
+        assert_eq!(list.pop(), Some(1));
+        assert_eq!(list.pop(), Some(2));
+
+        // Push some more just to make sure nothing's corrupted
+        list.push(4);
+        list.push(5);
 
-        // // Check normal removal
-        // assert_eq!(list.pop(), Some(1));
-        // assert_eq!(list.pop(), Some(2));
+        // Check normal removal
+        assert_eq!(list.pop(), Some(3));
+        assert_eq!(list.pop(), Some(4));
 
-        // // Push some more just to make sure nothing's corrupted
-        // list.push(4);
-        // list.push(5);
+        // Check exhaustion
+        assert_eq!(list.pop(), Some(5));
+        assert_eq!(list.pop(), None);
 
-        // // Check normal removal
-        // assert_eq!(list.pop(), Some(3));
-        // assert_eq!(list.pop(), Some(4));
+        // Check the exhaustion case fixed the pointer right
+        list.push(6);
+        list.push(7);
 
-        // // Check exhaustion
-        // assert_eq!(list.pop(), Some(5));
-        // assert_eq!(list.pop(), None);
+        // Check normal removal
+        assert_eq!(list.pop(), Some(6));
+        assert_eq!(list.pop(), Some(7));
+        assert_eq!(list.pop(), None);
+    }
+
+    #[test]
+    fn into_iter() {
+        let mut list = List::new();
+        list.push(1);
+        list.push(2);
+        list.push(3);
+
+        let mut iter = list.into_iter();
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.next(), Some(2));
+        assert_eq!(iter.next(), Some(3));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn iter() {
+        let mut list = List::new();
+        list.push(1);
+        list.push(2);
+        list.push(3);
+
+        let mut iter = list.iter();
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next(), Some(&2));
+        assert_eq!(iter.next(), Some(&3));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn iter_mut() {
+        let mut list = List::new();
+        list.push(1);
+        list.push(2);
+        list.push(3);
+
+        let mut iter = list.iter_mut();
+        assert_eq!(iter.next(), Some(&mut 1));
+        assert_eq!(iter.next(), Some(&mut 2));
+        assert_eq!(iter.next(), Some(&mut 3));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn peek() {
+        let mut list = List::new();
+        assert_eq!(list.peek(), None);
+        assert_eq!(list.peek_mut(), None);
+
+        list.push(1);
+        list.push(2);
+
+        assert_eq!(list.peek(), Some(&1));
+        if let Some(value) = list.peek_mut() {
+            *value = 10;
+        }
+        assert_eq!(list.peek(), Some(&10));
+        assert_eq!(list.pop(), Some(10));
+        assert_eq!(list.peek(), Some(&2));
+    }
+
+    #[test]
+    fn break_the_stack() {
+        {
+            let mut list = List::new();
+
+            for i in 1..100000 {
+                list.push(i);
+            }
+            println!("Leaving, call dtor");
+        }
+
+        println!("Still alive!");
+    }
+
+    // Mixes all the operations, where reference to the head (from peek, or iterators)
+    // and raw tail pointer are used one after another. This is the place where
+    // stacked borrows are violated if references are created from the raw pointer
+    // carelessly - run it under `cargo +nightly miri test`
+    #[test]
+    fn miri_food() {
+        let mut list = List::new();
+
+        list.push(1);
+        list.push(2);
+        list.push(3);
+
+        assert_eq!(list.pop(), Some(1));
+        list.push(4);
+        assert_eq!(list.pop(), Some(2));
+        list.push(5);
+
+        assert_eq!(list.peek(), Some(&3));
+        list.push(6);
+        if let Some(x) = list.peek_mut() {
+            *x *= 10;
+        }
+        assert_eq!(list.peek(), Some(&30));
+        assert_eq!(list.pop(), Some(30));
+
+        for elem in list.iter_mut() {
+            *elem *= 100;
+        }
+
+        let mut iter = list.iter();
+        assert_eq!(iter.next(), Some(&400));
+        assert_eq!(iter.next(), Some(&500));
+        assert_eq!(iter.next(), Some(&600));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next(), None);
+
+        assert_eq!(list.pop(), Some(400));
+        if let Some(x) = list.peek_mut() {
+            *x *= 10;
+        }
+        assert_eq!(list.peek(), Some(&5000));
+        list.push(7);
+
+        // Drop it on the ground and let the dtor exercise itself
+    }
+
+    #[test]
+    fn append() {
+        let mut list = List::new();
+        let mut other = List::new();
+
+        // Both empty
+        list.append(List::new());
+        assert_eq!(list.pop(), None);
+
+        other.push(1);
+        other.push(2);
+
+        // Append to empty list, tail has to be taken from other
+        list.append(other);
+        list.push(3);
+
+        let mut other = List::new();
+        other.push(4);
+        other.push(5);
+        list.append(other);
+
+        // Appending empty list keeps the tail
+        list.append(List::new());
+        list.push(6);
+
+        assert_eq!(list.pop(), Some(1));
+        assert_eq!(list.pop(), Some(2));
+        assert_eq!(list.pop(), Some(3));
+        assert_eq!(list.pop(), Some(4));
+        assert_eq!(list.pop(), Some(5));
+        assert_eq!(list.pop(), Some(6));
+        assert_eq!(list.pop(), None);
+    }
+
+    // Counts live allocations, to check that every node goes back to the allocator
+    // it came from
+    #[cfg(feature = "allocator_api")]
+    #[test]
+    fn custom_allocator() {
+        use std::alloc::{AllocError, Allocator, Global, Layout};
+        use std::cell::Cell;
+        use std::ptr::NonNull;
+
+        #[derive(Default)]
+        struct Counting {
+            live: Cell<usize>,
+        }
+
+        unsafe impl Allocator for &Counting {
+            fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+                self.live.set(self.live.get() + 1);
+                Global.allocate(layout)
+            }
+
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                self.live.set(self.live.get() - 1);
+                Global.deallocate(ptr, layout)
+            }
+        }
+
+        let counting = Counting::default();
+
+        {
+            let mut list = List::new_in(&counting);
+            for i in 0..10 {
+                list.push(i);
+            }
+            assert_eq!(counting.live.get(), 10);
+
+            assert_eq!(list.pop(), Some(0));
+            assert_eq!(counting.live.get(), 9);
+            assert!(list.iter().copied().eq(1..10));
+        }
+
+        assert_eq!(counting.live.get(), 0);
+
+        // Bump arena frees everything at once, when it's dropped
+        let bump = bumpalo::Bump::new();
+        let mut list = List::new_in(&bump);
+        for i in 0..10 {
+            list.push(i);
+        }
+        assert!(bump.allocated_bytes() > 0);
+        assert!(list.into_iter().eq(0..10));
+    }
+
+    #[test]
+    fn heap_size() {
+        let mut list = List::new();
+        list.push(1u64);
+        list.push(2);
+        list.push(3);
+        // u64 and the next link
+        assert_eq!(list.heap_size_in_bytes(), 3 * 16);
+        list.pop();
+        assert_eq!(list.heap_size_in_bytes(), 2 * 16);
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn rayon() {
+        use rayon::prelude::*;
+
+        let mut list: List<u64> = (0..10_000).collect();
+        list.par_iter_mut().for_each(|x| *x += 1);
+        assert_eq!(list.par_iter().sum::<u64>(), (1..=10_000).sum::<u64>());
+
+        let list: List<u64> = list.into_par_iter().map(|x| x * 3).collect();
+        assert!(list.into_iter().eq((1..=10_000).map(|x| x * 3)));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() {
+        let mut list = List::new();
+        list.push(1);
+        list.push(2);
+        list.push(3);
+
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(json, "[1,2,3]");
+
+        let mut list = serde_json::from_str::<List<i32>>(&json).unwrap();
+        assert_eq!(list.pop(), Some(1));
+        list.push(4);
+        assert!(list.into_iter().eq(vec![2, 3, 4]));
+    }
+
+    #[test]
+    fn len_and_exact_size_iterators() {
+        let mut list = List::new();
+        assert!(list.is_empty());
+
+        for i in 0..5 {
+            list.push(i);
+        }
+        list.pop();
+        assert_eq!(list.len(), 4);
+
+        let mut other = List::new();
+        other.push(10);
+        list.append(other);
+        assert_eq!(list.len(), 5);
+
+        let mut iter = list.iter();
+        iter.next();
+        assert_eq!(iter.len(), 4);
+        assert_eq!(list.iter_mut().len(), 5);
+
+        let mut iter = list.into_iter();
+        iter.next();
+        assert_eq!(iter.size_hint(), (4, Some(4)));
+    }
+
+    #[test]
+    fn eq_ord_hash() {
+        use std::collections::HashSet;
+
+        let queue = |elems: &[i32]| {
+            let mut list = List::new();
+            elems.iter().for_each(|elem| list.push(*elem));
+            list
+        };
+
+        assert!(queue(&[1, 2]) == queue(&[1, 2]));
+        assert!(queue(&[1, 2]) != queue(&[1]));
+        assert!(queue(&[1, 2]) < queue(&[2]));
+        assert!(queue(&[]) < queue(&[1]));
+
+        let set: HashSet<_> = vec![queue(&[1]), queue(&[1]), queue(&[1, 2])]
+            .into_iter()
+            .collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn debug() {
+        let mut list = List::new();
+        list.push(1);
+        list.push(2);
+        assert_eq!(format!("{:?}", list), "[1, 2]");
+    }
+
+    #[test]
+    fn to_dot() {
+        let mut list = List::new();
+        list.push(1);
+        list.push(2);
+
+        let dot = list.to_dot();
+        // head, tail, 1 -> 2
+        assert_eq!(dot.matches("->").count(), 3);
+        assert!(dot.contains("[label=\"2\"]"));
+    }
+
+    #[test]
+    fn debug_validate() {
+        let mut list = List::new();
+        list.debug_validate();
+
+        for i in 0..10 {
+            list.push(i);
+            list.debug_validate();
+        }
+        for _ in 0..5 {
+            list.pop();
+            list.debug_validate();
+        }
+
+        let mut other = List::new();
+        other.push(10);
+        list.append(other);
+        list.debug_validate();
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "tail is not the last node")]
+    fn debug_validate_catches_bad_tail() {
+        let mut list = List::new();
+        list.push(1);
+        list.push(2);
+        list.tail = list.head;
+        list.debug_validate();
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "cycle?")]
+    fn debug_validate_catches_cycle() {
+        let mut list = List::new();
+        list.push(1);
+        list.push(2);
+        // 1 -> 2 -> 1 -> ...
+        unsafe { (*list.tail.unwrap().as_ptr()).next = list.head };
+        let result =
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| list.debug_validate()));
+        // Break the cycle again, or drop would go round forever
+        unsafe { (*list.tail.unwrap().as_ptr()).next = None };
+        std::panic::resume_unwind(result.unwrap_err());
+    }
+
+    #[test]
+    fn from_iter_and_extend() {
+        let mut list: List<_> = (1..4).collect();
+        list.extend(vec![4, 5]);
+        list.debug_validate();
+
+        assert_eq!(list.len(), 5);
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn variance_and_auto_traits() {
+        // Compiles only if List is covariant in T: a list of longer lived
+        // references can be used as a list of shorter lived ones
+        fn shorten<'a>(list: List<&'static str>) -> List<&'a str> {
+            list
+        }
+
+        let mut list = List::new();
+        list.push("static");
+        let local = String::from("local");
+        let mut list = shorten(list);
+        list.push(&local);
+        assert_eq!(list.pop(), Some("static"));
+
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<List<i32>>();
+        assert_send_sync::<List<String>>();
+        // And what must not compile is in tests/ui
+    }
+
+    #[test]
+    fn bounded() {
+        let mut list = List::bounded(2);
+        assert_eq!(list.max_len(), Some(2));
+        assert_eq!(list.try_push(1), Ok(()));
+        assert_eq!(list.try_push(2), Ok(()));
+        assert!(list.is_full());
+        assert_eq!(list.try_push(3), Err(3));
+
+        assert_eq!(list.pop(), Some(1));
+        assert_eq!(list.try_push(3), Ok(()));
+        assert!(list.iter().copied().eq([2, 3]));
+        list.debug_validate();
+
+        let mut list = List::new();
+        assert_eq!(list.max_len(), None);
+        for i in 0..100 {
+            assert_eq!(list.try_push(i), Ok(()));
+        }
+        assert!(!list.is_full());
+    }
+
+    #[test]
+    fn has_cycle() {
+        let mut list = List::new();
+        assert!(!list.has_cycle());
+
+        for i in 0..5 {
+            list.push(i);
+            assert!(!list.has_cycle());
+        }
+
+        let second = unsafe { (*list.head.unwrap().as_ptr()).next };
+        // 0 -> 1 -> 2 -> 3 -> 4 -> 1 -> ...
+        unsafe { (*list.tail.unwrap().as_ptr()).next = second };
+        assert!(list.has_cycle());
+
+        // Node pointing at itself
+        unsafe { (*list.tail.unwrap().as_ptr()).next = list.tail };
+        assert!(list.has_cycle());
+
+        // Break it again, or drop would go round forever
+        unsafe { (*list.tail.unwrap().as_ptr()).next = None };
+        assert!(!list.has_cycle());
+        list.debug_validate();
+    }
+
+    #[test]
+    fn extend_and_collect_copied() {
+        let mut list: List<i32> = [1, 2].iter().collect();
+        list.extend(&[3, 4]);
+        assert!(list.into_iter().eq(1..=4));
     }
 }
@@ -1,30 +1,44 @@
-use std::mem;
+use alloc::boxed::Box;
+use alloc::string::String;
+use core::fmt;
+use core::mem;
 
+use crate::dot::Dot;
+use crate::traits::Stack;
+
+// Started as an i32 only stack, generic now - nothing in here cares what the element is,
+// it's only moved in and out of the nodes.
 #[derive(PartialEq, Debug)]
-pub struct List {
-    head: Link,
+pub struct List<T> {
+    head: Link<T>,
 }
 #[derive(PartialEq, Debug)]
-struct Node {
-    elem: i32,
-    next: Link,
+struct Node<T> {
+    elem: T,
+    next: Link<T>,
 }
 #[derive(PartialEq, Debug)]
-enum Link {
+enum Link<T> {
     Empty,
-    More(Box<Node>),
+    More(Box<Node<T>>),
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-impl List {
-    pub fn new() -> Self {
+impl<T> List<T> {
+    pub const fn new() -> Self {
         List { head: Link::Empty }
     }
 
-    pub fn push(&mut self, elem: i32) {
+    pub fn push(&mut self, elem: T) {
         // head -> Node
         // new_node -> Node, head -> Empty
         let new_node = Box::new(Node {
-            elem: elem,
+            elem,
             next: mem::replace(&mut self.head, Link::Empty),
         });
 
@@ -44,7 +58,7 @@ impl List {
         self.head = Link::More(new_node);
     }
 
-    pub fn pop(&mut self) -> Option<i32> {
+    pub fn pop(&mut self) -> Option<T> {
         let poped_value = mem::replace(&mut self.head, Link::Empty);
 
         match poped_value {
@@ -52,14 +66,14 @@ impl List {
             Link::More(node) => {
                 self.head = node.next;
 
-                return Some(node.elem);
+                Some(node.elem)
             }
         }
     }
 
-    pub fn push_back(&mut self, elem: i32) {
+    pub fn push_back(&mut self, elem: T) {
         let link = Link::More(Box::new(Node {
-            elem: elem,
+            elem,
             next: Link::Empty,
         }));
 
@@ -74,9 +88,84 @@ impl List {
 
         *tail = link;
     }
+
+    // Node per element, see heap_size.rs
+    pub fn heap_size_in_bytes(&self) -> usize {
+        let mut nodes = 0;
+        let mut cur = &self.head;
+        while let Link::More(node) = cur {
+            nodes += 1;
+            cur = &node.next;
+        }
+
+        nodes * mem::size_of::<Node<T>>()
+    }
+}
+
+impl<T: fmt::Debug> List<T> {
+    // Graphviz graph of the nodes, see dot.rs
+    pub fn to_dot(&self) -> String {
+        let mut dot = Dot::new();
+        let mut cur = &self.head;
+
+        dot.root("head", first_node(cur));
+        while let Link::More(node) = cur {
+            dot.node(&**node, &node.elem, "");
+            if let Some(next) = first_node(&node.next) {
+                dot.edge(&**node, next, "");
+            }
+            cur = &node.next;
+        }
+
+        dot.finish()
+    }
 }
 
-impl Drop for List {
+fn first_node<T>(link: &Link<T>) -> Option<*const Node<T>> {
+    match link {
+        Link::Empty => None,
+        Link::More(node) => Some(&**node),
+    }
+}
+
+// Pushing goes to the front, so push elements from the back to keep their order
+#[cfg(feature = "proptest")]
+impl<T: proptest::arbitrary::Arbitrary> proptest::arbitrary::Arbitrary for List<T> {
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <T as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = crate::arbitrary::ListStrategy<T, Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::arbitrary::list_strategy(args, |elems| {
+            let mut list = List::new();
+            for elem in elems.into_iter().rev() {
+                list.push(elem);
+            }
+            list
+        })
+    }
+}
+
+impl<T> Stack<T> for List<T> {
+    fn push(&mut self, elem: T) {
+        self.push(elem);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.pop()
+    }
+
+    fn peek(&self) -> Option<&T> {
+        match &self.head {
+            Link::Empty => None,
+            Link::More(node) => Some(&node.elem),
+        }
+    }
+}
+
+impl<T> Drop for List<T> {
     fn drop(&mut self) {
         let mut elem = Link::Empty;
         // Take ownership over the head
@@ -175,4 +264,57 @@ mod tests {
 
         println!("Still alive!");
     }
+
+    #[test]
+    fn to_dot() {
+        let mut list = List::new();
+        assert_eq!(list.to_dot().matches("->").count(), 0);
+
+        list.push(2);
+        list.push(1);
+        let dot = list.to_dot();
+        // head -> 1 -> 2
+        assert_eq!(dot.matches("->").count(), 2);
+        assert!(dot.contains("[label=\"1\"]"));
+    }
+
+    #[test]
+    fn generic() {
+        let mut list = List::new();
+        list.push(String::from("a"));
+        list.push_back(String::from("b"));
+
+        assert_eq!(list.pop().as_deref(), Some("a"));
+        assert_eq!(list.pop().as_deref(), Some("b"));
+        assert_eq!(list.pop(), None);
+    }
+
+    // Regression test for Drop. break_the_stack above needs a long list to overflow
+    // the main thread's stack, here the stack is small, so the default, recursive drop
+    // (a few frames per node) would crash after a couple thousand nodes.
+    #[test]
+    fn drop_is_iterative() {
+        std::thread::Builder::new()
+            .stack_size(64 * 1024)
+            .spawn(|| {
+                let mut list = List::new();
+                for i in 0..100_000 {
+                    list.push(i);
+                }
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn heap_size() {
+        let mut list = List::new();
+        assert_eq!(list.heap_size_in_bytes(), 0);
+
+        list.push(1u64);
+        list.push(2);
+        // u64 and the link, Box is a pointer
+        assert_eq!(list.heap_size_in_bytes(), 2 * 16);
+    }
 }
@@ -1,10 +1,15 @@
-use std::cell::{Ref, RefCell, RefMut};
-use std::ops::Deref;
-use std::rc::Rc;
+use alloc::rc::Rc;
+use alloc::string::String;
+use core::cell::{Ref, RefCell, RefMut};
+use core::fmt;
+use core::iter::{FromIterator, FusedIterator};
+
+use crate::dot::Dot;
 
 pub struct List<T> {
     head: Link<T>,
     tail: Link<T>,
+    len: usize,
 }
 
 type Link<T> = Option<Rc<RefCell<Node<T>>>>;
@@ -18,21 +23,41 @@ struct Node<T> {
 impl<T> Node<T> {
     fn new(elem: T) -> Rc<RefCell<Self>> {
         Rc::new(RefCell::new(Node {
-            elem: elem,
+            elem,
             prev: None,
             next: None,
         }))
     }
 }
 
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T> List<T> {
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
         List {
             head: None,
             tail: None,
+            len: 0,
         }
     }
 
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Rc<RefCell<Node>> per element, see heap_size.rs
+    pub fn heap_size_in_bytes(&self) -> usize {
+        self.len * crate::heap_size::rc_allocation::<RefCell<Node<T>>>()
+    }
+
     pub fn push_front(&mut self, elem: T) {
         let new_head = Node::new(elem);
         match self.head.take() {
@@ -52,6 +77,7 @@ impl<T> List<T> {
                                             // total: +2 new_head -- OK!
             }
         }
+        self.len += 1;
     }
 
     pub fn pop_front(&mut self) -> Option<T> {
@@ -81,11 +107,12 @@ impl<T> List<T> {
             // To workaround that, convert Option to Result by ok(),
             // Then call unwrap (will never panic) resulting in RefCell(T)
             // into_inner unpacks returning T, and finally return elem
+            self.len -= 1;
             Rc::try_unwrap(old_head).ok().unwrap().into_inner().elem
         })
     }
 
-    pub fn peek_front(&self) -> Option<Ref<T>> {
+    pub fn peek_front(&self) -> Option<Ref<'_, T>> {
         self.head.as_ref().map(|node| {
             // Ref::map get Ref, and applies f on it, where f: Ref<T> -> &T
             // Note, this is imm reference, if there is shared borrow somewhere
@@ -95,7 +122,7 @@ impl<T> List<T> {
     }
 
     // Does not need &mut self, because we use Cells
-    pub fn peek_front_mut(&self) -> Option<RefMut<T>> {
+    pub fn peek_front_mut(&self) -> Option<RefMut<'_, T>> {
         self.head
             .as_ref()
             .map(|node| RefMut::map(node.borrow_mut(), |node| &mut node.elem))
@@ -114,6 +141,7 @@ impl<T> List<T> {
                 self.tail = Some(new_tail);
             }
         }
+        self.len += 1;
     }
 
     pub fn pop_back(&mut self) -> Option<T> {
@@ -127,23 +155,97 @@ impl<T> List<T> {
                     self.head.take();
                 }
             }
+            self.len -= 1;
             Rc::try_unwrap(old_tail).ok().unwrap().into_inner().elem
         })
     }
 
-    pub fn peek_back(&self) -> Option<Ref<T>> {
+    pub fn peek_back(&self) -> Option<Ref<'_, T>> {
         self.tail
             .as_ref()
             .map(|node| Ref::map(node.borrow(), |node| &node.elem))
     }
 
-    pub fn peek_back_mut(&mut self) -> Option<RefMut<T>> {
+    pub fn peek_back_mut(&mut self) -> Option<RefMut<'_, T>> {
         self.tail
             .as_ref()
             .map(|node| RefMut::map(node.borrow_mut(), |node| &mut node.elem))
     }
 }
 
+impl<T: fmt::Debug> List<T> {
+    // Graphviz graph of the nodes, see dot.rs. next links are solid, prev dashed,
+    // so it's easy to check they agree.
+    pub fn to_dot(&self) -> String {
+        let mut dot = Dot::new();
+        let mut cur = self.head.clone();
+
+        dot.root("head", self.head.as_ref().map(Rc::as_ptr));
+        dot.root("tail", self.tail.as_ref().map(Rc::as_ptr));
+        while let Some(node) = cur {
+            let borrowed = node.borrow();
+            dot.node(Rc::as_ptr(&node), &borrowed.elem, "");
+            if let Some(next) = &borrowed.next {
+                dot.edge(Rc::as_ptr(&node), Rc::as_ptr(next), "");
+            }
+            if let Some(prev) = &borrowed.prev {
+                dot.edge(Rc::as_ptr(&node), Rc::as_ptr(prev), "style=dashed");
+            }
+            cur = borrowed.next.clone();
+        }
+
+        dot.finish()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T: proptest::arbitrary::Arbitrary> proptest::arbitrary::Arbitrary for List<T> {
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <T as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = crate::arbitrary::ListStrategy<T, Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::arbitrary::list_strategy(args, |elems| {
+            let mut list = List::new();
+            for elem in elems {
+                list.push_back(elem);
+            }
+            list
+        })
+    }
+}
+
+impl<T> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push_back(elem);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = List::new();
+        list.extend(iter);
+        list
+    }
+}
+
+// From references to Copy elements, same as second::List
+impl<'a, T: Copy + 'a> Extend<&'a T> for List<T> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<'a, T: Copy + 'a> FromIterator<&'a T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = &'a T>>(iter: I) -> Self {
+        iter.into_iter().copied().collect()
+    }
+}
+
 impl<T> Drop for List<T> {
     fn drop(&mut self) {
         while self.pop_front().is_some() {}
@@ -152,8 +254,11 @@ impl<T> Drop for List<T> {
 
 pub struct IntoIter<T>(List<T>);
 
-impl<T> List<T> {
-    pub fn into_iter(self) -> IntoIter<T> {
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
         IntoIter(self)
     }
 }
@@ -171,15 +276,71 @@ impl<T> DoubleEndedIterator for IntoIter<T> {
     }
 }
 
+// Length is not tracked, so no ExactSizeIterator, but an empty list stays empty
+impl<T> FusedIterator for IntoIter<T> {}
+
+// [1, 2, 3], front to back. Same walk as in Serialize below.
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut list = f.debug_list();
+        let mut cur = self.head.clone();
+
+        while let Some(node) = cur {
+            let node = node.borrow();
+            list.entry(&node.elem);
+            cur = node.next.clone();
+        }
+
+        list.finish()
+    }
+}
+
+// No working iter here (see below), so walk the nodes by hand, holding an Rc to
+// the current one, and borrowing it only for as long as its element is written
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for List<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeSeq;
+
+        let mut len = 0;
+        let mut cur = self.head.clone();
+        while let Some(node) = cur {
+            len += 1;
+            cur = node.borrow().next.clone();
+        }
+
+        let mut seq = serializer.serialize_seq(Some(len))?;
+        let mut cur = self.head.clone();
+        while let Some(node) = cur {
+            let node = node.borrow();
+            seq.serialize_element(&node.elem)?;
+            cur = node.next.clone();
+        }
+        seq.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for List<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_seq::deserialize_seq(deserializer, "a deque", |elems| {
+            let mut list = List::new();
+            elems.for_each(|elem| list.push_back(elem));
+            list
+        })
+    }
+}
+
 // Writing iter, which returns T or Ref<T> is almost impossible,
 // Problem is we borrow Ref inside a closure, and then try to use it, outside it
 // Looks like there is something missing in Ref implementation to make it possible???
 // It could be possible when returning Node<T>
 // And probably even statically checked - no RefCell needed? Left for reader as an exercise.
+#[allow(dead_code)]
 pub struct Iter<'a, T>(Option<Ref<'a, Node<T>>>);
 
 impl<T> List<T> {
-    pub fn iter(&self) -> Iter<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
         Iter(self.head.as_ref().map(|head| head.borrow()))
     }
 }
@@ -314,4 +475,76 @@ mod test {
         // Second borrow, first is still in scope - panic!
         let _b = list.peek_front();
     }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() {
+        let mut list = List::new();
+        list.push_back(1);
+        list.push_back(2);
+        list.push_front(0);
+
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(json, "[0,1,2]");
+
+        let list = serde_json::from_str::<List<i32>>(&json).unwrap();
+        assert!(list.into_iter().eq(0..3));
+    }
+
+    #[test]
+    fn debug() {
+        let mut list = List::new();
+        list.push_back(2);
+        list.push_front(1);
+        assert_eq!(format!("{:?}", list), "[1, 2]");
+    }
+
+    #[test]
+    fn to_dot() {
+        let mut list = List::new();
+        list.push_back(1);
+        list.push_back(2);
+        list.push_back(3);
+
+        let dot = list.to_dot();
+        // head, tail, 2 next links, 2 prev links
+        assert_eq!(dot.matches("->").count(), 6);
+        assert_eq!(dot.matches("style=dashed").count(), 2);
+    }
+
+    #[test]
+    fn len_and_from_iter() {
+        let mut list: List<_> = (1..4).collect();
+        assert_eq!(list.len(), 3);
+
+        list.extend(vec![4, 5]);
+        list.push_front(0);
+        assert_eq!(list.len(), 6);
+
+        assert_eq!(list.pop_back(), Some(5));
+        assert_eq!(list.pop_front(), Some(0));
+        assert_eq!(list.len(), 4);
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn heap_size() {
+        let mut list = List::new();
+        assert_eq!(list.heap_size_in_bytes(), 0);
+        list.push_back(1usize);
+        list.push_front(0);
+
+        // Rc counters, RefCell flag, the element, and two links
+        assert_eq!(
+            list.heap_size_in_bytes(),
+            2 * 6 * std::mem::size_of::<usize>()
+        );
+    }
+
+    #[test]
+    fn extend_and_collect_copied() {
+        let mut list: List<i32> = [1, 2].iter().collect();
+        list.extend(&[3, 4]);
+        assert!(list.into_iter().eq(1..=4));
+    }
 }
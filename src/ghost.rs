@@ -0,0 +1,385 @@
+use alloc::rc::{Rc, Weak};
+use core::cell::UnsafeCell;
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+
+// fourth::List again, but without RefCell. The idea is from the GhostCell paper
+// (Yanovski et al., "GhostCell: Separating Permissions from Data in Rust"):
+//
+// RefCell counts borrows of every node at runtime, and panics if they overlap.
+// GhostCell moves the permission out of the node, into a token. There is one token
+// for the whole list, and it's an ordinary value, so the borrow checker does the job:
+// - &token lets you read any node (many shared borrows of the token at once are fine)
+// - &mut token lets you write one node at a time (it's unique)
+//
+// What ties a cell to its token is a brand - an invariant lifetime 'id, which is
+// unique for every GhostToken::new call, so a token can't open cells of another list.
+//
+// The price: every call that touches nodes takes the token, and everything is done
+// inside the GhostToken::new closure. The only unsafe code is in GhostCell::borrow(_mut),
+// the list itself is safe Rust.
+
+// fn(&'id ()) -> &'id () makes 'id invariant - it can't be shortened or extended
+// to make two different brands match
+type InvariantLifetime<'id> = PhantomData<fn(&'id ()) -> &'id ()>;
+
+pub struct GhostToken<'id> {
+    _brand: InvariantLifetime<'id>,
+}
+
+impl<'id> GhostToken<'id> {
+    // 'new is chosen by the caller of f, f has to work for any of them, so the brand
+    // can't be named outside, nor be equal to the brand of any other token
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new<R>(f: impl for<'new> FnOnce(GhostToken<'new>) -> R) -> R {
+        f(GhostToken {
+            _brand: PhantomData,
+        })
+    }
+}
+
+pub struct GhostCell<'id, T: ?Sized> {
+    _brand: InvariantLifetime<'id>,
+    value: UnsafeCell<T>,
+}
+
+// Same rules as for RwLock: sharing the cell lets others get &T (with &token)
+// or &mut T (with &mut token) on their thread
+unsafe impl<'id, T: ?Sized + Send> Send for GhostCell<'id, T> {}
+unsafe impl<'id, T: ?Sized + Send + Sync> Sync for GhostCell<'id, T> {}
+
+impl<'id, T> GhostCell<'id, T> {
+    pub const fn new(value: T) -> Self {
+        GhostCell {
+            _brand: PhantomData,
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<'id, T: ?Sized> GhostCell<'id, T> {
+    pub fn borrow<'a>(&'a self, _token: &'a GhostToken<'id>) -> &'a T {
+        // There is one token with this brand, it's borrowed shared for 'a,
+        // so nobody can borrow_mut any cell of the brand for that long
+        unsafe { &*self.value.get() }
+    }
+
+    pub fn borrow_mut<'a>(&'a self, _token: &'a mut GhostToken<'id>) -> &'a mut T {
+        // The token is borrowed unique for 'a, so there is no other borrow of any cell
+        // of the brand for that long
+        unsafe { &mut *self.value.get() }
+    }
+
+    // Unique access to the cell is enough, no token needed
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+}
+
+pub struct List<'id, T> {
+    head: Link<'id, T>,
+    tail: Link<'id, T>,
+    len: usize,
+}
+
+type NodeRef<'id, T> = Rc<GhostCell<'id, Node<'id, T>>>;
+type Link<'id, T> = Option<NodeRef<'id, T>>;
+
+// prev is Weak - unlike in fourth, every node has one strong owner (the previous node,
+// or head), so popped nodes can be unwrapped, and Drop doesn't need the token
+struct Node<'id, T> {
+    elem: T,
+    next: Link<'id, T>,
+    prev: Option<Weak<GhostCell<'id, Node<'id, T>>>>,
+}
+
+impl<'id, T> Node<'id, T> {
+    fn new(elem: T) -> NodeRef<'id, T> {
+        Rc::new(GhostCell::new(Node {
+            elem,
+            next: None,
+            prev: None,
+        }))
+    }
+}
+
+// The node is unlinked, head/tail and its neighbours don't point at it any more
+fn unwrap<'id, T>(node: NodeRef<'id, T>) -> T {
+    match Rc::try_unwrap(node) {
+        Ok(cell) => cell.into_inner().elem,
+        Err(_) => unreachable!("unlinked node has other owners"),
+    }
+}
+
+impl<'id, T> Default for List<'id, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'id, T> List<'id, T> {
+    pub const fn new() -> Self {
+        List {
+            head: None,
+            tail: None,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push_front(&mut self, elem: T, token: &mut GhostToken<'id>) {
+        let new_head = Node::new(elem);
+        match self.head.take() {
+            Some(old_head) => {
+                old_head.borrow_mut(token).prev = Some(Rc::downgrade(&new_head));
+                new_head.borrow_mut(token).next = Some(old_head);
+            }
+            None => self.tail = Some(new_head.clone()),
+        }
+        self.head = Some(new_head);
+        self.len += 1;
+    }
+
+    pub fn push_back(&mut self, elem: T, token: &mut GhostToken<'id>) {
+        let new_tail = Node::new(elem);
+        match self.tail.take() {
+            Some(old_tail) => {
+                new_tail.borrow_mut(token).prev = Some(Rc::downgrade(&old_tail));
+                old_tail.borrow_mut(token).next = Some(new_tail.clone());
+            }
+            None => self.head = Some(new_tail.clone()),
+        }
+        self.tail = Some(new_tail);
+        self.len += 1;
+    }
+
+    pub fn pop_front(&mut self, token: &mut GhostToken<'id>) -> Option<T> {
+        self.head.take().map(|old_head| {
+            match old_head.borrow_mut(token).next.take() {
+                Some(new_head) => {
+                    new_head.borrow_mut(token).prev = None;
+                    self.head = Some(new_head);
+                }
+                None => {
+                    self.tail = None;
+                }
+            }
+
+            self.len -= 1;
+            unwrap(old_head)
+        })
+    }
+
+    pub fn pop_back(&mut self, token: &mut GhostToken<'id>) -> Option<T> {
+        self.tail.take().map(|old_tail| {
+            // The previous node is alive, it's the strong owner of old_tail
+            match old_tail.borrow_mut(token).prev.take() {
+                Some(new_tail) => {
+                    let new_tail = new_tail.upgrade().unwrap();
+                    new_tail.borrow_mut(token).next = None;
+                    self.tail = Some(new_tail);
+                }
+                None => {
+                    self.head = None;
+                }
+            }
+
+            self.len -= 1;
+            unwrap(old_tail)
+        })
+    }
+
+    // Plain references, no Ref guards - they live as long as the borrow of the token
+    pub fn peek_front<'a>(&'a self, token: &'a GhostToken<'id>) -> Option<&'a T> {
+        self.head.as_ref().map(|node| &node.borrow(token).elem)
+    }
+
+    pub fn peek_back<'a>(&'a self, token: &'a GhostToken<'id>) -> Option<&'a T> {
+        self.tail.as_ref().map(|node| &node.borrow(token).elem)
+    }
+
+    // &self is enough, the token is what's borrowed unique
+    pub fn peek_front_mut<'a>(&'a self, token: &'a mut GhostToken<'id>) -> Option<&'a mut T> {
+        self.head
+            .as_ref()
+            .map(move |node| &mut node.borrow_mut(token).elem)
+    }
+
+    pub fn peek_back_mut<'a>(&'a self, token: &'a mut GhostToken<'id>) -> Option<&'a mut T> {
+        self.tail
+            .as_ref()
+            .map(move |node| &mut node.borrow_mut(token).elem)
+    }
+
+    pub fn iter<'a>(&'a self, token: &'a GhostToken<'id>) -> Iter<'a, 'id, T> {
+        Iter {
+            next: self.head.as_deref(),
+            token,
+            len: self.len,
+        }
+    }
+
+    // There is no IterMut: every &mut T borrows the token unique, so one can't be
+    // handed out while the previous one is alive. A closure gets them one at a time.
+    pub fn for_each_mut<F: FnMut(&mut T)>(&self, token: &mut GhostToken<'id>, mut f: F) {
+        let mut cur = self.head.clone();
+        while let Some(node) = cur {
+            let node = node.borrow_mut(token);
+            f(&mut node.elem);
+            cur = node.next.clone();
+        }
+    }
+}
+
+impl<'id, T> Drop for List<'id, T> {
+    fn drop(&mut self) {
+        // With tail gone, every node has exactly one strong owner, so they unwrap one
+        // by one, without the token and without a recursive drop of the next links
+        self.tail.take();
+        let mut cur = self.head.take();
+
+        while let Some(node) = cur {
+            cur = Rc::try_unwrap(node)
+                .ok()
+                .and_then(|cell| cell.into_inner().next);
+        }
+    }
+}
+
+pub struct Iter<'a, 'id, T> {
+    next: Option<&'a GhostCell<'id, Node<'id, T>>>,
+    token: &'a GhostToken<'id>,
+    len: usize,
+}
+
+impl<'a, 'id, T> Iterator for Iter<'a, 'id, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|cell| {
+            let node = cell.borrow(self.token);
+            self.next = node.next.as_deref();
+            self.len -= 1;
+            &node.elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, 'id, T> ExactSizeIterator for Iter<'a, 'id, T> {}
+
+impl<'a, 'id, T> FusedIterator for Iter<'a, 'id, T> {}
+
+#[cfg(test)]
+mod test {
+    use super::{GhostCell, GhostToken, List};
+    use std::rc::Rc;
+
+    #[test]
+    fn basics() {
+        GhostToken::new(|mut token| {
+            let mut list = List::new();
+
+            // Check empty list behaves right
+            assert_eq!(list.pop_front(&mut token), None);
+            assert_eq!(list.pop_back(&mut token), None);
+            assert_eq!(list.peek_front(&token), None);
+
+            // Populate list
+            list.push_front(2, &mut token);
+            list.push_front(1, &mut token);
+            list.push_back(3, &mut token);
+            assert_eq!(list.len(), 3);
+            assert_eq!(list.peek_front(&token), Some(&1));
+            assert_eq!(list.peek_back(&token), Some(&3));
+
+            *list.peek_front_mut(&mut token).unwrap() = 10;
+            *list.peek_back_mut(&mut token).unwrap() = 30;
+            assert!(list.iter(&token).copied().eq(vec![10, 2, 30]));
+
+            // Both ends
+            assert_eq!(list.pop_back(&mut token), Some(30));
+            assert_eq!(list.pop_front(&mut token), Some(10));
+            assert_eq!(list.pop_back(&mut token), Some(2));
+            assert_eq!(list.pop_front(&mut token), None);
+            assert!(list.is_empty());
+
+            // Still works after being emptied
+            list.push_back(4, &mut token);
+            assert_eq!(list.pop_front(&mut token), Some(4));
+        });
+    }
+
+    #[test]
+    fn shared_reads() {
+        GhostToken::new(|mut token| {
+            let mut list = List::new();
+            for i in 0..5 {
+                list.push_back(i, &mut token);
+            }
+
+            // Any number of readers, all borrowing the token shared
+            let first = list.peek_front(&token).unwrap();
+            let sum: i32 = list.iter(&token).sum();
+            assert_eq!(*first + sum, 10);
+            assert_eq!(list.iter(&token).len(), 5);
+
+            list.for_each_mut(&mut token, |elem| *elem *= 2);
+            assert!(list.iter(&token).copied().eq(vec![0, 2, 4, 6, 8]));
+        });
+    }
+
+    #[test]
+    fn cell() {
+        GhostToken::new(|mut token| {
+            let cell = GhostCell::new(String::from("a"));
+            cell.borrow_mut(&mut token).push('b');
+            assert_eq!(cell.borrow(&token), "ab");
+
+            let mut cell = cell;
+            cell.get_mut().push('c');
+            assert_eq!(cell.into_inner(), "abc");
+        });
+    }
+
+    #[test]
+    fn drop_elements() {
+        let elem = Rc::new(());
+
+        GhostToken::new(|mut token| {
+            let mut list = List::new();
+            for _ in 0..10 {
+                list.push_back(elem.clone(), &mut token);
+            }
+            list.pop_back(&mut token);
+            list.pop_front(&mut token);
+        });
+
+        assert_eq!(Rc::strong_count(&elem), 1);
+    }
+
+    #[test]
+    fn long() {
+        // Dropping many nodes doesn't recurse
+        GhostToken::new(|mut token| {
+            let mut list = List::new();
+            for i in 0..100_000 {
+                list.push_front(i, &mut token);
+            }
+        });
+    }
+}
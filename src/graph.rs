@@ -0,0 +1,255 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::iter::FusedIterator;
+
+use crate::{fifth, second};
+
+// Directed graph as adjacency lists - vertices are numbers 0..vertex_count, and every
+// vertex owns a second::List of the vertices its edges go to.
+//
+// 0 -> [2, 1]
+// 1 -> [2]
+// 2 -> []
+//
+// add_edge pushes to the front of the list, O(1), so neighbors come newest edge first.
+// The traversals are built of the lists too: DFS keeps its stack in a second::List,
+// BFS its queue in a fifth::List.
+#[derive(Clone, Debug, Default)]
+pub struct Graph {
+    adjacency: Vec<second::List<usize>>,
+    edge_count: usize,
+}
+
+impl Graph {
+    pub const fn new() -> Self {
+        Graph {
+            adjacency: Vec::new(),
+            edge_count: 0,
+        }
+    }
+
+    pub fn with_vertices(count: usize) -> Self {
+        let mut graph = Graph::new();
+        graph.adjacency.resize_with(count, second::List::new);
+        graph
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    // Returns the new vertex
+    pub fn add_vertex(&mut self) -> usize {
+        self.adjacency.push(second::List::new());
+        self.adjacency.len() - 1
+    }
+
+    fn check_vertex(&self, vertex: usize) {
+        assert!(
+            vertex < self.adjacency.len(),
+            "vertex {} out of bounds, there are {} vertices",
+            vertex,
+            self.adjacency.len()
+        );
+    }
+
+    // Parallel edges are allowed, every call adds one
+    pub fn add_edge(&mut self, from: usize, to: usize) {
+        self.check_vertex(from);
+        self.check_vertex(to);
+
+        self.adjacency[from].push(to);
+        self.edge_count += 1;
+    }
+
+    // Removes one edge from -> to, if there is one
+    pub fn remove_edge(&mut self, from: usize, to: usize) -> bool {
+        self.check_vertex(from);
+
+        let removed = self.adjacency[from].remove_first(&to);
+        if removed {
+            self.edge_count -= 1;
+        }
+        removed
+    }
+
+    pub fn has_edge(&self, from: usize, to: usize) -> bool {
+        self.check_vertex(from);
+        self.adjacency[from].contains(&to)
+    }
+
+    pub fn neighbors(&self, vertex: usize) -> second::Iter<'_, usize> {
+        self.check_vertex(vertex);
+        self.adjacency[vertex].iter()
+    }
+
+    // Vertices reachable from start, closest first
+    pub fn bfs(&self, start: usize) -> Bfs<'_> {
+        self.check_vertex(start);
+
+        let mut visited = vec![false; self.vertex_count()];
+        visited[start] = true;
+
+        let mut queue = fifth::List::new();
+        queue.push(start);
+
+        Bfs {
+            graph: self,
+            visited,
+            queue,
+        }
+    }
+
+    // Vertices reachable from start, depth first, every one before its descendants
+    pub fn dfs(&self, start: usize) -> Dfs<'_> {
+        self.check_vertex(start);
+
+        let mut stack = second::List::new();
+        stack.push(start);
+
+        Dfs {
+            graph: self,
+            visited: vec![false; self.vertex_count()],
+            stack,
+        }
+    }
+}
+
+// Vertices are marked when they're queued, so every one is queued once
+pub struct Bfs<'a> {
+    graph: &'a Graph,
+    visited: Vec<bool>,
+    queue: fifth::List<usize>,
+}
+
+impl<'a> Iterator for Bfs<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let vertex = self.queue.pop()?;
+
+        for &next in self.graph.neighbors(vertex) {
+            if !self.visited[next] {
+                self.visited[next] = true;
+                self.queue.push(next);
+            }
+        }
+
+        Some(vertex)
+    }
+}
+
+impl<'a> FusedIterator for Bfs<'a> {}
+
+// Vertices are marked when they're popped - a vertex may be on the stack more than once,
+// reached from different places, and the deepest path has to win
+pub struct Dfs<'a> {
+    graph: &'a Graph,
+    visited: Vec<bool>,
+    stack: second::List<usize>,
+}
+
+impl<'a> Iterator for Dfs<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(vertex) = self.stack.pop() {
+            if self.visited[vertex] {
+                continue;
+            }
+            self.visited[vertex] = true;
+
+            // Pushed in reverse, so the first neighbor is popped first,
+            // same order as a recursive DFS would go
+            for &next in self.graph.neighbors(vertex).rev() {
+                if !self.visited[next] {
+                    self.stack.push(next);
+                }
+            }
+
+            return Some(vertex);
+        }
+
+        None
+    }
+}
+
+impl<'a> FusedIterator for Dfs<'a> {}
+
+#[cfg(test)]
+mod test {
+    use super::Graph;
+
+    //   0 -> 1 -> 3
+    //   |    |
+    //   v    v
+    //   2 -> 4    5 (not reachable)
+    fn graph() -> Graph {
+        let mut graph = Graph::with_vertices(6);
+        // Newest first, so added in reverse to get neighbors in order
+        graph.add_edge(0, 2);
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 4);
+        graph.add_edge(1, 3);
+        graph.add_edge(2, 4);
+        graph
+    }
+
+    #[test]
+    fn basics() {
+        let mut graph = graph();
+        assert_eq!(graph.vertex_count(), 6);
+        assert_eq!(graph.edge_count(), 5);
+        assert!(graph.neighbors(0).copied().eq(vec![1, 2]));
+        assert!(graph.has_edge(2, 4));
+        assert!(!graph.has_edge(4, 2));
+
+        let v = graph.add_vertex();
+        assert_eq!(v, 6);
+        graph.add_edge(5, v);
+        assert!(graph.neighbors(5).copied().eq(vec![6]));
+
+        assert!(graph.remove_edge(0, 2));
+        assert!(!graph.remove_edge(0, 2));
+        assert!(graph.neighbors(0).copied().eq(vec![1]));
+        assert_eq!(graph.edge_count(), 5);
+    }
+
+    #[test]
+    fn bfs() {
+        let graph = graph();
+        assert_eq!(graph.bfs(0).collect::<Vec<_>>(), vec![0, 1, 2, 3, 4]);
+        assert_eq!(graph.bfs(2).collect::<Vec<_>>(), vec![2, 4]);
+        assert_eq!(graph.bfs(5).collect::<Vec<_>>(), vec![5]);
+    }
+
+    #[test]
+    fn dfs() {
+        let graph = graph();
+        assert_eq!(graph.dfs(0).collect::<Vec<_>>(), vec![0, 1, 3, 4, 2]);
+        assert_eq!(graph.dfs(1).collect::<Vec<_>>(), vec![1, 3, 4]);
+    }
+
+    #[test]
+    fn cycles() {
+        let mut graph = Graph::with_vertices(3);
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 2);
+        graph.add_edge(2, 0);
+        graph.add_edge(2, 2);
+
+        assert_eq!(graph.bfs(1).collect::<Vec<_>>(), vec![1, 2, 0]);
+        assert_eq!(graph.dfs(2).collect::<Vec<_>>(), vec![2, 0, 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn bad_vertex() {
+        let mut graph = Graph::with_vertices(2);
+        graph.add_edge(0, 2);
+    }
+}
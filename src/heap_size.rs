@@ -0,0 +1,16 @@
+use core::alloc::Layout;
+
+// Helpers for heap_size_in_bytes of the lists. The lists count their own allocations
+// only - nodes, arenas, pools - not what the elements own: a String element is counted
+// as its 24 bytes in the node, wherever its text is.
+//
+// A Box<T> allocation is just size_of::<T>(), Rc and Arc need a helper, they put
+// the counters (strong and weak) in front of the value.
+pub(crate) fn rc_allocation<T>() -> usize {
+    Layout::new::<[usize; 2]>()
+        .extend(Layout::new::<T>())
+        .unwrap()
+        .0
+        .pad_to_align()
+        .size()
+}
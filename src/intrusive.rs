@@ -0,0 +1,592 @@
+use core::cell::Cell;
+use core::iter::FusedIterator;
+use core::marker::{PhantomData, PhantomPinned};
+use core::pin::Pin;
+use core::ptr::NonNull;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+// Intrusive doubly linked list - list does not allocate nodes, instead user types embed
+// the Link, and the list threads through them:
+//
+// struct Task {
+//     id: u32,
+//     link: Link<Task>,
+// }
+//
+// unsafe impl Linked for Task {
+//     fn link(&self) -> &Link<Self> {
+//         &self.link
+//     }
+// }
+//
+// Ownership story: the list does not own the items, it borrows them for 'a, and
+// takes them as Pin<&'a T>:
+// - items point at each other, so they must not move while linked. Link is !Unpin
+//   (PhantomPinned), so the only way to get Pin<&T> is to really pin the item
+//   (Box::pin, or pin! on the stack), and pinned memory stays where it is until it's dropped
+// - the borrow keeps items alive as long as the list lives, so links never dangle
+// - links are mutated through Cells, so a shared borrow is all we need
+// Dropping the list unlinks all items, after that they can be inserted somewhere else.
+//
+// Items that live shorter than the list go in through a LinkGuard instead, see below.
+pub struct Link<T> {
+    prev: Cell<Option<NonNull<T>>>,
+    next: Cell<Option<NonNull<T>>>,
+    // Id of the list this item is linked into, 0 when not linked
+    owner: Cell<usize>,
+    // Linked by a LinkGuard, see Drop below
+    guarded: Cell<bool>,
+    _pin: PhantomPinned,
+}
+
+/// # Safety
+///
+/// link has to always return the same Link, embedded in self - the list trusts it,
+/// and follows pointers stored there
+pub unsafe trait Linked: Sized {
+    fn link(&self) -> &Link<Self>;
+}
+
+// Every list gets an unique id, so items can tell which list they belong to - removing
+// an item through the wrong list would corrupt both of them
+static NEXT_LIST_ID: AtomicUsize = AtomicUsize::new(1);
+
+pub struct IntrusiveList<'a, T: Linked> {
+    head: Option<NonNull<T>>,
+    tail: Option<NonNull<T>>,
+    len: usize,
+    id: usize,
+    _marker: PhantomData<Pin<&'a T>>,
+}
+
+impl<T> Link<T> {
+    pub const fn new() -> Self {
+        Link {
+            prev: Cell::new(None),
+            next: Cell::new(None),
+            owner: Cell::new(0),
+            guarded: Cell::new(false),
+            _pin: PhantomPinned,
+        }
+    }
+
+    pub fn is_linked(&self) -> bool {
+        self.owner.get() != 0
+    }
+
+    fn reset(&self) {
+        self.prev.set(None);
+        self.next.set(None);
+        self.owner.set(0);
+    }
+}
+
+// A LinkGuard unlinks its item when it's dropped, before the item can go away. Unless
+// it's forgotten (mem::forget is safe) - then the item stays linked, and the list
+// would be left with a dangling pointer once the item is dropped. Pin promises that
+// pinned memory isn't reused before drop runs, so drop is the last moment to stop it,
+// and there is nothing to do but abort. Items linked by push_* outlive the list,
+// they are never linked here. Only the guard clears guarded, an item unlinked any
+// other way (list dropped after the guard was forgotten) still aborts here.
+impl<T> Drop for Link<T> {
+    fn drop(&mut self) {
+        if self.guarded.get() {
+            abort("item dropped while linked, was its LinkGuard forgotten?");
+        }
+    }
+}
+
+// Panic while panicking aborts, no_std or not
+fn abort(msg: &str) -> ! {
+    struct Bomb;
+    impl Drop for Bomb {
+        fn drop(&mut self) {
+            panic!("aborting");
+        }
+    }
+
+    let _bomb = Bomb;
+    panic!("{}", msg);
+}
+
+impl<T> Default for Link<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T: Linked> Default for IntrusiveList<'a, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T: Linked> IntrusiveList<'a, T> {
+    pub fn new() -> Self {
+        IntrusiveList {
+            head: None,
+            tail: None,
+            len: 0,
+            id: NEXT_LIST_ID.fetch_add(1, Ordering::Relaxed),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    // Every pointer in the list comes from an item borrowed for 'a
+    fn item(ptr: NonNull<T>) -> &'a T {
+        unsafe { &*ptr.as_ptr() }
+    }
+
+    // Items were pinned when they were inserted
+    fn pinned(ptr: NonNull<T>) -> Pin<&'a T> {
+        unsafe { Pin::new_unchecked(Self::item(ptr)) }
+    }
+
+    // Panics if the item is already linked (into this, or any other list)
+    fn claim(&self, item: &T) -> NonNull<T> {
+        let link = item.link();
+        assert!(!link.is_linked(), "item is already linked");
+        link.owner.set(self.id);
+
+        NonNull::from(item)
+    }
+
+    pub fn push_back(&mut self, item: Pin<&'a T>) {
+        let ptr = self.claim(item.get_ref());
+        self.link_back(ptr);
+    }
+
+    fn link_back(&mut self, ptr: NonNull<T>) {
+        Self::item(ptr).link().prev.set(self.tail);
+
+        match self.tail {
+            Some(tail) => Self::item(tail).link().next.set(Some(ptr)),
+            None => self.head = Some(ptr),
+        }
+
+        self.tail = Some(ptr);
+        self.len += 1;
+    }
+
+    pub fn push_front(&mut self, item: Pin<&'a T>) {
+        let ptr = self.claim(item.get_ref());
+        self.link_front(ptr);
+    }
+
+    fn link_front(&mut self, ptr: NonNull<T>) {
+        Self::item(ptr).link().next.set(self.head);
+
+        match self.head {
+            Some(head) => Self::item(head).link().prev.set(Some(ptr)),
+            None => self.tail = Some(ptr),
+        }
+
+        self.head = Some(ptr);
+        self.len += 1;
+    }
+
+    // An item linked by a guard lives only as long as the guard, it can't be handed out
+    // for 'a. While the guard is alive it borrows the list, so the list itself can run
+    // into such an item only if the guard was forgotten.
+    fn unguarded(ptr: NonNull<T>) -> Pin<&'a T> {
+        assert_unguarded(unsafe { ptr.as_ref() });
+        Self::pinned(ptr)
+    }
+
+    pub fn front(&self) -> Option<Pin<&'a T>> {
+        self.head.map(Self::unguarded)
+    }
+
+    pub fn back(&self) -> Option<Pin<&'a T>> {
+        self.tail.map(Self::unguarded)
+    }
+
+    pub fn pop_front(&mut self) -> Option<Pin<&'a T>> {
+        self.head.map(|head| {
+            let item = Self::unguarded(head);
+            self.unlink(head);
+            item
+        })
+    }
+
+    pub fn pop_back(&mut self) -> Option<Pin<&'a T>> {
+        self.tail.map(|tail| {
+            let item = Self::unguarded(tail);
+            self.unlink(tail);
+            item
+        })
+    }
+
+    // Links item at the back for as long as the guard lives, the item only has to outlive
+    // the guard, not the list. The guard keeps the list (and item) borrowed unique, so
+    // nothing else can see the item, and can't keep a reference to it past the guard.
+    // More items can be linked through the guard, they are unlinked in reverse order.
+    pub fn push_back_guard<'g>(&'g mut self, item: Pin<&'g mut T>) -> LinkGuard<'g, 'a, T> {
+        let ptr = self.claim_guarded(item);
+        self.link_back(ptr);
+        LinkGuard::new(self, ptr)
+    }
+
+    pub fn push_front_guard<'g>(&'g mut self, item: Pin<&'g mut T>) -> LinkGuard<'g, 'a, T> {
+        let ptr = self.claim_guarded(item);
+        self.link_front(ptr);
+        LinkGuard::new(self, ptr)
+    }
+
+    fn claim_guarded(&self, item: Pin<&mut T>) -> NonNull<T> {
+        let item = Pin::into_ref(item).get_ref();
+        let ptr = self.claim(item);
+        item.link().guarded.set(true);
+        ptr
+    }
+
+    // O(1), the item knows its neighbours. Returns false if the item is not in this list.
+    pub fn remove(&mut self, item: Pin<&'a T>) -> bool {
+        let item = item.get_ref();
+
+        if item.link().owner.get() != self.id {
+            return false;
+        }
+
+        self.unlink(NonNull::from(item));
+        true
+    }
+
+    fn unlink(&mut self, ptr: NonNull<T>) {
+        let link = Self::item(ptr).link();
+
+        match link.prev.get() {
+            Some(prev) => Self::item(prev).link().next.set(link.next.get()),
+            None => self.head = link.next.get(),
+        }
+
+        match link.next.get() {
+            Some(next) => Self::item(next).link().prev.set(link.prev.get()),
+            None => self.tail = link.prev.get(),
+        }
+
+        link.reset();
+        self.len -= 1;
+    }
+
+    // Iterator borrows the list, so nothing can be unlinked while we walk
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.iter_with(false)
+    }
+
+    fn iter_with(&self, guarded: bool) -> Iter<'_, T> {
+        Iter {
+            next: self.head,
+            len: self.len,
+            guarded,
+            _marker: PhantomData,
+        }
+    }
+}
+
+fn assert_unguarded<T: Linked>(item: &T) {
+    assert!(
+        !item.link().guarded.get(),
+        "item linked by a LinkGuard, was the guard forgotten?"
+    );
+}
+
+impl<'a, T: Linked> Drop for IntrusiveList<'a, T> {
+    fn drop(&mut self) {
+        // Items outlive the list, they must not point to each other anymore. Not pop_front,
+        // items of forgotten guards are unlinked too, it's their drop that aborts.
+        while let Some(head) = self.head {
+            self.unlink(head);
+        }
+    }
+}
+
+pub struct LinkGuard<'g, 'a, T: Linked> {
+    list: &'g mut IntrusiveList<'a, T>,
+    // Pointer, not Pin<&mut T>, the list points at the item too
+    item: NonNull<T>,
+    _marker: PhantomData<Pin<&'g mut T>>,
+}
+
+// Everything here borrows the guard, so no reference to an item linked by it
+// (or by the guards before it) outlives the guard
+impl<'g, 'a, T: Linked> LinkGuard<'g, 'a, T> {
+    fn new(list: &'g mut IntrusiveList<'a, T>, item: NonNull<T>) -> Self {
+        LinkGuard {
+            list,
+            item,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn item(&self) -> Pin<&T> {
+        // Pinned and borrowed for 'g by push_*_guard
+        unsafe { Pin::new_unchecked(&*self.item.as_ptr()) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    // Items of the guards are fine here, the iterator borrows the guard
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.list.iter_with(true)
+    }
+
+    pub fn push_back_guard<'h>(&'h mut self, item: Pin<&'h mut T>) -> LinkGuard<'h, 'a, T> {
+        self.list.push_back_guard(item)
+    }
+
+    pub fn push_front_guard<'h>(&'h mut self, item: Pin<&'h mut T>) -> LinkGuard<'h, 'a, T> {
+        self.list.push_front_guard(item)
+    }
+}
+
+impl<'g, 'a, T: Linked> Drop for LinkGuard<'g, 'a, T> {
+    fn drop(&mut self) {
+        self.list.unlink(self.item);
+        unsafe { self.item.as_ref() }.link().guarded.set(false);
+    }
+}
+
+pub struct Iter<'l, T> {
+    next: Option<NonNull<T>>,
+    // Items left
+    len: usize,
+    // Whether items linked by guards may come out, see LinkGuard::iter
+    guarded: bool,
+    _marker: PhantomData<&'l T>,
+}
+
+impl<'l, T: Linked> Iterator for Iter<'l, T> {
+    type Item = Pin<&'l T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|ptr| {
+            // Items live longer than the borrow of the list
+            let item = unsafe { &*ptr.as_ptr() };
+            if !self.guarded {
+                assert_unguarded(item);
+            }
+            self.next = item.link().next.get();
+            self.len -= 1;
+
+            unsafe { Pin::new_unchecked(item) }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'l, T: Linked> ExactSizeIterator for Iter<'l, T> {}
+
+impl<'l, T: Linked> FusedIterator for Iter<'l, T> {}
+
+#[cfg(test)]
+mod test {
+    use super::{IntrusiveList, Link, Linked};
+    use std::pin::Pin;
+
+    struct Task {
+        id: u32,
+        link: Link<Task>,
+    }
+
+    unsafe impl Linked for Task {
+        fn link(&self) -> &Link<Self> {
+            &self.link
+        }
+    }
+
+    fn task(id: u32) -> Pin<Box<Task>> {
+        Box::pin(Task {
+            id,
+            link: Link::new(),
+        })
+    }
+
+    fn ids(list: &IntrusiveList<'_, Task>) -> Vec<u32> {
+        list.iter().map(|task| task.id).collect()
+    }
+
+    #[test]
+    fn basics() {
+        let (a, b, c) = (task(1), task(2), task(3));
+
+        let mut list = IntrusiveList::new();
+        assert!(list.is_empty());
+        assert!(list.pop_front().is_none());
+
+        list.push_back(a.as_ref());
+        list.push_back(b.as_ref());
+        list.push_front(c.as_ref());
+        assert_eq!(ids(&list), vec![3, 1, 2]);
+        assert_eq!(list.len(), 3);
+        assert!(a.link.is_linked());
+
+        assert_eq!(list.front().map(|t| t.id), Some(3));
+        assert_eq!(list.back().map(|t| t.id), Some(2));
+
+        assert_eq!(list.pop_front().map(|t| t.id), Some(3));
+        assert_eq!(list.pop_back().map(|t| t.id), Some(2));
+        assert!(!c.link.is_linked());
+
+        // Popped items can be inserted again
+        list.push_front(c.as_ref());
+        assert_eq!(ids(&list), vec![3, 1]);
+
+        assert_eq!(list.pop_back().map(|t| t.id), Some(1));
+        assert_eq!(list.pop_back().map(|t| t.id), Some(3));
+        assert!(list.pop_back().is_none());
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn remove() {
+        let tasks = (0..5).map(task).collect::<Vec<_>>();
+        let other_task = task(10);
+
+        let mut list = IntrusiveList::new();
+        let mut other = IntrusiveList::new();
+
+        for task in &tasks {
+            list.push_back(task.as_ref());
+        }
+        other.push_back(other_task.as_ref());
+
+        // Middle, front and back
+        assert!(list.remove(tasks[2].as_ref()));
+        assert!(list.remove(tasks[0].as_ref()));
+        assert!(list.remove(tasks[4].as_ref()));
+        assert_eq!(ids(&list), vec![1, 3]);
+        assert_eq!(list.len(), 2);
+
+        // Not linked anymore, or linked into another list
+        assert!(!list.remove(tasks[2].as_ref()));
+        assert!(!list.remove(other_task.as_ref()));
+        assert_eq!(ids(&other), vec![10]);
+
+        assert!(list.remove(tasks[1].as_ref()));
+        assert!(list.remove(tasks[3].as_ref()));
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_twice() {
+        let a = task(1);
+
+        let mut list = IntrusiveList::new();
+        list.push_back(a.as_ref());
+        list.push_back(a.as_ref());
+    }
+
+    #[test]
+    fn stack_pinned_items() {
+        let a = std::pin::pin!(Task {
+            id: 1,
+            link: Link::new(),
+        });
+        let b = std::pin::pin!(Task {
+            id: 2,
+            link: Link::new(),
+        });
+
+        {
+            let mut list = IntrusiveList::new();
+            list.push_back(a.as_ref());
+            list.push_back(b.as_ref());
+            assert_eq!(ids(&list), vec![1, 2]);
+        }
+
+        // Dropping the list unlinks the items
+        assert!(!a.link.is_linked());
+        assert!(!b.link.is_linked());
+    }
+
+    #[test]
+    fn guards() {
+        let a = task(1);
+        let mut list = IntrusiveList::new();
+        list.push_back(a.as_ref());
+
+        {
+            // Items that live shorter than the list
+            let mut b = std::pin::pin!(Task {
+                id: 2,
+                link: Link::new(),
+            });
+            let mut c = std::pin::pin!(Task {
+                id: 3,
+                link: Link::new(),
+            });
+
+            let mut guard_b = list.push_back_guard(b.as_mut());
+            assert_eq!(guard_b.item().id, 2);
+            {
+                let guard_c = guard_b.push_front_guard(c.as_mut());
+                assert!(guard_c.iter().map(|task| task.id).eq(vec![3, 1, 2]));
+                assert_eq!(guard_c.len(), 3);
+            }
+            assert!(guard_b.iter().map(|task| task.id).eq(vec![1, 2]));
+            assert!(!c.link.is_linked());
+            drop(guard_b);
+
+            assert!(!b.link.is_linked());
+            assert_eq!(ids(&list), vec![1]);
+
+            // Unlinked items can be linked again
+            let guard = list.push_front_guard(b.as_mut());
+            assert_eq!(guard.iter().len(), 2);
+        }
+
+        assert_eq!(ids(&list), vec![1]);
+        assert_eq!(list.pop_front().map(|t| t.id), Some(1));
+    }
+
+    #[test]
+    fn forgotten_guard() {
+        let a = task(1);
+        let mut list = IntrusiveList::new();
+        list.push_back(a.as_ref());
+
+        // Leaked, dropping an item of a forgotten guard aborts
+        let b: &'static mut Task = Box::leak(Box::new(Task {
+            id: 2,
+            link: Link::new(),
+        }));
+        let b_link: *const Link<Task> = &b.link;
+        std::mem::forget(list.push_front_guard(Pin::static_mut(b)));
+
+        // The list is not borrowed anymore, but b must not come out of it for 'a
+        let mut panics = |f: fn(&mut IntrusiveList<'_, Task>) -> bool| {
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| f(&mut list))).is_err()
+        };
+        assert!(panics(|list| list.pop_front().is_some()));
+        assert!(panics(|list| list.front().is_some()));
+        assert!(panics(|list| list.iter().count() > 0));
+        assert_eq!(list.len(), 2);
+
+        // Other items are fine
+        assert_eq!(list.pop_back().map(|t| t.id), Some(1));
+
+        drop(list);
+        let b_link = unsafe { &*b_link };
+        assert!(!b_link.is_linked());
+        assert!(b_link.guarded.get());
+    }
+}
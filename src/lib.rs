@@ -1,8 +1,51 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
+
+extern crate alloc;
+
 pub mod first;
 pub mod second;
 pub mod third;
 pub mod fourth;
 pub mod fifth;
+pub mod persistent_queue;
+pub mod treiber;
+pub mod ms_queue;
+#[cfg(feature = "std")]
+pub mod sync_queue;
+#[cfg(feature = "std")]
+pub mod bounded_queue;
+#[cfg(feature = "async")]
+pub mod async_queue;
+#[cfg(feature = "std")]
+pub mod spsc;
+#[cfg(feature = "std")]
+pub mod lru;
+pub mod intrusive;
+pub mod timer_wheel;
+pub mod skip_list;
+pub mod unrolled;
+pub mod small;
+pub mod sorted;
+pub mod pairing_heap;
+pub mod rope;
+pub mod graph;
+pub mod stream;
+pub mod xor_list;
+pub mod ring;
+pub mod ghost;
+pub mod arena;
+pub mod allocator;
+pub mod traits;
+pub mod pool;
+
+mod sync;
+mod dot;
+mod heap_size;
+#[cfg(feature = "serde")]
+mod serde_seq;
+#[cfg(feature = "proptest")]
+mod arbitrary;
 
 #[cfg(test)]
 mod tests {
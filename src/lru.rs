@@ -0,0 +1,221 @@
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::mem;
+
+use crate::arena::{self, Handle};
+
+// Least recently used cache: holds up to capacity entries, and when a new one doesn't fit,
+// throws out the one that wasn't touched for the longest time.
+//
+// The classic way - a doubly linked list of entries, most recently used at the front,
+// plus a map from a key to its node:
+// - get: map finds the node, the node is spliced out and moved to the front
+// - put: new node goes to the front, if the cache is full the back one is evicted,
+//   and its key (kept in the node, too) is removed from the map
+// all O(1). The list is arena::List, nodes are found again through its handles.
+//
+// Needs HashMap, so std only.
+pub struct LruCache<K, V> {
+    map: HashMap<K, Handle>,
+    // Most recently used first
+    list: arena::List<(K, V)>,
+    capacity: usize,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be non-zero");
+
+        LruCache {
+            map: HashMap::with_capacity(capacity),
+            list: arena::List::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    // Marks the entry as the most recently used
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_mut(key).map(|value| &*value)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let handle = *self.map.get(key)?;
+        self.list.move_to_front(handle);
+
+        self.list.get_mut(handle).map(|(_, value)| value)
+    }
+
+    // Looks, doesn't touch - the entry keeps its place
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let handle = *self.map.get(key)?;
+        self.list.get(handle).map(|(_, value)| value)
+    }
+
+    // Returns the old value if the key was there already, the entry becomes
+    // the most recently used either way. A new key in a full cache evicts
+    // the least recently used entry.
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(&handle) = self.map.get(&key) {
+            self.list.move_to_front(handle);
+            let (_, old) = self.list.get_mut(handle)?;
+            return Some(mem::replace(old, value));
+        }
+
+        if self.len() == self.capacity {
+            self.pop_lru();
+        }
+
+        let handle = self.list.push_front_handle((key.clone(), value));
+        self.map.insert(key, handle);
+        None
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let handle = self.map.remove(key)?;
+        self.list.remove(handle).map(|(_, value)| value)
+    }
+
+    // Evicts the least recently used entry
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        let (key, value) = self.list.pop_back()?;
+        self.map.remove(&key);
+        Some((key, value))
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.list.clear();
+    }
+
+    // Most recently used first, doesn't touch anything
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> + '_ {
+        self.list.iter().map(|(key, value)| (key, value))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::LruCache;
+
+    #[test]
+    fn basics() {
+        let mut cache = LruCache::new(2);
+        assert!(cache.is_empty());
+        assert_eq!(cache.get(&1), None);
+
+        assert_eq!(cache.put(1, "one"), None);
+        assert_eq!(cache.put(2, "two"), None);
+        assert_eq!(cache.len(), 2);
+
+        // Touch 1, so 2 is the one to go
+        assert_eq!(cache.get(&1), Some(&"one"));
+        assert_eq!(cache.put(3, "three"), None);
+        assert!(!cache.contains(&2));
+        assert!(cache.iter().eq(vec![(&3, &"three"), (&1, &"one")]));
+
+        // Updating an entry touches it
+        assert_eq!(cache.put(1, "uno"), Some("one"));
+        assert_eq!(cache.put(4, "four"), None);
+        assert!(!cache.contains(&3));
+        assert_eq!(cache.peek(&1), Some(&"uno"));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn peek_does_not_touch() {
+        let mut cache = LruCache::new(2);
+        cache.put("a", 1);
+        cache.put("b", 2);
+
+        assert_eq!(cache.peek("a"), Some(&1));
+        cache.put("c", 3);
+        assert!(!cache.contains("a"));
+
+        *cache.get_mut("b").unwrap() += 10;
+        cache.put("d", 4);
+        assert_eq!(cache.peek("b"), Some(&12));
+        assert!(!cache.contains("c"));
+    }
+
+    #[test]
+    fn remove_and_pop() {
+        let mut cache = LruCache::new(3);
+        for i in 0..3 {
+            cache.put(i.to_string(), i);
+        }
+
+        // Borrowed keys work, like with HashMap
+        assert_eq!(cache.remove("1"), Some(1));
+        assert_eq!(cache.remove("1"), None);
+        assert_eq!(cache.pop_lru(), Some(("0".to_string(), 0)));
+        assert_eq!(cache.len(), 1);
+
+        // Slots freed above are reused
+        cache.put("3".to_string(), 3);
+        cache.put("4".to_string(), 4);
+        cache.put("5".to_string(), 5);
+        assert!(cache.iter().map(|(_, &v)| v).eq(vec![5, 4, 3]));
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(cache.pop_lru(), None);
+    }
+
+    #[test]
+    fn many() {
+        let mut cache = LruCache::new(100);
+        for i in 0..10_000 {
+            cache.put(i, i * 2);
+            // Keep the first key alive
+            assert_eq!(cache.get(&0), Some(&0));
+        }
+
+        assert_eq!(cache.len(), 100);
+        assert!(cache.contains(&0));
+        assert!(cache.contains(&9_999));
+        assert!(!cache.contains(&9_900));
+    }
+
+    #[test]
+    #[should_panic(expected = "non-zero")]
+    fn zero_capacity() {
+        LruCache::<i32, i32>::new(0);
+    }
+}
@@ -0,0 +1,422 @@
+use alloc::boxed::Box;
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+use core::ptr::{self, null_mut};
+
+use crate::sync::{AtomicPtr, AtomicUsize, Ordering};
+
+// Lock-free MPMC queue (Michael-Scott queue). Like in fifth::List there is a head and a tail,
+// but both are atomic, and the list always starts with a dummy node:
+// head -> dummy -> a -> b -> c <- tail
+// Thanks to the dummy, push only touches tail (and tail.next), pop only touches head,
+// so producers and consumers don't fight over the same pointer.
+// Pop moves head forward, the node with the popped element becomes the new dummy.
+//
+// Tail can lag one node behind (push links the node first, moves tail second), everybody
+// who notices that, helps to move it forward - so nobody waits for the thread which was
+// preempted in between. That's what makes it lock-free.
+//
+// Freeing nodes: any thread in push or pop could have loaded a pointer to a node
+// which is unlinked in the meantime, so same as in treiber::Stack, nodes are freed only
+// when there is nobody else inside. Addresses are not reused while someone may still
+// hold them, so compare_exchange is safe from ABA.
+pub struct Queue<T> {
+    head: AtomicPtr<Node<T>>,
+    tail: AtomicPtr<Node<T>>,
+    threads_inside: AtomicUsize,
+    to_be_deleted: AtomicPtr<Node<T>>,
+    _marker: PhantomData<Box<Node<T>>>,
+}
+
+struct Node<T> {
+    // Uninitialized in the dummy node, element is moved out when the node becomes a dummy
+    elem: MaybeUninit<T>,
+    next: AtomicPtr<Node<T>>,
+    next_to_delete: *mut Node<T>,
+}
+
+impl<T> Node<T> {
+    fn new(elem: MaybeUninit<T>) -> *mut Self {
+        Box::into_raw(Box::new(Node {
+            elem,
+            next: AtomicPtr::new(null_mut()),
+            next_to_delete: null_mut(),
+        }))
+    }
+}
+
+unsafe impl<T: Send> Send for Queue<T> {}
+unsafe impl<T: Send> Sync for Queue<T> {}
+
+impl<T> Default for Queue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Queue<T> {
+    pub fn new() -> Self {
+        let dummy = Node::new(MaybeUninit::uninit());
+
+        Queue {
+            head: AtomicPtr::new(dummy),
+            tail: AtomicPtr::new(dummy),
+            threads_inside: AtomicUsize::new(0),
+            to_be_deleted: AtomicPtr::new(null_mut()),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.enter();
+
+        let head = self.head.load(Ordering::SeqCst);
+        let empty = unsafe { (*head).next.load(Ordering::SeqCst).is_null() };
+
+        self.leave(null_mut());
+
+        empty
+    }
+
+    pub fn push(&self, elem: T) {
+        let node = Node::new(MaybeUninit::new(elem));
+
+        self.enter();
+
+        loop {
+            let tail = self.tail.load(Ordering::SeqCst);
+            let next = unsafe { (*tail).next.load(Ordering::SeqCst) };
+
+            // Is the tail still the same, or we have inconsistent snapshot?
+            if tail != self.tail.load(Ordering::SeqCst) {
+                continue;
+            }
+
+            if next.is_null() {
+                // tail is really the last node, try to link ours after it
+                let linked = unsafe {
+                    (*tail)
+                        .next
+                        .compare_exchange(next, node, Ordering::SeqCst, Ordering::SeqCst)
+                        .is_ok()
+                };
+
+                if linked {
+                    // Move tail to our node, if it fails someone already helped us
+                    let _ =
+                        self.tail
+                            .compare_exchange(tail, node, Ordering::SeqCst, Ordering::SeqCst);
+                    break;
+                }
+            } else {
+                // Tail is lagging behind, help to move it
+                let _ = self
+                    .tail
+                    .compare_exchange(tail, next, Ordering::SeqCst, Ordering::SeqCst);
+            }
+        }
+
+        self.leave(null_mut());
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        self.enter();
+
+        loop {
+            let head = self.head.load(Ordering::SeqCst);
+            let tail = self.tail.load(Ordering::SeqCst);
+            let next = unsafe { (*head).next.load(Ordering::SeqCst) };
+
+            if head != self.head.load(Ordering::SeqCst) {
+                continue;
+            }
+
+            if head == tail {
+                if next.is_null() {
+                    // Only the dummy is left
+                    self.leave(null_mut());
+                    return None;
+                }
+
+                // Tail is lagging behind, it must not stay on the node we are about to
+                // unlink, so help to move it first
+                let _ = self
+                    .tail
+                    .compare_exchange(tail, next, Ordering::SeqCst, Ordering::SeqCst);
+            } else if self
+                .head
+                .compare_exchange(head, next, Ordering::SeqCst, Ordering::SeqCst)
+                .is_ok()
+            {
+                // We won, next is the new dummy, and its element is ours
+                let elem = unsafe { ptr::read((*next).elem.as_ptr()) };
+
+                // Old dummy is unlinked
+                self.leave(head);
+
+                return Some(elem);
+            }
+        }
+    }
+
+    fn enter(&self) {
+        self.threads_inside.fetch_add(1, Ordering::SeqCst);
+    }
+
+    // Leaves push or pop, unlinked node (if any) is freed now, or after everybody leaves.
+    // See treiber::Stack::try_reclaim for details.
+    fn leave(&self, unlinked: *mut Node<T>) {
+        if self.threads_inside.load(Ordering::SeqCst) == 1 {
+            let to_delete = self.to_be_deleted.swap(null_mut(), Ordering::SeqCst);
+
+            if self.threads_inside.fetch_sub(1, Ordering::SeqCst) == 1 {
+                unsafe {
+                    delete_nodes(to_delete);
+                }
+            } else if !to_delete.is_null() {
+                self.chain_pending_nodes(to_delete);
+            }
+
+            if !unlinked.is_null() {
+                unsafe {
+                    drop(Box::from_raw(unlinked));
+                }
+            }
+        } else {
+            if !unlinked.is_null() {
+                self.chain_pending_nodes(unlinked);
+            }
+
+            self.threads_inside.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    fn chain_pending_nodes(&self, first: *mut Node<T>) {
+        let mut last = first;
+
+        unsafe {
+            while !(*last).next_to_delete.is_null() {
+                last = (*last).next_to_delete;
+            }
+        }
+
+        let mut pending = self.to_be_deleted.load(Ordering::SeqCst);
+
+        loop {
+            unsafe {
+                (*last).next_to_delete = pending;
+            }
+
+            match self.to_be_deleted.compare_exchange_weak(
+                pending,
+                first,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(_) => return,
+                Err(current) => pending = current,
+            }
+        }
+    }
+}
+
+// Frees nodes linked with next_to_delete, they are former dummies, no elements inside
+unsafe fn delete_nodes<T>(mut node: *mut Node<T>) {
+    while !node.is_null() {
+        let next = (*node).next_to_delete;
+        drop(Box::from_raw(node));
+        node = next;
+    }
+}
+
+impl<T> Drop for Queue<T> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+
+        unsafe {
+            // Only the dummy is left
+            drop(Box::from_raw(self.head.load(Ordering::Relaxed)));
+            delete_nodes(self.to_be_deleted.load(Ordering::Relaxed));
+        }
+    }
+}
+
+#[cfg(all(test, not(loom)))]
+mod test {
+    use super::Queue;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn basics() {
+        let queue = Queue::new();
+        assert!(queue.is_empty());
+        assert_eq!(queue.pop(), None);
+
+        queue.push(1);
+        queue.push(2);
+        queue.push(3);
+        assert!(!queue.is_empty());
+
+        assert_eq!(queue.pop(), Some(1));
+        assert_eq!(queue.pop(), Some(2));
+
+        queue.push(4);
+        assert_eq!(queue.pop(), Some(3));
+        assert_eq!(queue.pop(), Some(4));
+        assert_eq!(queue.pop(), None);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn drop_frees_elements() {
+        let elem = Arc::new(());
+
+        {
+            let queue = Queue::new();
+            for _ in 0..10 {
+                queue.push(elem.clone());
+            }
+            queue.pop();
+        }
+
+        assert_eq!(Arc::strong_count(&elem), 1);
+    }
+
+    #[test]
+    fn mpmc() {
+        const PRODUCERS: usize = 4;
+        const CONSUMERS: usize = 4;
+        const PER_PRODUCER: usize = 10_000;
+
+        let queue = Arc::new(Queue::new());
+        let remaining = Arc::new(AtomicUsize::new(PRODUCERS * PER_PRODUCER));
+
+        let producers = (0..PRODUCERS)
+            .map(|p| {
+                let queue = queue.clone();
+                thread::spawn(move || {
+                    for i in 0..PER_PRODUCER {
+                        queue.push((p, i));
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let consumers = (0..CONSUMERS)
+            .map(|_| {
+                let queue = queue.clone();
+                let remaining = remaining.clone();
+                thread::spawn(move || {
+                    let mut popped = Vec::new();
+                    let mut last_seen = [None; PRODUCERS];
+
+                    while remaining.load(Ordering::SeqCst) > 0 {
+                        if let Some((p, i)) = queue.pop() {
+                            remaining.fetch_sub(1, Ordering::SeqCst);
+
+                            // Elements of one producer come out in FIFO order
+                            assert!(last_seen[p] < Some(i));
+                            last_seen[p] = Some(i);
+                            popped.push((p, i));
+                        } else {
+                            thread::yield_now();
+                        }
+                    }
+
+                    popped
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for producer in producers {
+            producer.join().unwrap();
+        }
+
+        let mut all = consumers
+            .into_iter()
+            .flat_map(|consumer| consumer.join().unwrap())
+            .collect::<Vec<_>>();
+
+        all.sort();
+        all.dedup();
+        assert_eq!(all.len(), PRODUCERS * PER_PRODUCER);
+        assert!(queue.is_empty());
+    }
+}
+
+#[cfg(all(test, loom))]
+mod loom_test {
+    use super::Queue;
+    use loom::sync::Arc;
+    use loom::thread;
+
+    #[test]
+    fn push_pop() {
+        loom::model(|| {
+            let queue = Arc::new(Queue::new());
+
+            let producer = {
+                let queue = queue.clone();
+                thread::spawn(move || {
+                    queue.push(1);
+                    queue.push(2);
+                })
+            };
+
+            // Whatever we got, it has to be in order
+            let first = queue.pop();
+            let second = queue.pop();
+
+            producer.join().unwrap();
+
+            match (first, second) {
+                (None, None) | (Some(1), Some(2)) => {}
+                (Some(1), None) => assert_eq!(queue.pop(), Some(2)),
+                (None, Some(1)) => assert_eq!(queue.pop(), Some(2)),
+                other => panic!("unexpected {:?}", other),
+            }
+        });
+    }
+
+    #[test]
+    fn concurrent_pops() {
+        loom::model(|| {
+            let queue = Arc::new(Queue::new());
+            queue.push(1);
+            queue.push(2);
+
+            let other = {
+                let queue = queue.clone();
+                thread::spawn(move || queue.pop())
+            };
+
+            let mine = queue.pop().unwrap();
+            let other = other.join().unwrap().unwrap();
+
+            assert_ne!(mine, other);
+            assert_eq!(queue.pop(), None);
+        });
+    }
+
+    #[test]
+    fn concurrent_pushes() {
+        loom::model(|| {
+            let queue = Arc::new(Queue::new());
+
+            let other = {
+                let queue = queue.clone();
+                thread::spawn(move || queue.push(1))
+            };
+
+            queue.push(2);
+            other.join().unwrap();
+
+            let mut popped = vec![queue.pop().unwrap(), queue.pop().unwrap()];
+            popped.sort();
+            assert_eq!(popped, vec![1, 2]);
+            assert_eq!(queue.pop(), None);
+        });
+    }
+}
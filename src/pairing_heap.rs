@@ -0,0 +1,298 @@
+use alloc::boxed::Box;
+use core::iter::{FromIterator, FusedIterator};
+
+// Pairing heap - a priority queue (min first) made of Box nodes, like second::List.
+// Every node is a root of a heap: it's smaller than (or equal to) all its children.
+// Children of a node are kept in a singly linked list - the node points at its first
+// child, and every child at its next sibling:
+//
+//        1               1
+//      / | \             |
+//     3  2  5    is     3 -> 2 -> 5
+//     |                 |
+//     4                 4
+//
+// - merge of two heaps: the bigger root becomes the first child of the smaller one, O(1)
+// - push: merge with a one-node heap, O(1)
+// - pop_min: the root goes away, and its children have to be merged into one heap.
+//   Merging them one by one could leave a root with n children again, so it's done
+//   in two passes: merge children in pairs, left to right, then merge the pairs, right
+//   to left. That's what keeps pop_min O(log n) amortized.
+//
+// Nodes can be nested as deep as the heap is big (and sibling lists are as long), so
+// nothing walks them recursively - the passes and Drop use loops.
+pub struct PairingHeap<T: Ord> {
+    root: Link<T>,
+    len: usize,
+}
+
+type Link<T> = Option<Box<Node<T>>>;
+
+struct Node<T> {
+    elem: T,
+    child: Link<T>,
+    sibling: Link<T>,
+}
+
+// Roots of both heaps don't have siblings, so neither has the result
+fn meld<T: Ord>(mut a: Box<Node<T>>, mut b: Box<Node<T>>) -> Box<Node<T>> {
+    if b.elem < a.elem {
+        core::mem::swap(&mut a, &mut b);
+    }
+
+    b.sibling = a.child.take();
+    a.child = Some(b);
+    a
+}
+
+fn meld_links<T: Ord>(a: Link<T>, b: Link<T>) -> Link<T> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(meld(a, b)),
+        (a, None) => a,
+        (None, b) => b,
+    }
+}
+
+// Two-pass merge of a sibling list
+fn merge_pairs<T: Ord>(mut first: Link<T>) -> Link<T> {
+    // Merged pairs, linked through sibling - the last one is at the top
+    let mut pairs = None;
+
+    while let Some(mut a) = first {
+        let mut pair = match a.sibling.take() {
+            Some(mut b) => {
+                first = b.sibling.take();
+                meld(a, b)
+            }
+            None => {
+                first = None;
+                a
+            }
+        };
+
+        pair.sibling = pairs;
+        pairs = Some(pair);
+    }
+
+    // Right to left, from the top of the stack
+    let mut heap = None;
+    while let Some(mut pair) = pairs {
+        pairs = pair.sibling.take();
+        heap = meld_links(heap, Some(pair));
+    }
+
+    heap
+}
+
+impl<T: Ord> Default for PairingHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> PairingHeap<T> {
+    pub const fn new() -> Self {
+        PairingHeap { root: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push(&mut self, elem: T) {
+        let node = Box::new(Node {
+            elem,
+            child: None,
+            sibling: None,
+        });
+
+        self.root = meld_links(self.root.take(), Some(node));
+        self.len += 1;
+    }
+
+    pub fn peek_min(&self) -> Option<&T> {
+        self.root.as_ref().map(|node| &node.elem)
+    }
+
+    pub fn pop_min(&mut self) -> Option<T> {
+        self.root.take().map(|mut node| {
+            self.root = merge_pairs(node.child.take());
+            self.len -= 1;
+            node.elem
+        })
+    }
+
+    // O(1), other is left empty
+    pub fn merge(&mut self, other: &mut PairingHeap<T>) {
+        self.root = meld_links(self.root.take(), other.root.take());
+        self.len += other.len;
+        other.len = 0;
+    }
+
+    pub fn clear(&mut self) {
+        *self = PairingHeap::new();
+    }
+}
+
+// A node with children is rotated: its first child takes its place, and the node
+// becomes the child's first sibling, taking the child's other siblings as its children.
+// Every rotation takes one node out of the child lists, so all of them end up in
+// one sibling list, which is dropped one by one.
+impl<T: Ord> Drop for PairingHeap<T> {
+    fn drop(&mut self) {
+        let mut cur = self.root.take();
+
+        while let Some(mut node) = cur {
+            cur = match node.child.take() {
+                Some(mut child) => {
+                    node.child = child.sibling.take();
+                    child.sibling = Some(node);
+                    Some(child)
+                }
+                // No children left, node is dropped here
+                None => node.sibling.take(),
+            };
+        }
+    }
+}
+
+impl<T: Ord> FromIterator<T> for PairingHeap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut heap = PairingHeap::new();
+        heap.extend(iter);
+        heap
+    }
+}
+
+impl<T: Ord> Extend<T> for PairingHeap<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push(elem);
+        }
+    }
+}
+
+// Elements come out sorted, smallest first
+pub struct IntoIter<T: Ord>(PairingHeap<T>);
+
+impl<T: Ord> IntoIterator for PairingHeap<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<T: Ord> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop_min()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T: Ord> ExactSizeIterator for IntoIter<T> {}
+
+impl<T: Ord> FusedIterator for IntoIter<T> {}
+
+#[cfg(test)]
+mod test {
+    use super::PairingHeap;
+    use std::rc::Rc;
+
+    #[test]
+    fn basics() {
+        let mut heap = PairingHeap::new();
+
+        // Check empty heap behaves right
+        assert_eq!(heap.pop_min(), None);
+        assert_eq!(heap.peek_min(), None);
+
+        // Populate heap
+        heap.push(3);
+        heap.push(1);
+        heap.push(4);
+        heap.push(1);
+        heap.push(5);
+        assert_eq!(heap.len(), 5);
+        assert_eq!(heap.peek_min(), Some(&1));
+
+        // Check normal removal
+        assert_eq!(heap.pop_min(), Some(1));
+        assert_eq!(heap.pop_min(), Some(1));
+        assert_eq!(heap.pop_min(), Some(3));
+
+        // Push some more just to make sure nothing's corrupted
+        heap.push(2);
+        heap.push(9);
+        assert_eq!(heap.pop_min(), Some(2));
+        assert_eq!(heap.pop_min(), Some(4));
+        assert_eq!(heap.pop_min(), Some(5));
+        assert_eq!(heap.pop_min(), Some(9));
+
+        // Check exhaustion
+        assert_eq!(heap.pop_min(), None);
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn merge() {
+        let mut a: PairingHeap<_> = vec![5, 1, 9].into_iter().collect();
+        let mut b: PairingHeap<_> = vec![4, 0, 7, 2].into_iter().collect();
+
+        a.merge(&mut b);
+        assert!(b.is_empty());
+        assert_eq!(b.pop_min(), None);
+        assert_eq!(a.len(), 7);
+        assert!(a.into_iter().eq(vec![0, 1, 2, 4, 5, 7, 9]));
+    }
+
+    #[test]
+    fn sorts() {
+        // Some pseudo random numbers, with duplicates
+        let elems: Vec<u32> = (0..1000).map(|i| (i * 7919) % 503).collect();
+        let heap: PairingHeap<_> = elems.iter().copied().collect();
+
+        let mut sorted = elems;
+        sorted.sort();
+        assert!(heap.into_iter().eq(sorted));
+    }
+
+    #[test]
+    fn drop_elements() {
+        let elem = Rc::new(());
+
+        {
+            let mut heap = PairingHeap::new();
+            for i in 0..10 {
+                heap.push((i, elem.clone()));
+            }
+            heap.pop_min();
+        }
+
+        assert_eq!(Rc::strong_count(&elem), 1);
+    }
+
+    #[test]
+    fn deep() {
+        // Decreasing pushes nest every node in the previous one, increasing make
+        // a long sibling list - neither may blow the stack
+        let mut heap: PairingHeap<_> = (0..100_000).rev().collect();
+        heap.extend(200_000..300_000);
+        heap.pop_min();
+        drop(heap);
+
+        let mut heap: PairingHeap<_> = (0..100_000).collect();
+        assert_eq!(heap.pop_min(), Some(0));
+        assert_eq!(heap.pop_min(), Some(1));
+    }
+}
@@ -0,0 +1,155 @@
+use crate::third::List;
+
+// Immutable FIFO queue made of two persistent stacks (Okasaki's batched queue):
+// elements are dequeued from the front list, and enqueued onto the back list,
+// which is kept in reversed order. When front runs out, back is reversed and becomes
+// the new front.
+//
+// Invariant: front is empty only if the whole queue is empty, so peek is always O(1).
+//
+// Every element is reversed at most once, so enqueue/dequeue are amortized O(1),
+// as long as every version of the queue is used once (like an ephemeral queue).
+// When the same old version is dequeued over and over, the reverse is done each time -
+// fixing that requires lazy evaluation (Okasaki's banker's queue).
+//
+// Reversing copies the elements (nodes of the back list are shared, we cannot relink them),
+// hence T: Clone.
+pub struct Queue<T> {
+    front: List<T>,
+    back: List<T>,
+}
+
+impl<T> Default for Queue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for Queue<T> {
+    fn clone(&self) -> Self {
+        Queue {
+            front: self.front.clone(),
+            back: self.back.clone(),
+        }
+    }
+}
+
+impl<T> Queue<T> {
+    pub const fn new() -> Self {
+        Queue {
+            front: List::new(),
+            back: List::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.front.len() + self.back.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        // See the invariant
+        self.front.is_empty()
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.front.head()
+    }
+}
+
+impl<T: Clone> Queue<T> {
+    // Restores the invariant
+    fn balanced(front: List<T>, back: List<T>) -> Self {
+        if front.is_empty() {
+            Queue {
+                front: back.rev(),
+                back: List::new(),
+            }
+        } else {
+            Queue { front, back }
+        }
+    }
+
+    pub fn enqueue(&self, elem: T) -> Self {
+        Queue::balanced(self.front.clone(), self.back.prepend(elem))
+    }
+
+    // Queue without the first element, empty queue stays empty
+    pub fn dequeue(&self) -> Self {
+        Queue::balanced(self.front.tail(), self.back.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Queue;
+
+    #[test]
+    fn basics() {
+        let queue = Queue::new();
+        assert!(queue.is_empty());
+        assert_eq!(queue.peek(), None);
+        assert!(queue.dequeue().is_empty());
+
+        let queue = queue.enqueue(1).enqueue(2).enqueue(3);
+        assert_eq!(queue.len(), 3);
+        assert_eq!(queue.peek(), Some(&1));
+
+        let queue = queue.dequeue();
+        assert_eq!(queue.peek(), Some(&2));
+
+        // Enqueue while front is not empty yet
+        let queue = queue.enqueue(4);
+        assert_eq!(queue.len(), 3);
+
+        let queue = queue.dequeue();
+        assert_eq!(queue.peek(), Some(&3));
+        let queue = queue.dequeue();
+        assert_eq!(queue.peek(), Some(&4));
+        let queue = queue.dequeue();
+        assert_eq!(queue.peek(), None);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn persistence() {
+        let base = Queue::new().enqueue(1).enqueue(2);
+
+        let left = base.enqueue(3);
+        let right = base.dequeue().enqueue(30);
+
+        // Old versions are untouched
+        assert_eq!(base.len(), 2);
+        assert_eq!(base.peek(), Some(&1));
+
+        assert_eq!(left.len(), 3);
+        assert_eq!(left.dequeue().dequeue().peek(), Some(&3));
+
+        assert_eq!(right.len(), 2);
+        assert_eq!(right.peek(), Some(&2));
+        assert_eq!(right.dequeue().peek(), Some(&30));
+    }
+
+    #[test]
+    fn fifo_order() {
+        let mut queue = Queue::new();
+        let mut expected = 0;
+
+        for i in 0..1000 {
+            queue = queue.enqueue(i);
+
+            if i % 3 == 0 {
+                assert_eq!(queue.peek(), Some(&expected));
+                queue = queue.dequeue();
+                expected += 1;
+            }
+        }
+
+        while let Some(&elem) = queue.peek() {
+            assert_eq!(elem, expected);
+            queue = queue.dequeue();
+            expected += 1;
+        }
+
+        assert_eq!(expected, 1000);
+    }
+}
@@ -0,0 +1,177 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::mem::MaybeUninit;
+use core::ptr;
+
+use crate::allocator::{Allocator, Global};
+
+// Free list of allocations for values of one type. Freeing a Box and allocating one of
+// the same size right after (popping and pushing a list in a loop) costs two trips to
+// the allocator. release keeps the allocation (up to capacity of them) after moving
+// the value out, and the next acquire puts the new value in it.
+//
+// That's how second::List reuses its nodes (List::with_pool_capacity), but nothing here
+// is specific to lists - any code that allocates and frees same sized Boxes in a loop
+// can use it.
+//
+// Allocations come from alloc (see allocator.rs), with allocator_api the Boxes carry it.
+pub struct NodePool<T, A: Allocator = Global> {
+    // Allocations with nothing in them
+    #[cfg(feature = "allocator_api")]
+    free: Vec<Box<MaybeUninit<T>, A>>,
+    #[cfg(not(feature = "allocator_api"))]
+    free: Vec<Box<MaybeUninit<T>>>,
+    capacity: usize,
+    alloc: A,
+}
+
+impl<T> NodePool<T> {
+    pub const fn new(capacity: usize) -> Self {
+        NodePool::new_in(capacity, Global)
+    }
+}
+
+impl<T, A: Allocator> NodePool<T, A> {
+    // Keeps up to capacity released allocations, 0 turns the pool off.
+    // Room for them is made as they come, so new is const, and costs nothing.
+    pub const fn new_in(capacity: usize, alloc: A) -> Self {
+        NodePool {
+            free: Vec::new(),
+            capacity,
+            alloc,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    // Released allocations waiting for acquire
+    pub fn available(&self) -> usize {
+        self.free.len()
+    }
+
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    // Frees all the kept allocations
+    pub fn clear(&mut self) {
+        self.free.clear();
+    }
+
+    // Box with value in it, in a reused allocation if there is one
+    #[cfg(feature = "allocator_api")]
+    pub fn acquire(&mut self, value: T) -> Box<T, A>
+    where
+        A: Clone,
+    {
+        match self.free.pop() {
+            Some(free) => Box::write(free, value),
+            None => Box::new_in(value, self.alloc.clone()),
+        }
+    }
+
+    #[cfg(not(feature = "allocator_api"))]
+    pub fn acquire(&mut self, value: T) -> Box<T> {
+        match self.free.pop() {
+            Some(free) => Box::write(free, value),
+            None => Box::new(value),
+        }
+    }
+
+    // Moves the value out, and keeps the allocation if there is room for it.
+    // boxed doesn't have to come from acquire, any Box with the same allocator will do.
+    #[cfg(feature = "allocator_api")]
+    pub fn release(&mut self, boxed: Box<T, A>) -> T {
+        if self.free.len() == self.capacity {
+            return *boxed;
+        }
+
+        let (ptr, alloc) = Box::into_raw_with_allocator(boxed);
+
+        // The value is moved out, and the Box gets back the memory it was in, as
+        // uninitialized. MaybeUninit<T> has the same layout as T, so it's freed the same way.
+        let value = unsafe { ptr::read(ptr) };
+        self.free
+            .push(unsafe { Box::from_raw_in(ptr.cast::<MaybeUninit<T>>(), alloc) });
+
+        value
+    }
+
+    #[cfg(not(feature = "allocator_api"))]
+    pub fn release(&mut self, boxed: Box<T>) -> T {
+        if self.free.len() == self.capacity {
+            return *boxed;
+        }
+
+        let ptr = Box::into_raw(boxed);
+
+        // Same as above
+        let value = unsafe { ptr::read(ptr) };
+        self.free
+            .push(unsafe { Box::from_raw(ptr.cast::<MaybeUninit<T>>()) });
+
+        value
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::NodePool;
+    use std::rc::Rc;
+
+    #[test]
+    fn reuses_allocations() {
+        let mut pool = NodePool::new(2);
+        assert_eq!(pool.capacity(), 2);
+
+        let a = pool.acquire(1);
+        let b = pool.acquire(2);
+        let c = pool.acquire(3);
+        let b_addr = &*b as *const i32;
+
+        assert_eq!(pool.release(a), 1);
+        assert_eq!(pool.release(b), 2);
+        // No room for the third one, it's freed
+        assert_eq!(pool.release(c), 3);
+        assert_eq!(pool.available(), 2);
+
+        // Last released comes back first
+        let d = pool.acquire(4);
+        assert_eq!(*d, 4);
+        assert_eq!(&*d as *const i32, b_addr);
+        assert_eq!(pool.available(), 1);
+
+        pool.clear();
+        assert_eq!(pool.available(), 0);
+        assert_eq!(pool.release(d), 4);
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let mut pool = NodePool::new(0);
+        let boxed = pool.acquire(String::from("a"));
+        assert_eq!(pool.release(boxed), "a");
+        assert_eq!(pool.available(), 0);
+    }
+
+    #[test]
+    fn drop_values() {
+        let elem = Rc::new(());
+
+        {
+            let mut pool = NodePool::new(4);
+            for _ in 0..3 {
+                let boxed = pool.acquire(elem.clone());
+                pool.release(boxed);
+            }
+            let _kept = pool.acquire(elem.clone());
+            // Values moved out by release are dropped by the caller,
+            // the pool keeps only the memory
+            assert_eq!(Rc::strong_count(&elem), 2);
+        }
+
+        assert_eq!(Rc::strong_count(&elem), 1);
+    }
+}
@@ -0,0 +1,442 @@
+use alloc::boxed::Box;
+use core::fmt;
+use core::iter::FromIterator;
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+use core::ptr::null_mut;
+
+// Circular singly linked list - the last node points back at the first one, there are
+// no ends, only a cursor: the current node. rotate moves the cursor one node forward,
+// push and pop work at the cursor, all O(1). Good for round-robin (take the current task,
+// rotate) and Josephus-style problems (rotate k - 1 times, pop).
+//
+//   last -> [current] -> [a] -> [b] -> last -> [current] -> ...
+//
+// The list keeps a pointer to the node *before* the cursor, not to the cursor itself.
+// A singly linked node can't unlink itself (its prev has to point elsewhere), but from
+// last everything is at hand: last.next is the current node, and pushing/popping only
+// changes last.next.
+//
+// Unsafe contract:
+// - every node is allocated with Box::into_raw in insert_after_last, and freed exactly once
+//   with Box::from_raw in pop, after it's taken out of the ring
+// - last is null iff the ring is empty, otherwise following next from last comes back
+//   to last after exactly len steps (a single node points at itself)
+// - references to nodes live only as long as the borrow of the list (peek, iter),
+//   and the list is never modified while they exist
+//
+// Drop can't just drop the nodes one after another the way Box does it in second::List -
+// the cycle would never end, and nodes are not Boxes anyway. It pops until the ring is
+// empty, which breaks the cycle at the first pop.
+pub struct List<T> {
+    last: *mut Node<T>,
+    len: usize,
+    _marker: PhantomData<Box<Node<T>>>,
+}
+
+struct Node<T> {
+    elem: T,
+    next: *mut Node<T>,
+}
+
+// Nodes are owned by the list, same as with Box
+unsafe impl<T: Send> Send for List<T> {}
+unsafe impl<T: Sync> Sync for List<T> {}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> List<T> {
+    pub const fn new() -> Self {
+        List {
+            last: null_mut(),
+            len: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Puts a new node right after last, returns it. Empty ring gets a node pointing at itself.
+    fn insert_after_last(&mut self, elem: T) -> *mut Node<T> {
+        let node = Box::into_raw(Box::new(Node {
+            elem,
+            next: null_mut(),
+        }));
+
+        unsafe {
+            if self.last.is_null() {
+                (*node).next = node;
+                self.last = node;
+            } else {
+                (*node).next = (*self.last).next;
+                (*self.last).next = node;
+            }
+        }
+
+        self.len += 1;
+        node
+    }
+
+    // New element becomes the current one, the old current is next after it
+    pub fn push(&mut self, elem: T) {
+        self.insert_after_last(elem);
+    }
+
+    // New element goes just behind the cursor, so it's the last one to be reached
+    // by rotating - new task at the end of a round-robin queue
+    pub fn push_back(&mut self, elem: T) {
+        self.last = self.insert_after_last(elem);
+    }
+
+    // Takes out the current element, the next one becomes current
+    pub fn pop(&mut self) -> Option<T> {
+        if self.last.is_null() {
+            return None;
+        }
+
+        unsafe {
+            let node = (*self.last).next;
+            if node == self.last {
+                // The only node
+                self.last = null_mut();
+            } else {
+                (*self.last).next = (*node).next;
+            }
+
+            self.len -= 1;
+            Some(Box::from_raw(node).elem)
+        }
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        unsafe { self.last.as_ref().map(|last| &(*last.next).elem) }
+    }
+
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        unsafe { self.last.as_mut().map(|last| &mut (*last.next).elem) }
+    }
+
+    // Cursor moves one node forward, the current element becomes the last one
+    pub fn rotate(&mut self) {
+        if !self.last.is_null() {
+            unsafe { self.last = (*self.last).next };
+        }
+    }
+
+    // Same as rotate n times, but never goes around more than once
+    pub fn rotate_by(&mut self, n: usize) {
+        if self.len == 0 {
+            return;
+        }
+
+        for _ in 0..n % self.len {
+            self.rotate();
+        }
+    }
+}
+
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+// Elements end up in the same order, the first one is current
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = List::new();
+        list.extend(iter);
+        list
+    }
+}
+
+// Goes behind the cursor, one after another, same as push_back
+impl<T> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push_back(elem);
+        }
+    }
+}
+
+// From references to Copy elements, same as second::List
+impl<'a, T: Copy + 'a> Extend<&'a T> for List<T> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<'a, T: Copy + 'a> FromIterator<&'a T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = &'a T>>(iter: I) -> Self {
+        iter.into_iter().copied().collect()
+    }
+}
+
+pub struct IntoIter<T>(List<T>);
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
+// One lap, starting at the cursor. There's no null to stop at, so it counts nodes instead.
+pub struct Iter<'a, T> {
+    next: *mut Node<T>,
+    len: usize,
+    _marker: PhantomData<&'a T>,
+}
+
+pub struct IterMut<'a, T> {
+    next: *mut Node<T>,
+    len: usize,
+    _marker: PhantomData<&'a mut T>,
+}
+
+impl<T> List<T> {
+    fn current(&self) -> *mut Node<T> {
+        if self.last.is_null() {
+            null_mut()
+        } else {
+            unsafe { (*self.last).next }
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.current(),
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            next: self.current(),
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+
+        unsafe {
+            let node = &*self.next;
+            self.next = node.next;
+            Some(&node.elem)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> FusedIterator for Iter<'a, T> {}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+
+        // Every node is visited at most once in a lap, so the &mut never alias
+        unsafe {
+            let node = &mut *self.next;
+            self.next = node.next;
+            Some(&mut node.elem)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+
+impl<'a, T> FusedIterator for IterMut<'a, T> {}
+
+#[cfg(test)]
+mod test {
+    use super::List;
+    use std::rc::Rc;
+
+    #[test]
+    fn basics() {
+        let mut list = List::new();
+
+        // Check empty list behaves right
+        assert_eq!(list.pop(), None);
+        assert_eq!(list.peek(), None);
+        list.rotate();
+        list.rotate_by(3);
+        assert!(list.is_empty());
+
+        // Populate list
+        list.push(1);
+        assert_eq!(list.peek(), Some(&1));
+        // A single node goes around to itself
+        list.rotate();
+        assert_eq!(list.peek(), Some(&1));
+
+        list.push(2);
+        list.push(3);
+        assert_eq!(list.len(), 3);
+        assert!(list.iter().copied().eq(vec![3, 2, 1]));
+
+        list.rotate();
+        assert_eq!(list.peek(), Some(&2));
+        assert!(list.iter().copied().eq(vec![2, 1, 3]));
+
+        // Check normal removal
+        assert_eq!(list.pop(), Some(2));
+        assert_eq!(list.peek(), Some(&1));
+
+        // Push some more just to make sure nothing's corrupted
+        list.push_back(4);
+        if let Some(elem) = list.peek_mut() {
+            *elem = 10;
+        }
+        assert!(list.iter().copied().eq(vec![10, 3, 4]));
+
+        // Check exhaustion
+        assert_eq!(list.pop(), Some(10));
+        assert_eq!(list.pop(), Some(3));
+        assert_eq!(list.pop(), Some(4));
+        assert_eq!(list.pop(), None);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn rotate_by() {
+        let mut list: List<_> = (0..5).collect();
+        assert!(list.iter().copied().eq(0..5));
+
+        list.rotate_by(2);
+        assert!(list.iter().copied().eq(vec![2, 3, 4, 0, 1]));
+        list.rotate_by(5 * 1000 + 3);
+        assert!(list.iter().copied().eq(0..5));
+    }
+
+    #[test]
+    fn round_robin() {
+        // (task, steps left)
+        let mut tasks: List<_> = vec![('a', 3), ('b', 1), ('c', 2)].into_iter().collect();
+        let mut order = vec![];
+
+        while let Some((task, left)) = tasks.peek_mut() {
+            order.push(*task);
+            *left -= 1;
+            if *left == 0 {
+                tasks.pop();
+            } else {
+                tasks.rotate();
+            }
+        }
+
+        assert_eq!(order, vec!['a', 'b', 'c', 'a', 'c', 'a']);
+    }
+
+    #[test]
+    fn josephus() {
+        // 7 people, every 3rd one is out
+        let mut circle: List<_> = (1..=7).collect();
+        let mut out = vec![];
+
+        while !circle.is_empty() {
+            circle.rotate_by(2);
+            out.push(circle.pop().unwrap());
+        }
+
+        assert_eq!(out, vec![3, 6, 2, 7, 5, 1, 4]);
+    }
+
+    #[test]
+    fn iter_mut() {
+        let mut list: List<_> = (0..4).collect();
+        list.rotate();
+        for elem in list.iter_mut() {
+            *elem *= 10;
+        }
+
+        assert_eq!(list.iter_mut().len(), 4);
+        assert_eq!(format!("{:?}", list), "[10, 20, 30, 0]");
+        assert!(list.into_iter().eq(vec![10, 20, 30, 0]));
+    }
+
+    #[test]
+    fn drop_elements() {
+        let elem = Rc::new(());
+
+        {
+            let mut list = List::new();
+            for _ in 0..10 {
+                list.push(elem.clone());
+            }
+            list.rotate_by(3);
+            list.pop();
+        }
+
+        assert_eq!(Rc::strong_count(&elem), 1);
+
+        // Dropping a long ring doesn't recurse
+        let list: List<_> = (0..100_000).collect();
+        drop(list);
+    }
+
+    #[test]
+    fn extend_and_collect_copied() {
+        let mut list: List<i32> = [1, 2].iter().collect();
+        list.extend(&[3, 4]);
+        assert!(list.into_iter().eq(1..=4));
+    }
+}
@@ -0,0 +1,442 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::iter::FusedIterator;
+use core::ops::{Bound, RangeBounds};
+
+// Rope - text kept as a singly linked list of chunks, every chunk its own String:
+//
+// head -> "Hello, " -> "wor" -> "ld!" -> None
+//
+// Inserting in the middle of a String moves everything behind it. Here only the chunk
+// the text goes to is touched: small insertions go into the chunk, bigger ones split it,
+// and link new chunks in between. Removal drops whole chunks, and cuts the ones at
+// the ends of the range. Positions are in chars, not bytes, like an editor cursor.
+//
+// Finding a position is still a walk over the chunks, O(n / CHUNK) - a real editor would
+// keep the chunks in a tree, but this is a lists crate.
+//
+// Every chunk keeps its length in chars, so walks don't have to count them,
+// and chunks are never empty.
+pub struct Rope {
+    head: Link,
+    // In chars
+    len: usize,
+}
+
+type Link = Option<Box<Chunk>>;
+
+struct Chunk {
+    text: String,
+    chars: usize,
+    next: Link,
+}
+
+// Chunks grow up to that many bytes, longer text is cut into more of them
+const CHUNK: usize = 64;
+
+// Byte position of the char_idx'th char, end of the text for char_idx == number of chars
+fn byte_index(text: &str, char_idx: usize) -> usize {
+    text.char_indices()
+        .nth(char_idx)
+        .map_or(text.len(), |(index, _)| index)
+}
+
+// Cuts text into chunks, and links them in front of next
+fn chunked(mut text: &str, mut next: Link) -> Link {
+    let mut pieces = Vec::new();
+    while !text.is_empty() {
+        let mut end = text.len().min(CHUNK);
+        // Don't cut a char in half
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        pieces.push(&text[..end]);
+        text = &text[end..];
+    }
+
+    for piece in pieces.into_iter().rev() {
+        next = Some(Box::new(Chunk {
+            text: String::from(piece),
+            chars: piece.chars().count(),
+            next,
+        }));
+    }
+
+    next
+}
+
+impl Chunk {
+    // char_idx is within the chunk, or just at its end. Might leave the chunk empty
+    // (when text goes in front of it), the rope unlinks it then.
+    fn insert(&mut self, char_idx: usize, text: &str, chars: usize) {
+        let at = byte_index(&self.text, char_idx);
+
+        if self.text.len() + text.len() <= CHUNK {
+            self.text.insert_str(at, text);
+            self.chars += chars;
+            return;
+        }
+
+        // Doesn't fit, the chunk is split at char_idx, and new ones go between the halves
+        let mut next = self.next.take();
+
+        let right = self.text.split_off(at);
+        if !right.is_empty() {
+            next = Some(Box::new(Chunk {
+                text: right,
+                chars: self.chars - char_idx,
+                next,
+            }));
+        }
+        self.chars = char_idx;
+
+        self.next = chunked(text, next);
+    }
+}
+
+impl Default for Rope {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Rope {
+    pub const fn new() -> Self {
+        Rope { head: None, len: 0 }
+    }
+
+    // In chars
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn insert(&mut self, char_idx: usize, text: &str) {
+        assert!(
+            char_idx <= self.len,
+            "char index {} out of bounds, len is {}",
+            char_idx,
+            self.len
+        );
+
+        if text.is_empty() {
+            return;
+        }
+
+        let chars = text.chars().count();
+        self.len += chars;
+
+        let mut idx = char_idx;
+        let mut link = &mut self.head;
+        loop {
+            let node = match link {
+                Some(node) => node,
+                // Empty rope
+                None => {
+                    *link = chunked(text, None);
+                    return;
+                }
+            };
+
+            // Position at the end of a chunk goes to that chunk, not the next one
+            if idx > node.chars {
+                idx -= node.chars;
+                link = &mut link.as_mut().unwrap().next;
+                continue;
+            }
+
+            node.insert(idx, text, chars);
+            if node.chars == 0 {
+                let next = node.next.take();
+                *link = next;
+            }
+            return;
+        }
+    }
+
+    pub fn push_str(&mut self, text: &str) {
+        self.insert(self.len, text);
+    }
+
+    // Range is in chars
+    pub fn remove<R: RangeBounds<usize>>(&mut self, range: R) {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.checked_add(1).expect("range start overflows usize"),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.checked_add(1).expect("range end overflows usize"),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len,
+        };
+        assert!(
+            start <= end,
+            "range starts at {} but ends at {}",
+            start,
+            end
+        );
+        assert!(
+            end <= self.len,
+            "range end {} out of bounds, len is {}",
+            end,
+            self.len
+        );
+
+        let mut idx = start;
+        let mut count = end - start;
+        self.len -= count;
+
+        let mut link = &mut self.head;
+        while count > 0 {
+            let node = link.as_mut().unwrap();
+
+            if idx >= node.chars {
+                idx -= node.chars;
+                link = &mut link.as_mut().unwrap().next;
+                continue;
+            }
+
+            let removed = count.min(node.chars - idx);
+            let from = byte_index(&node.text, idx);
+            let to = from + byte_index(&node.text[from..], removed);
+            node.text.replace_range(from..to, "");
+            node.chars -= removed;
+
+            // The rest of the range starts at the beginning of the next chunk
+            count -= removed;
+            idx = 0;
+
+            if node.chars == 0 {
+                let next = node.next.take();
+                *link = next;
+            } else {
+                link = &mut link.as_mut().unwrap().next;
+            }
+        }
+    }
+
+    pub fn char_at(&self, char_idx: usize) -> Option<char> {
+        let mut idx = char_idx;
+        let mut link = &self.head;
+
+        while let Some(node) = link {
+            if idx < node.chars {
+                return node.text[byte_index(&node.text, idx)..].chars().next();
+            }
+            idx -= node.chars;
+            link = &node.next;
+        }
+
+        None
+    }
+
+    pub fn chunks(&self) -> Chunks<'_> {
+        Chunks {
+            next: self.head.as_deref(),
+        }
+    }
+
+    pub fn chars(&self) -> Chars<'_> {
+        Chars {
+            chunks: self.chunks(),
+            chars: "".chars(),
+            len: self.len,
+        }
+    }
+}
+
+impl From<&str> for Rope {
+    fn from(text: &str) -> Self {
+        Rope {
+            head: chunked(text, None),
+            len: text.chars().count(),
+        }
+    }
+}
+
+impl Drop for Rope {
+    fn drop(&mut self) {
+        let mut chunk = self.head.take();
+
+        while let Some(boxed) = chunk {
+            chunk = boxed.next;
+        }
+    }
+}
+
+impl fmt::Display for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for chunk in self.chunks() {
+            f.write_str(chunk)?;
+        }
+        Ok(())
+    }
+}
+
+// Shows how the text is chunked
+impl fmt::Debug for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.chunks()).finish()
+    }
+}
+
+pub struct Chunks<'a> {
+    next: Option<&'a Chunk>,
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|chunk| {
+            self.next = chunk.next.as_deref();
+            chunk.text.as_str()
+        })
+    }
+}
+
+impl<'a> FusedIterator for Chunks<'a> {}
+
+pub struct Chars<'a> {
+    chunks: Chunks<'a>,
+    // Of the current chunk
+    chars: core::str::Chars<'a>,
+    // Chars left
+    len: usize,
+}
+
+impl<'a> Iterator for Chars<'a> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(c) = self.chars.next() {
+                self.len -= 1;
+                return Some(c);
+            }
+
+            self.chars = self.chunks.next()?.chars();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a> ExactSizeIterator for Chars<'a> {}
+
+impl<'a> FusedIterator for Chars<'a> {}
+
+#[cfg(test)]
+mod test {
+    use super::{Rope, CHUNK};
+
+    #[test]
+    fn basics() {
+        let mut rope = Rope::new();
+        assert!(rope.is_empty());
+        assert_eq!(rope.char_at(0), None);
+
+        rope.insert(0, "world");
+        rope.insert(0, "Hello ");
+        rope.push_str("!");
+        rope.insert(5, ",");
+        assert_eq!(rope.to_string(), "Hello, world!");
+        assert_eq!(rope.len(), 13);
+
+        assert_eq!(rope.char_at(0), Some('H'));
+        assert_eq!(rope.char_at(7), Some('w'));
+        assert_eq!(rope.char_at(13), None);
+
+        rope.remove(5..12);
+        assert_eq!(rope.to_string(), "Hello!");
+        rope.remove(..);
+        assert!(rope.is_empty());
+        assert_eq!(rope.chunks().count(), 0);
+    }
+
+    #[test]
+    fn chunks() {
+        let text: String = (0..200)
+            .map(|i| char::from(b'a' + (i % 26) as u8))
+            .collect();
+        let mut rope = Rope::from(text.as_str());
+        let mut expected = text.clone();
+
+        assert_eq!(rope.chunks().count(), 4);
+        assert!(rope.chunks().all(|chunk| chunk.len() <= CHUNK));
+
+        // Big insert in the middle of a chunk splits it
+        let big = "X".repeat(100);
+        rope.insert(30, &big);
+        expected.insert_str(30, &big);
+        assert_eq!(rope.to_string(), expected);
+        assert!(rope
+            .chunks()
+            .all(|chunk| !chunk.is_empty() && chunk.len() <= CHUNK));
+
+        // Removal across chunks
+        rope.remove(20..=250);
+        expected.replace_range(20..=250, "");
+        assert_eq!(rope.to_string(), expected);
+        assert_eq!(rope.len(), expected.len());
+        assert!(rope.chars().eq(expected.chars()));
+        assert_eq!(rope.chars().len(), expected.len());
+
+        // Small insert at the very front, into a full chunk
+        rope.insert(0, &big);
+        expected.insert_str(0, &big);
+        assert_eq!(rope.to_string(), expected);
+        assert!(rope.chunks().all(|chunk| !chunk.is_empty()));
+    }
+
+    #[test]
+    fn unicode() {
+        // Positions are chars, whatever their size in bytes
+        let mut rope = Rope::from("zażółć");
+        rope.insert(3, "🦀");
+        assert_eq!(rope.to_string(), "zaż🦀ółć");
+        assert_eq!(rope.char_at(3), Some('🦀'));
+        assert_eq!(rope.char_at(6), Some('ć'));
+
+        // Chunks are cut at char boundaries
+        let crabs = "🦀".repeat(50);
+        rope.insert(1, &crabs);
+        assert_eq!(rope.len(), 57);
+        assert_eq!(rope.char_at(50), Some('🦀'));
+        assert_eq!(rope.char_at(51), Some('a'));
+
+        rope.remove(1..51);
+        assert_eq!(rope.to_string(), "zaż🦀ółć");
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn insert_out_of_bounds() {
+        let mut rope = Rope::from("abc");
+        rope.insert(4, "d");
+    }
+
+    #[test]
+    #[should_panic(expected = "overflows usize")]
+    fn remove_inclusive_max() {
+        let mut rope = Rope::from("hello");
+        rope.remove(..=usize::MAX);
+    }
+
+    #[test]
+    fn long() {
+        // Dropping many chunks doesn't recurse
+        let text = "a".repeat(CHUNK * 100_000);
+        let rope = Rope::from(text.as_str());
+        assert_eq!(rope.chunks().count(), 100_000);
+    }
+}
@@ -1,22 +1,276 @@
-use std::{fmt::Debug, ops::Deref};
-
-#[derive(PartialEq, Debug)]
-pub struct List<T> {
-    head: Link<T>,
+use alloc::boxed::Box;
+use alloc::collections::{LinkedList, VecDeque};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt::{self, Debug, Display};
+use core::hash::{Hash, Hasher};
+use core::iter::{FromIterator, FusedIterator};
+use core::marker::PhantomData;
+use core::ops::{Bound, Index, IndexMut, RangeBounds};
+
+use crate::allocator::{Allocator, Global};
+use crate::dot::Dot;
+use crate::pool::NodePool;
+use crate::traits::Stack;
+
+// Nodes are allocated with alloc (see allocator.rs), every Box keeps its own copy of it,
+// to know where to free the node
+pub struct List<T, A: Allocator = Global> {
+    head: Link<T, A>,
+    // Number of nodes, kept up to date by everything which links or unlinks them
+    len: usize,
+    alloc: A,
+    // Popping and pushing in a loop frees a node and allocates the same one right back.
+    // The pool keeps up to capacity popped nodes (their elements moved out), and push takes
+    // them before asking the allocator. Capacity is 0, so nothing is kept, unless the list
+    // was made with with_pool_capacity.
+    pool: NodePool<Node<T, A>, A>,
+    // Most elements try_push lets in, None for no limit, see bounded
+    max_len: Option<usize>,
 }
-#[derive(PartialEq, Debug)]
-struct Node<T> {
+struct Node<T, A: Allocator> {
     elem: T,
-    next: Link<T>,
+    next: Link<T, A>,
+    // On stable Box has no allocator parameter, and A would be used only recursively
+    _alloc: PhantomData<A>,
+}
+type Link<T, A> = Option<NodeBox<T, A>>;
+
+#[cfg(feature = "allocator_api")]
+type NodeBox<T, A> = Box<Node<T, A>, A>;
+#[cfg(not(feature = "allocator_api"))]
+type NodeBox<T, A> = Box<Node<T, A>>;
+
+impl<T, A: Allocator> Node<T, A> {
+    fn new(elem: T, next: Link<T, A>) -> Self {
+        Node {
+            elem,
+            next,
+            _alloc: PhantomData,
+        }
+    }
+
+    fn boxed(elem: T, next: Link<T, A>, alloc: A) -> NodeBox<T, A> {
+        let node = Node::new(elem, next);
+
+        #[cfg(feature = "allocator_api")]
+        let boxed = Box::new_in(node, alloc);
+
+        #[cfg(not(feature = "allocator_api"))]
+        let boxed = {
+            let _ = alloc;
+            Box::new(node)
+        };
+
+        boxed
+    }
+}
+
+impl<T, A: Allocator + Clone + Default> Default for List<T, A> {
+    fn default() -> Self {
+        Self::new_in(A::default())
+    }
 }
-type Link<T> = Option<Box<Node<T>>>;
 
 impl<T> List<T> {
-    pub fn new() -> Self {
-        List { head: None }
+    // Spelled out, new_in clones alloc, and clone is not const
+    pub const fn new() -> Self {
+        List {
+            head: None,
+            len: 0,
+            pool: NodePool::new_in(0, Global),
+            alloc: Global,
+            max_len: None,
+        }
+    }
+
+    pub fn bounded(max_len: usize) -> Self {
+        List::bounded_in(max_len, Global)
+    }
+
+    pub fn with_pool_capacity(capacity: usize) -> Self {
+        List::with_pool_capacity_in(capacity, Global)
+    }
+
+    // List of n elements, f(i) gives the i-th one. f is called in order, from 0 up,
+    // nodes are appended at the tail as they come (see Extend), so it's O(n).
+    pub fn from_fn<F>(n: usize, f: F) -> Self
+    where
+        F: FnMut(usize) -> T,
+    {
+        (0..n).map(f).collect()
+    }
+}
+
+impl<T, A: Allocator> List<T, A> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn max_len(&self) -> Option<usize> {
+        self.max_len
+    }
+
+    // Never true for unbounded lists
+    pub fn is_full(&self) -> bool {
+        matches!(self.max_len, Some(max_len) if self.len >= max_len)
+    }
+
+    // Bounds check is O(1), but getting to the element is still O(n) - it's a list,
+    // not a Vec, don't use it in a loop over all indices, use iter
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+
+        self.iter().nth(index)
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+
+        self.iter_mut().nth(index)
+    }
+
+    // n-th element counting from the back, 0 is the last one. The classic two cursors:
+    // lead goes n nodes ahead first, then both move together, and when lead falls off
+    // the end, the other one is n + 1 nodes before it. One pass, len is not needed.
+    pub fn nth_from_back(&self, n: usize) -> Option<&T> {
+        let mut lead = self.head.as_deref();
+        for _ in 0..n {
+            lead = lead?.next.as_deref();
+        }
+
+        let mut trail = self.head.as_deref();
+        while let Some(node) = lead?.next.as_deref() {
+            lead = Some(node);
+            trail = trail?.next.as_deref();
+        }
+
+        trail.map(|node| &node.elem)
+    }
+
+    // Element at len / 2, for even lengths the first one of the second half:
+    // [1, 2, 3] -> 2, [1, 2, 3, 4] -> 3. Fast and slow cursors, fast goes two nodes
+    // for every one of slow, when it gets to the end slow is in the middle.
+    pub fn middle(&self) -> Option<&T> {
+        let mut slow = self.head.as_deref()?;
+        let mut fast = slow;
+
+        while let Some(next) = fast.next.as_deref() {
+            slow = slow.next.as_deref()?;
+            fast = match next.next.as_deref() {
+                Some(node) => node,
+                None => break,
+            };
+        }
+
+        Some(&slow.elem)
+    }
+
+    // Two cursors can't walk the same nodes while one of them is &mut, but we do know len,
+    // so it's the element at len - 1 - n, also found in one pass
+    pub fn nth_from_back_mut(&mut self, n: usize) -> Option<&mut T> {
+        let index = self.len.checked_sub(n)?.checked_sub(1)?;
+        self.get_mut(index)
+    }
+
+    // Searches are linear, they stop at the first match
+    pub fn contains(&self, x: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|elem| elem == x)
+    }
+
+    pub fn find<P>(&self, mut predicate: P) -> Option<&T>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.iter().find(|elem| predicate(elem))
+    }
+
+    pub fn position<P>(&self, predicate: P) -> Option<usize>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.iter().position(predicate)
+    }
+}
+
+// list[i], panics when out of bounds, same as Vec. O(n), see get.
+impl<T, A: Allocator> Index<usize> for List<T, A> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        let len = self.len;
+
+        self.get(index).unwrap_or_else(|| {
+            panic!(
+                "index out of bounds: the len is {} but the index is {}",
+                len, index
+            )
+        })
+    }
+}
+
+impl<T, A: Allocator> IndexMut<usize> for List<T, A> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let len = self.len;
+
+        self.get_mut(index).unwrap_or_else(|| {
+            panic!(
+                "index out of bounds: the len is {} but the index is {}",
+                len, index
+            )
+        })
+    }
+}
+
+impl<T, A: Allocator + Clone> List<T, A> {
+    pub fn new_in(alloc: A) -> Self {
+        List::with_pool_capacity_in(0, alloc)
+    }
+
+    // Keeps up to capacity popped nodes for reuse by the next pushes, see pool
+    pub fn with_pool_capacity_in(capacity: usize, alloc: A) -> Self {
+        List {
+            head: None,
+            len: 0,
+            pool: NodePool::new_in(capacity, alloc.clone()),
+            alloc,
+            max_len: None,
+        }
     }
 
-    pub fn peek(&mut self) -> Option<&T> {
+    // List used as a bounded buffer: try_push gives the element back once there are
+    // max_len of them. Only try_push looks at the limit - push, push_back, extend and
+    // the rest still add whatever they are given, that's what they promise.
+    pub fn bounded_in(max_len: usize, alloc: A) -> Self {
+        let mut list = List::new_in(alloc);
+        list.max_len = Some(max_len);
+        list
+    }
+
+    // For the chains of nodes cut off from other lists
+    fn from_head(head: Link<T, A>, len: usize, alloc: A) -> Self {
+        List {
+            head,
+            len,
+            pool: NodePool::new_in(0, alloc.clone()),
+            alloc,
+            max_len: None,
+        }
+    }
+
+    pub fn peek(&self) -> Option<&T> {
         self.head.as_ref().map(|elem| &elem.elem)
     }
 
@@ -27,12 +281,11 @@ impl<T> List<T> {
     pub fn push(&mut self, elem: T) {
         // head -> Node
         // new_node -> Node, head -> Empty
-        let new_node = Box::new(Node {
-            elem: elem,
-
+        let new_node = self.pool.acquire(Node::new(
+            elem,
             // Takes the value out of the option, leaving a None in its place.
-            next: self.head.take(), // mem::replace(&mut self.head, None),
-        });
+            self.head.take(), // mem::replace(&mut self.head, None),
+        ));
 
         // cannot write:
         // let new_node = Box::new(Node{
@@ -48,21 +301,118 @@ impl<T> List<T> {
 
         // head -> new_node -> Node
         self.head = Some(new_node);
+        self.len += 1;
+    }
+
+    // push for bounded lists, Err(elem) when the list is full
+    pub fn try_push(&mut self, elem: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(elem);
+        }
+
+        self.push(elem);
+        Ok(())
     }
 
     pub fn pop(&mut self) -> Option<T> {
-        self.head.take().map(|node| {
-            self.head = node.next;
-            node.elem
-        })
+        let mut node = self.head.take()?;
+        self.head = node.next.take();
+        self.len -= 1;
+        Some(self.pool.release(node).elem)
+    }
+
+    // Puts the elements in front as one block, in the order they come:
+    // push_many([1, 2]) on [3] gives [1, 2, 3] (push in a loop would give [2, 1, 3]).
+    // The nodes are linked into a chain of their own first, and the chain is spliced
+    // in with one write of head - if iter panics, the list stays as it was.
+    pub fn push_many<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let mut chain = List::from_head(None, 0, self.alloc.clone());
+        let mut tail = &mut chain.head;
+        let mut added = 0;
+
+        for elem in iter {
+            tail = &mut tail.insert(self.pool.acquire(Node::new(elem, None))).next;
+            added += 1;
+        }
+
+        *tail = self.head.take();
+        self.head = chain.head.take();
+        self.len += added;
+    }
+
+    // Takes the first n elements (all of them if there are fewer) as a new list,
+    // O(n) to find where to cut, then one relink, nodes are not touched
+    pub fn pop_n(&mut self, n: usize) -> List<T, A> {
+        let n = n.min(self.len);
+        let alloc = self.alloc.clone();
+
+        let rest = self.link_at(n).unwrap().take();
+        let head = core::mem::replace(&mut self.head, rest);
+        self.len -= n;
+
+        List::from_head(head, n, alloc)
+    }
+
+    // Take the list apart from one end, the way a slice pattern would: [first, rest @ ..].
+    // None for an empty list. The rest keeps the nodes, the allocator and the pool,
+    // split_first is O(1), split_last is O(n) like pop_back.
+    pub fn split_first(mut self) -> Option<(T, List<T, A>)> {
+        let first = self.pop()?;
+        Some((first, self))
+    }
+
+    pub fn split_last(mut self) -> Option<(T, List<T, A>)> {
+        let last = self.pop_back()?;
+        Some((last, self))
     }
 
     pub fn push_back(&mut self, elem: T) {
-        let link = Some(Box::new(Node {
-            elem: elem,
-            next: None,
-        }));
+        let link = Some(self.pool.acquire(Node::new(elem, None)));
+
+        *self.tail_link() = link;
+        self.len += 1;
+    }
+
+    // No back pointer, so all the back operations walk the list, O(n).
+    // Thanks to len we know which link holds the last node, so it's one pass.
+    pub fn pop_back(&mut self) -> Option<T> {
+        let last = self.len.checked_sub(1)?;
+        self.remove(last)
+    }
+
+    pub fn peek_back(&self) -> Option<&T> {
+        self.iter().last()
+    }
+
+    pub fn peek_back_mut(&mut self) -> Option<&mut T> {
+        self.iter_mut().last()
+    }
+
+    // Same as the peeks, named after the ends, like VecDeque's. back is O(n) as well.
+    pub fn front(&self) -> Option<&T> {
+        self.peek()
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.peek_mut()
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.peek_back()
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.peek_back_mut()
+    }
+
+    // Bytes allocated by the list: a node per element, and the nodes kept by the pool
+    // for reuse. What the elements allocate themselves is not counted, see heap_size.rs.
+    pub fn heap_size_in_bytes(&self) -> usize {
+        (self.len + self.pool.available()) * core::mem::size_of::<Node<T, A>>()
+    }
 
+    // Returns the last link of the list (the None one), O(n)
+    fn tail_link(&mut self) -> &mut Link<T, A> {
         let mut tail = &mut self.head;
 
         loop {
@@ -72,298 +422,3294 @@ impl<T> List<T> {
             }
         }
 
-        *tail = link;
+        tail
     }
-}
 
-// Version 1, create a dedicated struct for IntoIterator
-// pub struct IntoIter<T>(List<T>);
+    // Returns the link holding element at index, for index == len it's the last (None) link,
+    // for anything further there is no such link
+    fn link_at(&mut self, index: usize) -> Option<&mut Link<T, A>> {
+        let mut link = &mut self.head;
 
-// impl<T> Iterator for IntoIter<T> {
-//     type Item = T;
+        for _ in 0..index {
+            // ? bails out, when we run out of nodes
+            link = &mut link.as_mut()?.next;
+        }
 
-//     fn next(&mut self) -> Option<Self::Item> {
-//         self.0.pop()
-//     }
-// }
+        Some(link)
+    }
 
-// impl<T> IntoIterator for List<T> {
-//     type Item = T;
+    // Inserts elem at position index, shifting everything after it.
+    // index == len appends at the end, anything further is out of bounds and
+    // elem is given back in Err.
+    pub fn insert(&mut self, index: usize, elem: T) -> Result<(), T> {
+        if index > self.len {
+            return Err(elem);
+        }
 
-//     type IntoIter = IntoIter<T>;
+        let mut node = self.pool.acquire(Node::new(elem, None));
+        let link = self.link_at(index).unwrap();
+        node.next = link.take();
+        *link = Some(node);
 
-//     fn into_iter(self) -> Self::IntoIter {
-//         IntoIter(self)
-//     }
-// }
+        self.len += 1;
+        Ok(())
+    }
 
-// Version 2 Implement Iterator directly for list
-// Single responsibility principle up your butt!
-impl<T> Iterator for List<T> {
-    type Item = T;
+    // Removes and returns element at position index, None if out of bounds
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        let link = self.link_at(index)?;
+        let mut node = link.take()?;
+        *link = node.next.take();
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.pop()
+        self.len -= 1;
+        Some(self.pool.release(node).elem)
     }
-}
 
-// There is a blank trait implementation in std::core:
-// impl<I> IntoIterator for I
-// where I: Iterator;
-// Anything what implements Iterator, implements IntoIterator
+    // Keeps first at elements, and returns the rest as a new list.
+    // Same as Vec::split_off it panics if at > len.
+    pub fn split_off(&mut self, at: usize) -> List<T, A> {
+        assert!(at <= self.len, "split_off index out of bounds");
 
-// pub struct Iter<'a, T> {
-//     next: Option<&'a Node<T>>,
-// }
+        let alloc = self.alloc.clone();
+        let head = self.link_at(at).unwrap().take();
+        let len = self.len - at;
+        self.len = at;
 
-// impl<T> List<T> {
-//     // Compiler deduces lifetime for Iter is the same as for self,
-//     // Makes sense -> Iter needs to lives as long as &self (List)
-//     pub fn iter(&self) -> Iter<T> {
-//         Iter {
+        List::from_head(head, len, alloc)
+    }
 
-//             // node: &Box<Node<T>>
-//             // *node: Box<Node<T>>
-//             // **node: Node<T>
-//             // &**node: &Node<T>
-//             // next: self.head.as_ref().map(|node| &**node),
+    // Cuts the list in half: keeps len / 2 elements, returns the rest, starting with
+    // middle(). Same as split_off(len / 2) - with len known, walking to the cut is half
+    // a pass, fast and slow cursors would make one and a half.
+    pub fn split_middle(&mut self) -> List<T, A> {
+        self.split_off(self.len / 2)
+    }
 
-//             // node.as_ref() calls method from Box, which is clever enough to figure we mean
-//             // &Node<T>, not &Box<Node<T>>
+    // Like split_off, but splits at the first element matching pred, in one pass.
+    // That element starts the returned list. No match - nothing is split off,
+    // the returned list is empty.
+    pub fn split_when<P>(&mut self, mut pred: P) -> List<T, A>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        let alloc = self.alloc.clone();
+        let mut link = &mut self.head;
+        let mut at = 0;
 
-//             next: self.head.as_ref().map(|node| {let t= node.as_ref(); t}),
-//         }
-//     }
-// }
+        loop {
+            let found = match link.as_ref() {
+                None => break,
+                Some(node) => pred(&node.elem),
+            };
 
-// impl<'a, T> Iterator for Iter<'a, T> {
-//     type Item = &'a T;
+            if found {
+                break;
+            }
 
-//     fn next(&mut self) -> Option<Self::Item> {
-//         self.next.map(|node| {
-//             self.next = node.next.as_ref().map(|node| node.as_ref());
-//             &node.elem
-//         })
-//     }
-// }
+            link = &mut link.as_mut().unwrap().next;
+            at += 1;
+        }
 
-pub struct Iter<'a, T> {
-    next: Option<&'a Node<T>>,
-}
+        let head = link.take();
+        let len = self.len - at;
+        self.len = at;
 
-impl<T> List<T> {
-    pub fn iter<'a>(&'a self) -> Iter<'a, T> {
-        // As deref for Option extracts value from some:
-        // Some(a) -> a.deref()
-        // where 'a' is Box<Node<T>>
-        // calling deref on a box results with reference to underlying value -> &Node<T>
-        Iter {
-            next: self.head.as_deref(),
-        }
+        List::from_head(head, len, alloc)
     }
-}
 
-impl<'a, T> Iterator for Iter<'a, T> {
-    type Item = &'a T;
+    // slice::chunk_by for lists: splits the list into runs of neighbours for which
+    // same_group(a, b) holds, [1, 1, 2, 3, 3] by == gives [[1, 1], [2], [3, 3]].
+    // Nodes are only relinked, elements are not moved or cloned. O(n).
+    pub fn group_by<F>(mut self, mut same_group: F) -> List<List<T, A>, A>
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        let mut groups = List::new_in(self.alloc.clone());
+
+        groups.extend(core::iter::from_fn(|| {
+            let mut node = self.head.as_deref()?;
+            let mut run = 1;
+
+            while let Some(next) = node.next.as_deref() {
+                if !same_group(&node.elem, &next.elem) {
+                    break;
+                }
+                node = next;
+                run += 1;
+            }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        // map takes self by value, but since Item is an immutable reference
-        // there is no move but COPY (you can have as many imm references as you like)
-        // 
-        // self.next.map(|node| { &node.elem });
-        //
-        // It's possible to use self.next, after map:
-        // println!("self next after {:?}", self.next);
-        self.next.map(|node| {
-            self.next = node.next.as_deref();
-            &node.elem
-        })
-    }
-}
+            let rest = self.split_off(run);
+            Some(core::mem::replace(&mut self, rest))
+        }));
 
-pub struct IterMut<'a, T> {
-    next: Option<&'a mut Node<T>>,
-}
+        groups
+    }
 
-impl<T> List<T> {
-    pub fn iter_mut<'a>(&'a mut self) -> IterMut<'a, T> {
-        // As deref for Option extracts value from some:
-        // Some(a) -> a.deref()
-        // where 'a' is Box<Node<T>>
-        // calling deref on a box results with reference to underlying value -> &Node<T>
-        IterMut {
-            next: self.head.as_deref_mut(),
+    // Consuming map which can fail: stops at the first Err and returns it, the elements
+    // not mapped yet are dropped with their nodes, and so is what was mapped so far
+    pub fn try_map<U, E, F>(self, mut f: F) -> Result<List<U, A>, E>
+    where
+        F: FnMut(T) -> Result<U, E>,
+    {
+        let mut mapped = List::new_in(self.alloc.clone());
+        let mut tail = &mut mapped.head;
+
+        for elem in self {
+            let node = mapped.pool.acquire(Node::new(f(elem)?, None));
+            tail = &mut tail.insert(node).next;
+            mapped.len += 1;
         }
-    }
-}
 
-impl<'a, T> Iterator for IterMut<'a, T> {
-    type Item = &'a mut T;
+        Ok(mapped)
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        // self.next.take(), sets next to None, and we get control of value from next
-        self.next.take().map(|node| {
-            // We map Some(a) to a
+    // map for T -> T which keeps the nodes, every element is moved out of its node,
+    // through f, and right back in. Nothing is allocated or freed.
+    //
+    // If f panics, the element it took is gone - its node is freed, everything mapped
+    // before stays in the list, and everything after is dropped.
+    pub fn map_in_place<F>(&mut self, mut f: F)
+    where
+        F: FnMut(T) -> T,
+    {
+        // Not mapped yet. A list, not a bare link, so it's dropped without recursion
+        // if f panics.
+        let mut rest = List::from_head(self.head.take(), self.len, self.alloc.clone());
+        self.len = 0;
+        let mut tail = &mut self.head;
 
-            // We set next to point to another element
-            self.next = node.next.as_deref_mut();
+        while let Some(mut node) = rest.head.take() {
+            rest.head = node.next.take();
+            rest.len -= 1;
 
-            // And we return current element
-            &mut node.elem
-        })
+            node.elem = f(node.elem);
+            tail = &mut tail.insert(node).next;
+            self.len += 1;
+        }
     }
-}
-
-impl<T> Drop for List<T> {
-    fn drop(&mut self) {
-        // Take ownership over the head, sets head to None
-        let mut elem = self.head.take();
 
-        while let Some(boxed) = elem {
-            // Old value got drop here
-            elem = boxed.next;
+    // Like Iterator::partition, elements for which pred holds go to the first list, the rest
+    // to the second, both keep the original order. Nodes are relinked, one pass, no clones.
+    pub fn partition<P>(mut self, mut pred: P) -> (List<T, A>, List<T, A>)
+    where
+        P: FnMut(&T) -> bool,
+    {
+        let mut yes = List::new_in(self.alloc.clone());
+        let mut no = List::new_in(self.alloc.clone());
+        let (mut yes_len, mut no_len) = (0, 0);
+        let mut yes_tail = &mut yes.head;
+        let mut no_tail = &mut no.head;
+
+        while let Some(mut node) = self.head.take() {
+            self.head = node.next.take();
+
+            if pred(&node.elem) {
+                yes_tail = &mut yes_tail.insert(node).next;
+                yes_len += 1;
+            } else {
+                no_tail = &mut no_tail.insert(node).next;
+                no_len += 1;
+            }
         }
-    }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        yes.len = yes_len;
+        no.len = no_len;
+        (yes, no)
+    }
 
-    #[test]
-    fn push_back_works() {
-        let mut list = List::new();
+    // Takes both lists, and makes a new one of f(a, b) for elements at the same positions.
+    // Stops at the end of the shorter one, what's left of the longer one is dropped.
+    pub fn zip_with<U, V, F>(self, other: List<U, A>, mut f: F) -> List<V, A>
+    where
+        F: FnMut(T, U) -> V,
+    {
+        let mut zipped = List::new_in(self.alloc.clone());
+        zipped.extend(self.into_iter().zip(other).map(|(a, b)| f(a, b)));
+        zipped
+    }
 
-        assert_eq!(list, List { head: None });
+    // Unlinks the first node equal to x, returns whether there was one
+    pub fn remove_first(&mut self, x: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        let mut link = &mut self.head;
 
-        list.push_back(69);
+        // Same dance as in retain_mut, look first, then move on or unlink
+        loop {
+            let found = match link.as_ref() {
+                None => return false,
+                Some(node) => node.elem == *x,
+            };
 
-        assert_eq!(
-            list,
-            List {
-                head: Some(Box::new(Node {
-                    elem: 69,
-                    next: None
-                }))
+            if found {
+                break;
             }
-        );
 
-        list.push_back(13);
+            link = &mut link.as_mut().unwrap().next;
+        }
 
-        assert_eq!(
-            list,
-            List {
-                head: Some(Box::new(Node {
-                    elem: 69,
-                    next: Some(Box::new(Node {
-                        elem: 13,
-                        next: None
-                    }))
-                }))
-            }
-        );
+        let node = link.take().unwrap();
+        *link = node.next;
+        self.len -= 1;
+
+        true
     }
 
-    #[test]
+    // Unlinks every node equal to x in one pass, returns how many were removed
+    pub fn remove_all(&mut self, x: &T) -> usize
+    where
+        T: PartialEq,
+    {
+        let before = self.len;
+        self.retain(|elem| elem != x);
+
+        before - self.len
+    }
+
+    // Keeps only elements for which f returns true, in one pass
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.retain_mut(|elem| f(elem));
+    }
+
+    // Same as retain, but f can also modify elements which stay
+    pub fn retain_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        let mut link = &mut self.head;
+
+        loop {
+            // Borrow of the node has to end before we decide what to do with the link,
+            // matching and moving in the same match arm does not compile
+            let keep = match link.as_mut() {
+                None => break,
+                Some(node) => f(&mut node.elem),
+            };
+
+            if keep {
+                link = &mut link.as_mut().unwrap().next;
+            } else {
+                // Unlink the node, link now holds the next one, so don't move
+                let node = link.take().unwrap();
+                *link = node.next;
+                self.len -= 1;
+            }
+        }
+    }
+
+    // Removes consecutive equal elements, the first one of each run stays.
+    // Same as Vec::dedup, for all duplicates to go, sort the list first.
+    pub fn dedup(&mut self)
+    where
+        T: PartialEq,
+    {
+        self.dedup_by(|a, b| a == b);
+    }
+
+    pub fn dedup_by_key<K, F>(&mut self, mut key: F)
+    where
+        F: FnMut(&mut T) -> K,
+        K: PartialEq,
+    {
+        self.dedup_by(|a, b| key(a) == key(b));
+    }
+
+    // same_bucket gets the next element first, and the one which stays second (same
+    // order as in Vec::dedup_by), next is unlinked when it returns true
+    pub fn dedup_by<F>(&mut self, mut same_bucket: F)
+    where
+        F: FnMut(&mut T, &mut T) -> bool,
+    {
+        let mut cur = self.head.as_mut();
+
+        while let Some(node) = cur {
+            let duplicate = match node.next.as_mut() {
+                Some(next) => same_bucket(&mut next.elem, &mut node.elem),
+                None => break,
+            };
+
+            if duplicate {
+                // Stay on the same node, there may be more duplicates after it
+                let removed = node.next.take().unwrap();
+                node.next = removed.next;
+                self.len -= 1;
+                cur = Some(node);
+            } else {
+                cur = node.next.as_mut();
+            }
+        }
+    }
+
+    // Keeps the first len elements, drops the rest. Nothing happens if the list
+    // is not longer than len, same as Vec::truncate.
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+
+        let rest = self.link_at(len).unwrap().take();
+        let rest_len = self.len - len;
+        self.len = len;
+
+        // Dropping the Box chain would recurse, a list drops it in a loop
+        drop(List::from_head(rest, rest_len, self.alloc.clone()));
+    }
+
+    // Grows the list to new_len with elements made by f, or truncates it. One walk
+    // to the end, new nodes are appended from there.
+    pub fn resize_with<F>(&mut self, new_len: usize, mut f: F)
+    where
+        F: FnMut() -> T,
+    {
+        if new_len <= self.len {
+            self.truncate(new_len);
+            return;
+        }
+
+        let mut link = &mut self.head;
+        while let Some(node) = link {
+            link = &mut node.next;
+        }
+
+        while self.len < new_len {
+            let node = self.pool.acquire(Node::new(f(), None));
+            link = &mut link.insert(node).next;
+            // Updated as we go, so len is right even if f panics
+            self.len += 1;
+        }
+    }
+
+    // Swaps the elements at i and j, the nodes stay where they are. One walk, to the
+    // further index. Panics if either is out of bounds, same as slice::swap.
+    pub fn swap(&mut self, i: usize, j: usize) {
+        let (lo, hi) = (i.min(j), i.max(j));
+        assert!(
+            hi < self.len,
+            "swap index {} out of bounds, len is {}",
+            hi,
+            self.len
+        );
+
+        if lo == hi {
+            return;
+        }
+
+        // Borrow the element and the rest of the list separately, so we can hold on
+        // to the first while walking to the second
+        let node = self.link_at(lo).unwrap().as_mut().unwrap();
+        let Node { elem, next, .. } = &mut **node;
+
+        let mut other = next.as_mut().unwrap();
+        for _ in lo + 1..hi {
+            other = other.next.as_mut().unwrap();
+        }
+
+        core::mem::swap(elem, &mut other.elem);
+    }
+
+    // Pops nodes from the front one by one and pushes them on the front of another
+    // chain, no allocations - Boxes are just moved around
+    pub fn reverse(&mut self) {
+        let mut reversed: Link<T, A> = None;
+        let mut current = self.head.take();
+
+        while let Some(mut node) = current {
+            current = node.next.take();
+            node.next = reversed;
+            reversed = Some(node);
+        }
+
+        self.head = reversed;
+    }
+
+    // First n elements go to the back, in the same order. Nodes are relinked, O(len),
+    // nothing is allocated. Like Vec::rotate_left it panics if n > len.
+    // rotate_left(1) in a loop is a round-robin over the elements.
+    pub fn rotate_left(&mut self, n: usize) {
+        assert!(n <= self.len, "rotation out of bounds");
+
+        if n == 0 || n == self.len {
+            return;
+        }
+
+        // head keeps first n nodes, they are hooked up after the last node of the rest
+        let mut rest = split_link(&mut self.head, n);
+        let mut tail = &mut rest;
+
+        loop {
+            match tail {
+                None => break,
+                Some(node) => tail = &mut node.next,
+            }
+        }
+
+        *tail = self.head.take();
+        self.head = rest;
+    }
+
+    // Last n elements go to the front
+    pub fn rotate_right(&mut self, n: usize) {
+        assert!(n <= self.len, "rotation out of bounds");
+
+        self.rotate_left(self.len - n);
+    }
+}
+
+// Version 1, create a dedicated struct for IntoIterator
+// pub struct IntoIter<T>(List<T>);
+
+// impl<T> Iterator for IntoIter<T> {
+//     type Item = T;
+
+//     fn next(&mut self) -> Option<Self::Item> {
+//         self.0.pop()
+//     }
+// }
+
+// impl<T> IntoIterator for List<T> {
+//     type Item = T;
+
+//     type IntoIter = IntoIter<T>;
+
+//     fn into_iter(self) -> Self::IntoIter {
+//         IntoIter(self)
+//     }
+// }
+
+// Version 2 Implement Iterator directly for list
+// Single responsibility principle up your butt!
+// impl<T> Iterator for List<T> {
+//     type Item = T;
+
+//     fn next(&mut self) -> Option<Self::Item> {
+//         self.pop()
+//     }
+// }
+
+// Version 3, back to the dedicated struct - iterating from the back needs
+// some additional state, which does not belong to the list.
+// List has no back links, so the first call to next_back pops all remaining elements
+// into a buffer, from now on both ends are served from it. It's O(n) once, instead of
+// walking to the end of the list on every next_back.
+pub struct IntoIter<T, A: Allocator = Global> {
+    list: List<T, A>,
+    back: VecDeque<T>,
+}
+
+impl<T, A: Allocator + Clone> IntoIterator for List<T, A> {
+    type Item = T;
+
+    type IntoIter = IntoIter<T, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            list: self,
+            back: VecDeque::new(),
+        }
+    }
+}
+
+impl<T, A: Allocator + Clone> Iterator for IntoIter<T, A> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Once the buffer is filled, list is empty
+        self.list.pop().or_else(|| self.back.pop_front())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.list.len() + self.back.len();
+        (len, Some(len))
+    }
+}
+
+impl<T, A: Allocator + Clone> DoubleEndedIterator for IntoIter<T, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while let Some(elem) = self.list.pop() {
+            self.back.push_back(elem);
+        }
+
+        self.back.pop_back()
+    }
+}
+
+// Exact, because size_hint is. Fused, because an empty list and buffer stay empty.
+impl<T, A: Allocator + Clone> ExactSizeIterator for IntoIter<T, A> {}
+
+impl<T, A: Allocator + Clone> FusedIterator for IntoIter<T, A> {}
+
+// There is a blank trait implementation in std::core:
+// impl<I> IntoIterator for I
+// where I: Iterator;
+// Anything what implements Iterator, implements IntoIterator
+
+// pub struct Iter<'a, T> {
+//     next: Option<&'a Node<T>>,
+// }
+
+// impl<T> List<T> {
+//     // Compiler deduces lifetime for Iter is the same as for self,
+//     // Makes sense -> Iter needs to lives as long as &self (List)
+//     pub fn iter(&self) -> Iter<T> {
+//         Iter {
+
+//             // node: &Box<Node<T>>
+//             // *node: Box<Node<T>>
+//             // **node: Node<T>
+//             // &**node: &Node<T>
+//             // next: self.head.as_ref().map(|node| &**node),
+
+//             // node.as_ref() calls method from Box, which is clever enough to figure we mean
+//             // &Node<T>, not &Box<Node<T>>
+
+//             next: self.head.as_ref().map(|node| {let t= node.as_ref(); t}),
+//         }
+//     }
+// }
+
+// impl<'a, T> Iterator for Iter<'a, T> {
+//     type Item = &'a T;
+
+//     fn next(&mut self) -> Option<Self::Item> {
+//         self.next.map(|node| {
+//             self.next = node.next.as_ref().map(|node| node.as_ref());
+//             &node.elem
+//         })
+//     }
+// }
+
+pub struct Iter<'a, T, A: Allocator = Global> {
+    next: Option<&'a Node<T, A>>,
+    // Nodes left from next on, the ones moved to back are not counted
+    len: usize,
+    // Filled on first next_back, same as for IntoIter
+    back: VecDeque<&'a T>,
+}
+
+impl<T, A: Allocator> List<T, A> {
+    pub fn iter<'a>(&'a self) -> Iter<'a, T, A> {
+        // As deref for Option extracts value from some:
+        // Some(a) -> a.deref()
+        // where 'a' is Box<Node<T>>
+        // calling deref on a box results with reference to underlying value -> &Node<T>
+        Iter {
+            next: self.head.as_deref(),
+            len: self.len,
+            back: VecDeque::new(),
+        }
+    }
+}
+
+impl<'a, T, A: Allocator> Iterator for Iter<'a, T, A> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // map takes self by value, but since Item is an immutable reference
+        // there is no move but COPY (you can have as many imm references as you like)
+        //
+        // self.next.map(|node| { &node.elem });
+        //
+        // It's possible to use self.next, after map:
+        // println!("self next after {:?}", self.next);
+        match self.next {
+            Some(node) => {
+                self.next = node.next.as_deref();
+                self.len -= 1;
+                Some(&node.elem)
+            }
+            None => self.back.pop_front(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len + self.back.len();
+        (len, Some(len))
+    }
+}
+
+impl<'a, T, A: Allocator> DoubleEndedIterator for Iter<'a, T, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.next {
+            self.back.push_back(&node.elem);
+            self.next = node.next.as_deref();
+            self.len -= 1;
+        }
+
+        self.back.pop_back()
+    }
+}
+
+impl<'a, T, A: Allocator> ExactSizeIterator for Iter<'a, T, A> {}
+
+impl<'a, T, A: Allocator> FusedIterator for Iter<'a, T, A> {}
+
+pub struct IterMut<'a, T, A: Allocator = Global> {
+    next: Option<&'a mut Node<T, A>>,
+    // Same as in Iter
+    len: usize,
+    back: VecDeque<&'a mut T>,
+}
+
+impl<T, A: Allocator> List<T, A> {
+    pub fn iter_mut<'a>(&'a mut self) -> IterMut<'a, T, A> {
+        // As deref for Option extracts value from some:
+        // Some(a) -> a.deref()
+        // where 'a' is Box<Node<T>>
+        // calling deref on a box results with reference to underlying value -> &Node<T>
+        IterMut {
+            next: self.head.as_deref_mut(),
+            len: self.len,
+            back: VecDeque::new(),
+        }
+    }
+}
+
+impl<'a, T, A: Allocator> Iterator for IterMut<'a, T, A> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // self.next.take(), sets next to None, and we get control of value from next
+        match self.next.take() {
+            Some(node) => {
+                // We set next to point to another element
+                self.next = node.next.as_deref_mut();
+                self.len -= 1;
+
+                // And we return current element
+                Some(&mut node.elem)
+            }
+            None => self.back.pop_front(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len + self.back.len();
+        (len, Some(len))
+    }
+}
+
+impl<'a, T, A: Allocator> DoubleEndedIterator for IterMut<'a, T, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.next.take() {
+            // Borrows of next and elem are disjoint, so both can be taken out of node
+            self.next = node.next.as_deref_mut();
+            self.back.push_back(&mut node.elem);
+            self.len -= 1;
+        }
+
+        self.back.pop_back()
+    }
+}
+
+impl<'a, T, A: Allocator> ExactSizeIterator for IterMut<'a, T, A> {}
+
+impl<'a, T, A: Allocator> FusedIterator for IterMut<'a, T, A> {}
+
+impl<'a, T, A: Allocator> IterMut<'a, T, A> {
+    // Element next() is going to return, without moving. It's a different node than
+    // any element handed out so far, so it can be read while the current one is mutated:
+    //
+    // while let Some(elem) = iter.next() {
+    //     if let Some(next) = iter.peek_next() { *elem = (*elem + *next) / 2 }
+    // }
+    pub fn peek_next(&self) -> Option<&T> {
+        match &self.next {
+            Some(node) => Some(&node.elem),
+            None => self.back.front().map(|elem| &**elem),
+        }
+    }
+}
+
+impl<T, A: Allocator> List<T, A> {
+    // Overlapping windows of size elements, each of them mutable, like slice::windows,
+    // but with &mut. Panics if size is 0.
+    pub fn windows_mut(&mut self, size: usize) -> WindowsMut<'_, T, A> {
+        assert!(size != 0, "window size must be non-zero");
+
+        WindowsMut {
+            iter: self.iter_mut(),
+            window: VecDeque::with_capacity(size),
+            size,
+        }
+    }
+}
+
+// Windows overlap, so there can't be two of them at once - next window can be made only
+// once the previous one is gone. That's a lending iterator, which Iterator can't express
+// (its items can't borrow from the iterator), so there is just next, use it with while let:
+//
+// let mut windows = list.windows_mut(2);
+// while let Some(mut window) = windows.next() {
+//     window[0] += window[1];
+// }
+//
+// Underneath it's IterMut, and the window keeps &mut to size consecutive elements, moving
+// by one node per step. Each &mut points at a different node, so they never alias.
+pub struct WindowsMut<'a, T, A: Allocator = Global> {
+    iter: IterMut<'a, T, A>,
+    window: VecDeque<&'a mut T>,
+    size: usize,
+}
+
+impl<'a, T, A: Allocator> WindowsMut<'a, T, A> {
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<WindowMut<'_, 'a, T>> {
+        if self.window.len() == self.size {
+            self.window.pop_front();
+        }
+
+        while self.window.len() < self.size {
+            self.window.push_back(self.iter.next()?);
+        }
+
+        Some(WindowMut {
+            elems: self.window.make_contiguous(),
+        })
+    }
+}
+
+// Only indexing, the &mut references inside are not handed out,
+// so they can't be swapped around or kept
+pub struct WindowMut<'w, 'a, T> {
+    elems: &'w mut [&'a mut T],
+}
+
+impl<'w, 'a, T> WindowMut<'w, 'a, T> {
+    pub fn len(&self) -> usize {
+        self.elems.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elems.is_empty()
+    }
+}
+
+impl<'w, 'a, T> Index<usize> for WindowMut<'w, 'a, T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        self.elems[index]
+    }
+}
+
+impl<'w, 'a, T> IndexMut<usize> for WindowMut<'w, 'a, T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        self.elems[index]
+    }
+}
+
+// for elem in &list / for elem in &mut list, same as with Vec.
+// Version 2 above (Iterator on the List itself) could not do that, it only consumed the list.
+impl<'a, T, A: Allocator> IntoIterator for &'a List<T, A> {
+    type Item = &'a T;
+
+    type IntoIter = Iter<'a, T, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, A: Allocator> IntoIterator for &'a mut List<T, A> {
+    type Item = &'a mut T;
+
+    type IntoIter = IterMut<'a, T, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+// Derived Clone would clone the head, which clones next node, which clones next...
+// recursion again, same story as with Drop. So build the copy in a loop, keeping
+// a reference to the last link of the new list (where next node goes).
+impl<T: Clone, A: Allocator + Clone> Clone for List<T, A> {
+    fn clone(&self) -> Self {
+        let mut list = List::new_in(self.alloc.clone());
+        let mut tail = &mut list.head;
+
+        for elem in self.iter() {
+            // insert puts the node into the None link, and returns reference to it
+            tail = &mut tail
+                .insert(Node::boxed(elem.clone(), None, self.alloc.clone()))
+                .next;
+        }
+
+        list.len = self.len;
+        list.max_len = self.max_len;
+        list
+    }
+}
+
+// Like vec!, elements end up in the written order:
+// list![1, 2, 3] has 1 at the top, list![0; 5] is five zeros
+#[macro_export]
+macro_rules! list {
+    () => {
+        $crate::second::List::new()
+    };
+    ($elem:expr; $n:expr) => {
+        <$crate::second::List<_> as ::core::iter::FromIterator<_>>::from_iter(
+            ::core::iter::repeat($elem).take($n),
+        )
+    };
+    ($($elem:expr),+ $(,)?) => {
+        <$crate::second::List<_> as ::core::iter::FromIterator<_>>::from_iter([$($elem),+])
+    };
+}
+
+impl<T, A: Allocator + Clone + Default> FromIterator<T> for List<T, A> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = List::new_in(A::default());
+        list.extend(iter);
+        list
+    }
+}
+
+// Elements are taken from the back and pushed on the front, so the list is built
+// back to front, in one pass, no walking to the tail.
+// Only for Global, with a generic allocator List::from(vec) would need type annotations.
+impl<T> From<Vec<T>> for List<T> {
+    fn from(vec: Vec<T>) -> Self {
+        let mut list = List::new();
+        vec.into_iter().rev().for_each(|elem| list.push(elem));
+        list
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for List<T> {
+    fn from(array: [T; N]) -> Self {
+        let mut list = List::new();
+        IntoIterator::into_iter(array)
+            .rev()
+            .for_each(|elem| list.push(elem));
+        list
+    }
+}
+
+// The other way around, elements are moved out, nothing is cloned. Length is known,
+// so the memory is reserved once up front.
+impl<T, A: Allocator + Clone> List<T, A> {
+    pub fn into_vec(self) -> Vec<T> {
+        let mut vec = Vec::with_capacity(self.len);
+        vec.extend(self);
+        vec
+    }
+}
+
+// Iterator::unzip would need A: Default, and extending two lists one element at
+// a time walks to their ends every time. Here both are built in one pass, keeping
+// a link to the end of each. Nodes of pairs don't fit either half, so they are freed.
+impl<X, Y, A: Allocator + Clone> List<(X, Y), A> {
+    pub fn unzip(self) -> (List<X, A>, List<Y, A>) {
+        let len = self.len;
+        let mut xs = List::new_in(self.alloc.clone());
+        let mut ys = List::new_in(self.alloc.clone());
+        let mut xs_tail = &mut xs.head;
+        let mut ys_tail = &mut ys.head;
+
+        for (x, y) in self {
+            xs_tail = &mut xs_tail.insert(xs.pool.acquire(Node::new(x, None))).next;
+            ys_tail = &mut ys_tail.insert(ys.pool.acquire(Node::new(y, None))).next;
+        }
+
+        xs.len = len;
+        ys.len = len;
+        (xs, ys)
+    }
+}
+
+// Run-length encoding: [a, a, a, b, a] <-> [(a, 3), (b, 1), (a, 1)]. Only neighbours
+// make a run. Both sides are borrowed, every run clones its element once to encode,
+// and count times to decode.
+impl<T: PartialEq + Clone, A: Allocator + Clone> List<T, A> {
+    pub fn rle_encode(&self) -> List<(T, usize), A> {
+        let mut runs = List::new_in(self.alloc.clone());
+        let mut elems = self.iter().peekable();
+
+        runs.extend(core::iter::from_fn(|| {
+            let first = elems.next()?;
+            let mut count = 1;
+            while elems.next_if_eq(&first).is_some() {
+                count += 1;
+            }
+
+            Some((first.clone(), count))
+        }));
+
+        runs
+    }
+}
+
+// Runs of 0 decode to nothing
+impl<T: Clone, A: Allocator + Clone> List<(T, usize), A> {
+    pub fn rle_decode(&self) -> List<T, A> {
+        let mut list = List::new_in(self.alloc.clone());
+        list.extend(
+            self.iter()
+                .flat_map(|(elem, count)| core::iter::repeat_n(elem, *count))
+                .cloned(),
+        );
+        list
+    }
+}
+
+impl<T, A: Allocator + Clone> From<List<T, A>> for VecDeque<T> {
+    fn from(list: List<T, A>) -> Self {
+        list.into_vec().into()
+    }
+}
+
+impl<T, A: Allocator + Clone> From<List<T, A>> for LinkedList<T> {
+    fn from(list: List<T, A>) -> Self {
+        list.into_iter().collect()
+    }
+}
+
+impl<T, A: Allocator + Clone> Extend<T> for List<T, A> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let alloc = self.alloc.clone();
+
+        // Walk to the end once, then keep appending at the last link,
+        // calling push_back in a loop would be O(n^2)
+        let mut tail = self.tail_link();
+        let mut added = 0;
+
+        for elem in iter {
+            tail = &mut tail.insert(Node::boxed(elem, None, alloc.clone())).next;
+            added += 1;
+        }
+
+        self.len += added;
+    }
+}
+
+// Extend and collect from references to Copy elements, like Vec's Extend<&T>:
+// list.extend(&[1, 2, 3]) instead of list.extend([1, 2, 3].iter().copied())
+impl<'a, T: Copy + 'a, A: Allocator + Clone> Extend<&'a T> for List<T, A> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<'a, T: Copy + 'a, A: Allocator + Clone + Default> FromIterator<&'a T> for List<T, A> {
+    fn from_iter<I: IntoIterator<Item = &'a T>>(iter: I) -> Self {
+        iter.into_iter().copied().collect()
+    }
+}
+
+impl<T, A: Allocator + Clone> Stack<T> for List<T, A> {
+    fn push(&mut self, elem: T) {
+        self.push(elem);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.pop()
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.peek()
+    }
+}
+
+// Difference list. Gluing lists together front to back walks to the tail of the result
+// every time, so building a list out of n pieces is O(n^2). The builder only records
+// the pieces, O(1) each, and links them all once, in build - every piece is walked
+// once, to find its last link.
+pub struct ListBuilder<T, A: Allocator = Global> {
+    pieces: VecDeque<List<T, A>>,
+    len: usize,
+    alloc: A,
+}
+
+impl<T> ListBuilder<T> {
+    pub const fn new() -> Self {
+        ListBuilder::new_in(Global)
+    }
+}
+
+impl<T> Default for ListBuilder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, A: Allocator + Clone> ListBuilder<T, A> {
+    // alloc is for the nodes made by push_back
+    pub const fn new_in(alloc: A) -> Self {
+        ListBuilder {
+            pieces: VecDeque::new(),
+            len: 0,
+            alloc,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn append(&mut self, list: List<T, A>) {
+        if !list.is_empty() {
+            self.len += list.len;
+            self.pieces.push_back(list);
+        }
+    }
+
+    pub fn prepend(&mut self, list: List<T, A>) {
+        if !list.is_empty() {
+            self.len += list.len;
+            self.pieces.push_front(list);
+        }
+    }
+
+    pub fn push_back(&mut self, elem: T) {
+        let mut list = List::new_in(self.alloc.clone());
+        list.push(elem);
+        self.append(list);
+    }
+
+    // Front of the first piece is the front of the result, so it goes straight there
+    pub fn push_front(&mut self, elem: T) {
+        match self.pieces.front_mut() {
+            Some(first) => {
+                first.push(elem);
+                self.len += 1;
+            }
+            None => self.push_back(elem),
+        }
+    }
+
+    // Links the pieces, from the last one, so every piece is hooked up to
+    // what's already built after it
+    pub fn build(self) -> List<T, A> {
+        let mut list = List::new_in(self.alloc);
+
+        for mut piece in self.pieces.into_iter().rev() {
+            *piece.tail_link() = list.head.take();
+            piece.len += list.len;
+            list = piece;
+        }
+
+        debug_assert_eq!(list.len, self.len);
+        list
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize, A: Allocator> serde::Serialize for List<T, A> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::serde_seq::serialize_seq(serializer, self.len, self.iter())
+    }
+}
+
+// collect goes through Extend, which keeps a cursor at the tail
+#[cfg(feature = "serde")]
+impl<'de, T, A> serde::Deserialize<'de> for List<T, A>
+where
+    T: serde::Deserialize<'de>,
+    A: Allocator + Clone + Default,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_seq::deserialize_seq(deserializer, "a list", |elems| elems.collect())
+    }
+}
+
+// Rayon support, turned on with the "rayon" feature: list.par_iter(), par_iter_mut(),
+// into_par_iter() and collect() come from rayon::prelude, as for Vec.
+//
+// Rayon splits the work in halves, and halves of halves, and a list can't be cut in the
+// middle without walking to it. So it's done once, up front: one pass collects
+// references to the elements into a Vec, and rayon splits that. The pass is sequential,
+// O(n), worth it when the work per element is much bigger than following a pointer.
+#[cfg(feature = "rayon")]
+impl<'a, T: Sync + 'a, A: Allocator> rayon::iter::IntoParallelIterator for &'a List<T, A> {
+    type Item = &'a T;
+    type Iter = rayon::vec::IntoIter<&'a T>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        self.iter().collect::<Vec<_>>().into_par_iter()
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<'a, T: Send + 'a, A: Allocator> rayon::iter::IntoParallelIterator for &'a mut List<T, A> {
+    type Item = &'a mut T;
+    type Iter = rayon::vec::IntoIter<&'a mut T>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        self.iter_mut().collect::<Vec<_>>().into_par_iter()
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T: Send, A: Allocator + Clone> rayon::iter::IntoParallelIterator for List<T, A> {
+    type Item = T;
+    type Iter = rayon::vec::IntoIter<T>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        self.into_vec().into_par_iter()
+    }
+}
+
+// The other way round: rayon collects into a Vec, in order, and the list is made of it
+#[cfg(feature = "rayon")]
+impl<T: Send, A: Allocator + Clone + Default> rayon::iter::FromParallelIterator<T> for List<T, A> {
+    fn from_par_iter<I>(par_iter: I) -> Self
+    where
+        I: rayon::iter::IntoParallelIterator<Item = T>,
+    {
+        let elems: Vec<T> = rayon::iter::FromParallelIterator::from_par_iter(par_iter);
+        elems.into_iter().collect()
+    }
+}
+
+// Bottom-up merge sort - no recursion, no additional memory, nodes are relinked.
+// Pass with width w, merges pairs of sorted runs of length w into runs of length 2w,
+// starting with w = 1 (every single node is sorted run), until run covers whole list.
+impl<T, A: Allocator> List<T, A> {
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(|a, b| a.cmp(b));
+    }
+
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut width = 1;
+
+        while width < self.len {
+            let mut rest = self.head.take();
+            // Merged runs are appended here
+            let mut tail = &mut self.head;
+
+            while rest.is_some() {
+                let mut left = rest;
+                let mut right = split_link(&mut left, width);
+                rest = split_link(&mut right, width);
+
+                tail = merge_links(tail, left, right, &mut rest, &mut compare);
+            }
+
+            width *= 2;
+        }
+    }
+
+    // Both lists have to be sorted, nodes of other are relinked into self, so the result
+    // is sorted as well. O(n + m), no allocation. Stable - for equal elements
+    // the ones from self go first.
+    // Every Box carries its allocator, so nodes of other can be freed by self later on.
+    pub fn merge(&mut self, other: List<T, A>)
+    where
+        T: Ord,
+    {
+        self.merge_by(other, |a, b| a.cmp(b));
+    }
+
+    pub fn merge_by<F>(&mut self, mut other: List<T, A>, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let left = self.head.take();
+        let right = other.head.take();
+        // Before merging, so it's right even if compare panics
+        self.len += other.len;
+        other.len = 0;
+
+        merge_links(&mut self.head, left, right, &mut None, &mut compare);
+    }
+}
+
+// Cuts the chain after n nodes, returns the second part
+fn split_link<T, A: Allocator>(mut link: &mut Link<T, A>, n: usize) -> Link<T, A> {
+    for _ in 0..n {
+        match link {
+            Some(node) => link = &mut node.next,
+            None => return None,
+        }
+    }
+
+    link.take()
+}
+
+// Merges two sorted chains, appending result to tail, returns new tail.
+// compare can panic, and the list still has to hold all its nodes then, len is trusted
+// (see ExactSizeIterator). So whatever is not merged yet, what's left of both chains
+// and rest (chains for later merges), is linked after tail by the guard.
+fn merge_links<'a, T, A, F>(
+    tail: &'a mut Link<T, A>,
+    left: Link<T, A>,
+    right: Link<T, A>,
+    rest: &mut Link<T, A>,
+    compare: &mut F,
+) -> &'a mut Link<T, A>
+where
+    A: Allocator,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut guard = MergeGuard {
+        tail: Some(tail),
+        left,
+        right,
+        rest,
+    };
+
+    loop {
+        let take_left = match (&guard.left, &guard.right) {
+            // Take right only if it is strictly less, that keeps the sort stable
+            (Some(l), Some(r)) => compare(&r.elem, &l.elem) != Ordering::Less,
+            (Some(_), None) => true,
+            (None, Some(_)) => false,
+            // Tail taken, guard has nothing to do
+            (None, None) => return guard.tail.take().unwrap(),
+        };
+
+        let source = if take_left {
+            &mut guard.left
+        } else {
+            &mut guard.right
+        };
+        let mut node = source.take().unwrap();
+        *source = node.next.take();
+
+        let tail = guard.tail.take().unwrap();
+        guard.tail = Some(&mut tail.insert(node).next);
+    }
+}
+
+struct MergeGuard<'a, 'r, T, A: Allocator> {
+    // Empty link at the end of what's merged so far
+    tail: Option<&'a mut Link<T, A>>,
+    left: Link<T, A>,
+    right: Link<T, A>,
+    rest: &'r mut Link<T, A>,
+}
+
+impl<'a, 'r, T, A: Allocator> Drop for MergeGuard<'a, 'r, T, A> {
+    fn drop(&mut self) {
+        if let Some(mut tail) = self.tail.take() {
+            for chain in [self.left.take(), self.right.take(), self.rest.take()] {
+                *tail = chain;
+                tail = end_link(tail);
+            }
+        }
+    }
+}
+
+// The empty link after the last node of the chain
+fn end_link<T, A: Allocator>(mut link: &mut Link<T, A>) -> &mut Link<T, A> {
+    while let Some(node) = link {
+        link = &mut node.next;
+    }
+
+    link
+}
+
+// Cursor for a singly linked list cannot point at the node itself - to unlink it
+// we need to modify the link which holds it (the head, or next of previous node).
+// So cursor keeps a mutable reference to that link instead.
+// When link is None, cursor points past the last element (so called "ghost" element).
+pub struct CursorMut<'a, T, A: Allocator = Global> {
+    // It's always Some, Option is only there to be able to move the reference
+    // out of self, and put it back (see move_next)
+    link: Option<&'a mut Link<T, A>>,
+    // Position of the current element, len of the list for the ghost
+    index: usize,
+    // Length of the list, cursor links and unlinks nodes
+    len: &'a mut usize,
+    // For new nodes
+    alloc: &'a A,
+}
+
+impl<T, A: Allocator + Clone> List<T, A> {
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T, A> {
+        CursorMut {
+            link: Some(&mut self.head),
+            index: 0,
+            len: &mut self.len,
+            alloc: &self.alloc,
+        }
+    }
+}
+
+impl<'a, T, A: Allocator + Clone> CursorMut<'a, T, A> {
+    fn link(&mut self) -> &mut Link<T, A> {
+        self.link.as_mut().unwrap()
+    }
+
+    pub fn move_next(&mut self) {
+        let link = self.link.take().unwrap();
+
+        // For the ghost there is no next link to move to, so stay where we are
+        self.link = Some(match link {
+            Some(node) => {
+                self.index += 1;
+                &mut node.next
+            }
+            None => link,
+        });
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.link().as_mut().map(|node| &mut node.elem)
+    }
+
+    // Inserts elem in front of current element, cursor still points to the same element.
+    // On the ghost it appends at the end of the list.
+    pub fn insert_before(&mut self, elem: T) {
+        let link = self.link.take().unwrap();
+
+        *link = Some(Node::boxed(elem, link.take(), self.alloc.clone()));
+        *self.len += 1;
+
+        // Current element is now next of the new node
+        self.link = Some(&mut link.as_mut().unwrap().next);
+        self.index += 1;
+    }
+
+    // Inserts elem after current element, cursor still points to the same element.
+    // There is nothing after the ghost, so it behaves like insert_before - appends at the end.
+    pub fn insert_after(&mut self, elem: T) {
+        let alloc = self.alloc;
+
+        match self.link() {
+            Some(node) => {
+                node.next = Some(Node::boxed(elem, node.next.take(), alloc.clone()));
+                *self.len += 1;
+            }
+            None => self.insert_before(elem),
+        }
+    }
+
+    // Swaps the current node with the next one by relinking them, elements don't move
+    // in memory. The cursor keeps its position, so the old next element is the current
+    // one now. Returns false (and does nothing) if there is no next, or on the ghost.
+    pub fn swap_adjacent(&mut self) -> bool {
+        let link = self.link();
+
+        // link -> a -> b -> rest  becomes  link -> b -> a -> rest
+        let mut a = match link.take() {
+            Some(a) => a,
+            None => return false,
+        };
+        let mut b = match a.next.take() {
+            Some(b) => b,
+            None => {
+                *link = Some(a);
+                return false;
+            }
+        };
+
+        a.next = b.next.take();
+        b.next = Some(a);
+        *link = Some(b);
+        true
+    }
+
+    // Unlinks current element and returns it, cursor moves to the next one.
+    pub fn remove_current(&mut self) -> Option<T> {
+        let link = self.link();
+        let node = link.take()?;
+        *link = node.next;
+
+        *self.len -= 1;
+        Some(node.elem)
+    }
+
+    // Cuts the list after current element, everything behind it is returned as a new list.
+    pub fn split_after(&mut self) -> List<T, A> {
+        let head = self.link().as_mut().and_then(|node| node.next.take());
+
+        // Nothing is cut off behind the ghost
+        let len = match head {
+            Some(_) => *self.len - self.index - 1,
+            None => 0,
+        };
+        *self.len -= len;
+
+        List::from_head(head, len, self.alloc.clone())
+    }
+}
+
+// Lazily removes elements matching the predicate, built on top of the cursor.
+// Elements are unlinked only as the iterator advances, if it's dropped early
+// the rest of the list stays untouched.
+pub struct ExtractIf<'a, T, F, A = Global>
+where
+    F: FnMut(&mut T) -> bool,
+    A: Allocator,
+{
+    cursor: CursorMut<'a, T, A>,
+    pred: F,
+}
+
+impl<T, A: Allocator + Clone> List<T, A> {
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, T, F, A>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        ExtractIf {
+            cursor: self.cursor_mut(),
+            pred,
+        }
+    }
+}
+
+impl<'a, T, F, A> Iterator for ExtractIf<'a, T, F, A>
+where
+    F: FnMut(&mut T) -> bool,
+    A: Allocator + Clone,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let matched = match self.cursor.current() {
+                Some(elem) => (self.pred)(elem),
+                None => return None,
+            };
+
+            if matched {
+                return self.cursor.remove_current();
+            }
+
+            self.cursor.move_next();
+        }
+    }
+}
+
+// Cursor stays on the ghost once it gets there
+impl<'a, T, F, A> FusedIterator for ExtractIf<'a, T, F, A>
+where
+    F: FnMut(&mut T) -> bool,
+    A: Allocator + Clone,
+{
+}
+
+// Unlike Vec::drain, nothing has to be shifted back into place when the iterator is done -
+// the range of nodes is unlinked up front, and the list is whole again before drain returns.
+// Drain owns the nodes it's left with, so it does not borrow the list, and whatever
+// is not taken out of it is dropped with it.
+pub struct Drain<T, A: Allocator = Global>(IntoIter<T, A>);
+
+impl<T, A: Allocator + Clone> List<T, A> {
+    // Takes all elements out, O(1), the list is left empty
+    pub fn drain(&mut self) -> Drain<T, A> {
+        let head = self.head.take();
+        let len = core::mem::replace(&mut self.len, 0);
+
+        Drain(List::from_head(head, len, self.alloc.clone()).into_iter())
+    }
+
+    // Takes the elements in range out, O(end of the range). Panics if the range
+    // is out of bounds, or starts after it ends, same as Vec::drain.
+    pub fn drain_range<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<T, A> {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start
+                .checked_add(1)
+                .expect("drain range start overflows usize"),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.checked_add(1).expect("drain range end overflows usize"),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len,
+        };
+        assert!(
+            start <= end,
+            "drain range starts at {} but ends at {}",
+            start,
+            end
+        );
+        assert!(
+            end <= self.len,
+            "drain range end {} out of bounds, len is {}",
+            end,
+            self.len
+        );
+
+        // Cut out the chain of nodes [start, end), and link what was after it in its place
+        let link = self.link_at(start).unwrap();
+        let mut head = link.take();
+        *link = split_link(&mut head, end - start);
+        self.len -= end - start;
+
+        Drain(List::from_head(head, end - start, self.alloc.clone()).into_iter())
+    }
+}
+
+impl<T, A: Allocator + Clone> Iterator for Drain<T, A> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T, A: Allocator + Clone> DoubleEndedIterator for Drain<T, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+impl<T, A: Allocator + Clone> ExactSizeIterator for Drain<T, A> {}
+
+impl<T, A: Allocator + Clone> FusedIterator for Drain<T, A> {}
+
+#[cfg(feature = "proptest")]
+impl<T: proptest::arbitrary::Arbitrary> proptest::arbitrary::Arbitrary for List<T> {
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <T as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = crate::arbitrary::ListStrategy<T, Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::arbitrary::list_strategy(args, |elems| elems.into_iter().collect())
+    }
+}
+
+// Derived Debug printed the nodes, Node { elem: 1, next: Some(Node { elem: 2, .. }) },
+// nested as deep as the list is long, and recursively. Elements are what matters,
+// so print them like Vec does: [1, 2, 3]
+impl<T: Debug, A: Allocator> Debug for List<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+// Elements separated with ", ", for other separator use display
+impl<T: Display, A: Allocator> Display for List<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.display(", ").fmt(f)
+    }
+}
+
+pub struct DisplayList<'a, T, A: Allocator = Global> {
+    list: &'a List<T, A>,
+    separator: &'a str,
+}
+
+impl<T, A: Allocator> List<T, A> {
+    // println!("{}", list.display(" -> ")) prints 1 -> 2 -> 3
+    pub fn display<'a>(&'a self, separator: &'a str) -> DisplayList<'a, T, A> {
+        DisplayList {
+            list: self,
+            separator,
+        }
+    }
+}
+
+impl<T: Debug, A: Allocator> List<T, A> {
+    // Graphviz graph of the nodes, see dot.rs
+    pub fn to_dot(&self) -> String {
+        let mut dot = Dot::new();
+        let mut cur = self.head.as_deref();
+
+        dot.root("head", cur.map(|node| node as *const Node<T, A>));
+        while let Some(node) = cur {
+            dot.node(node, &node.elem, "");
+            if let Some(next) = node.next.as_deref() {
+                dot.edge(node, next, "");
+            }
+            cur = node.next.as_deref();
+        }
+
+        dot.finish()
+    }
+}
+
+impl<'a, T: Display, A: Allocator> Display for DisplayList<'a, T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, elem) in self.list.iter().enumerate() {
+            if i > 0 {
+                f.write_str(self.separator)?;
+            }
+            // Pass the formatter on, so width, precision etc. apply to every element
+            elem.fmt(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+// Compares elements only, not allocators. Derived one would be recursive, same as Drop.
+impl<T: PartialEq, A: Allocator> PartialEq for List<T, A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq, A: Allocator> Eq for List<T, A> {}
+
+// Lexicographic, same as for slices: first different element decides, if one list
+// is a prefix of the other, the shorter one is less
+impl<T: PartialOrd, A: Allocator> PartialOrd for List<T, A> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T: Ord, A: Allocator> Ord for List<T, A> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+// Length goes first, same as for Vec, otherwise [[1], [2]] and [[1, 2]] could end up
+// with the same hash (elements are hashed one after another, with no separator)
+impl<T: Hash, A: Allocator> Hash for List<T, A> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        for elem in self.iter() {
+            elem.hash(state);
+        }
+    }
+}
+
+impl<T, A: Allocator> Drop for List<T, A> {
+    fn drop(&mut self) {
+        // Take ownership over the head, sets head to None
+        let mut elem = self.head.take();
+
+        while let Some(boxed) = elem {
+            // Old value got drop here
+            elem = boxed.next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_back_works() {
+        let mut list = List::new();
+
+        assert_eq!(
+            list,
+            List {
+                head: None,
+                len: 0,
+                alloc: Global,
+                pool: NodePool::new(0),
+                max_len: None,
+            }
+        );
+
+        list.push_back(69);
+
+        assert_eq!(
+            list,
+            List {
+                head: Some(Node::boxed(69, None, Global)),
+                len: 1,
+                alloc: Global,
+                pool: NodePool::new(0),
+                max_len: None,
+            }
+        );
+
+        list.push_back(13);
+
+        assert_eq!(
+            list,
+            List {
+                head: Some(Node::boxed(69, Some(Node::boxed(13, None, Global)), Global)),
+                len: 2,
+                alloc: Global,
+                pool: NodePool::new(0),
+                max_len: None,
+            }
+        );
+    }
+
+    #[test]
     fn basics() {
         let mut list = List::new();
 
-        // Check empty list behaves right
-        assert_eq!(list.pop(), None);
+        // Check empty list behaves right
+        assert_eq!(list.pop(), None);
+
+        // Populate list
+        list.push(1);
+        list.push(2);
+        list.push(3);
+
+        // Check normal removal
+        assert_eq!(list.pop(), Some(3));
+        assert_eq!(list.pop(), Some(2));
+
+        // Push some more just to make sure nothing's corrupted
+        list.push(4);
+        list.push(5);
+
+        // Check normal removal
+        assert_eq!(list.pop(), Some(5));
+        assert_eq!(list.pop(), Some(4));
+
+        // Check exhaustion
+        assert_eq!(list.pop(), Some(1));
+        assert_eq!(list.pop(), None);
+    }
+
+    #[test]
+    fn break_the_stack() {
+        {
+            let mut list = List::new();
+
+            for i in 1..100000 {
+                list.push(i);
+            }
+            println!("Leaving, call dtor");
+            // Without custom Drop implementation, compiler does:
+            // List.drop -> Link.drop() -> Box.drop() -> Node.drop() -> next.drop() (Link.drop())
+            //                ^---------------------------------------------------------|
+            // Recursive call! Tail recursion cannot be applied here
+            // Having list with huge amount of elements will blow up the stack
+        }
+
+        println!("Still alive!");
+    }
+
+    #[test]
+    fn peek() {
+        let mut list = List::new();
+        assert_eq!(list.peek(), None);
+        assert_eq!(list.peek_mut(), None);
+        list.push(1);
+        list.push(2);
+        list.push(3);
+
+        assert_eq!(list.peek(), Some(&3));
+        assert_eq!(list.peek_mut(), Some(&mut 3));
+
+        // value is of type &mut i32
+        // Some(&mut value) is a pattern matching, resulting if type for value to be i32
+        if let Some(value) = list.peek_mut() {
+            *value = 42;
+        }
+
+        assert_eq!(list.peek(), Some(&42));
+        assert_eq!(list.pop(), Some(42));
+    }
+
+    #[test]
+    fn into_iter() {
+        let mut list = List::new();
+        list.push(1);
+        list.push(2);
+        list.push(3);
+
+        // let mut iter = list.into_iter();
+
+        let mut expected = 3;
+        for el in list {
+            assert_eq!(el, expected);
+
+            expected -= 1;
+        }
+    }
+
+    #[test]
+    fn iter() {
+        let mut list = List::new();
+        list.push(1);
+        list.push(2);
+        list.push(3);
+
+        {
+            let mut iter = list.iter();
+            assert_eq!(iter.next(), Some(&3));
+            assert_eq!(iter.next(), Some(&2));
+            assert_eq!(iter.next(), Some(&1));
+        }
+
+        // Collection is not moved, nor altered
+        assert_eq!(list.peek(), Some(&3));
+    }
+
+    #[test]
+    fn cursor_mut() {
+        let mut list = List::new();
+        list.push(3);
+        list.push(1);
+
+        let mut cursor = list.cursor_mut();
+        assert_eq!(cursor.current(), Some(&mut 1));
+
+        // 0 1 3
+        cursor.insert_before(0);
+        assert_eq!(cursor.current(), Some(&mut 1));
+
+        // 0 1 2 3
+        cursor.insert_after(2);
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 2));
+
+        // Modify in place
+        *cursor.current().unwrap() = 20;
+
+        // 0 1 20
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(3));
+        assert_eq!(cursor.current(), None);
+
+        // Ghost stays where it is, and both inserts append
+        // 0 1 20 4 5
+        cursor.move_next();
+        cursor.insert_after(4);
+        cursor.insert_before(5);
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.remove_current(), None);
+
+        assert_eq!(
+            list.iter().copied().collect::<Vec<_>>(),
+            vec![0, 1, 20, 4, 5]
+        );
+
+        let mut cursor = list.cursor_mut();
+        cursor.move_next();
+        cursor.move_next();
+
+        // 0 1 20 | 4 5
+        let tail = cursor.split_after();
+        assert_eq!(tail.iter().copied().collect::<Vec<_>>(), vec![4, 5]);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![0, 1, 20]);
+
+        // Remove everything through the cursor
+        let mut cursor = list.cursor_mut();
+        while cursor.remove_current().is_some() {}
+        assert_eq!(list.peek(), None);
+        assert_eq!(list.cursor_mut().split_after(), List::new());
+    }
+
+    #[test]
+    fn double_ended() {
+        let mut list = List::new();
+        list.push(1);
+        list.push(2);
+        list.push(3);
+        list.push(4);
+
+        assert_eq!(
+            list.iter().rev().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3, 4]
+        );
+
+        // Meet in the middle
+        let mut iter = list.iter();
+        assert_eq!(iter.next(), Some(&4));
+        assert_eq!(iter.next_back(), Some(&1));
+        assert_eq!(iter.next(), Some(&3));
+        assert_eq!(iter.next_back(), Some(&2));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+
+        for (i, elem) in list.iter_mut().rev().enumerate() {
+            *elem *= 10 * (i as i32 + 1);
+        }
+
+        let mut iter = list.iter_mut();
+        assert_eq!(iter.next_back(), Some(&mut 10));
+        assert_eq!(iter.next(), Some(&mut 160));
+        assert_eq!(iter.next_back(), Some(&mut 40));
+        assert_eq!(iter.next_back(), Some(&mut 90));
+        assert_eq!(iter.next(), None);
+
+        let mut iter = list.into_iter();
+        assert_eq!(iter.next(), Some(160));
+        assert_eq!(iter.next_back(), Some(10));
+        assert_eq!(iter.next(), Some(90));
+        assert_eq!(iter.next_back(), Some(40));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+    }
+
+    #[test]
+    fn clone() {
+        let mut list = List::new();
+        assert_eq!(list.clone(), List::new());
+
+        list.push(1);
+        list.push(2);
+        list.push(3);
+
+        let mut cloned = list.clone();
+        assert_eq!(cloned, list);
+
+        // Copies are independent
+        cloned.pop();
+        assert_eq!(list.peek(), Some(&3));
+        assert_eq!(cloned.peek(), Some(&2));
+    }
+
+    #[test]
+    fn clone_does_not_break_the_stack() {
+        let mut list = List::new();
+
+        for i in 0..1_000_000 {
+            list.push(i);
+        }
+
+        let cloned = list.clone();
+
+        // PartialEq compares with iterators, no recursion there either
+        assert_eq!(cloned, list);
+    }
+
+    #[test]
+    fn from_iter_and_extend() {
+        let mut list = (0..5).collect::<List<_>>();
+        assert_eq!(
+            list.iter().copied().collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 4]
+        );
+
+        list.extend(5..8);
+        list.extend(Vec::<i32>::new());
+        assert!(list.iter().copied().eq(0..8));
+
+        let mut empty = List::new();
+        empty.extend(vec![1, 2]);
+        assert_eq!(empty.pop(), Some(1));
+        assert_eq!(empty.pop(), Some(2));
+        assert_eq!(empty.pop(), None);
+    }
+
+    #[test]
+    fn insert_remove() {
+        let mut list = List::new();
+
+        assert_eq!(list.insert(1, 0), Err(0));
+        assert_eq!(list.remove(0), None);
+
+        assert_eq!(list.insert(0, 1), Ok(()));
+        // Insert at len is an append
+        assert_eq!(list.insert(1, 3), Ok(()));
+        assert_eq!(list.insert(1, 2), Ok(()));
+        assert_eq!(list.insert(0, 0), Ok(()));
+        assert_eq!(list.insert(5, 5), Err(5));
+
+        assert!(list.iter().copied().eq(0..4));
+
+        assert_eq!(list.remove(4), None);
+        assert_eq!(list.remove(2), Some(2));
+        assert_eq!(list.remove(2), Some(3));
+        assert_eq!(list.remove(0), Some(0));
+        assert_eq!(list.remove(1), None);
+        assert_eq!(list.remove(0), Some(1));
+        assert_eq!(list.remove(0), None);
+    }
+
+    #[test]
+    fn reverse() {
+        let mut list: List<i32> = List::new();
+        list.reverse();
+        assert_eq!(list.peek(), None);
+
+        list.push(1);
+        list.reverse();
+        assert_eq!(list.peek(), Some(&1));
+
+        let mut list = (0..5).collect::<List<_>>();
+        list.reverse();
+        assert!(list.iter().copied().eq((0..5).rev()));
+
+        // Reversed list is still linked properly
+        list.push_back(-1);
+        assert_eq!(list.iter().next_back(), Some(&-1));
+    }
+
+    // Simple LCG, good enough to shuffle test data
+    fn random_numbers(count: usize, mut seed: u64) -> Vec<u64> {
+        (0..count)
+            .map(|_| {
+                seed = seed
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                seed >> 33
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sort() {
+        let mut list: List<i32> = List::new();
+        list.sort();
+        assert_eq!(list.peek(), None);
+
+        let mut list = vec![3, 1, 2].into_iter().collect::<List<_>>();
+        list.sort();
+        assert!(list.iter().copied().eq(1..4));
+
+        list.sort_by(|a, b| b.cmp(a));
+        assert!(list.iter().copied().eq((1..4).rev()));
+
+        // Still properly linked
+        list.push_back(0);
+        assert_eq!(list.iter().next_back(), Some(&0));
+    }
+
+    #[test]
+    fn sort_random() {
+        for &count in &[2, 3, 7, 64, 1000, 10_007] {
+            let mut expected = random_numbers(count, count as u64);
+            let mut list = expected.iter().copied().collect::<List<_>>();
+
+            expected.sort();
+            list.sort();
+
+            assert!(list.iter().eq(expected.iter()));
+        }
+    }
+
+    #[test]
+    fn sort_is_stable() {
+        // Only first element of the pair is compared, second keeps original position
+        let numbers = random_numbers(5000, 69);
+        let mut expected = numbers
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n % 10, i))
+            .collect::<Vec<_>>();
+
+        let mut list = expected.iter().copied().collect::<List<_>>();
+
+        expected.sort_by_key(|pair| pair.0);
+        list.sort_by(|a, b| a.0.cmp(&b.0));
+
+        assert!(list.iter().eq(expected.iter()));
+    }
+
+    #[test]
+    fn sort_panicking_compare() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        for panic_at in 0..20 {
+            let mut list = (0..8).rev().collect::<List<i32>>();
+            let mut calls = 0;
+            let result = catch_unwind(AssertUnwindSafe(|| {
+                list.sort_by(|a, b| {
+                    calls += 1;
+                    assert_ne!(calls, panic_at);
+                    a.cmp(b)
+                })
+            }));
+
+            // Order is unspecified after a panic, but no element is lost
+            assert_eq!(result.is_err(), panic_at > 0 && panic_at <= calls);
+            assert_eq!(list.len(), list.iter().count());
+            let mut elems = list.into_iter().collect::<Vec<_>>();
+            elems.sort();
+            assert_eq!(elems, (0..8).collect::<Vec<_>>());
+        }
+
+        let mut list = list![1, 3, 5];
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            list.merge_by(list![2, 4], |_, _| panic!("compare"))
+        }));
+        assert!(result.is_err());
+        assert_eq!(list.len(), 5);
+        assert_eq!(list.iter().count(), 5);
+    }
+
+    #[test]
+    fn split_off() {
+        let mut list = (0..5).collect::<List<_>>();
+
+        let tail = list.split_off(5);
+        assert_eq!(tail, List::new());
+
+        let tail = list.split_off(2);
+        assert!(list.iter().copied().eq(0..2));
+        assert!(tail.iter().copied().eq(2..5));
+
+        let tail = list.split_off(0);
+        assert_eq!(list, List::new());
+        assert!(tail.iter().copied().eq(0..2));
+    }
+
+    #[test]
+    #[should_panic]
+    fn split_off_out_of_bounds() {
+        let mut list = (0..5).collect::<List<_>>();
+        list.split_off(6);
+    }
+
+    #[test]
+    fn retain() {
+        let mut list = (0..10).collect::<List<_>>();
+
+        list.retain(|&x| x % 2 == 0);
+        assert!(list.iter().copied().eq((0..10).step_by(2)));
+
+        list.retain_mut(|x| {
+            *x *= 10;
+            *x != 40
+        });
+        assert_eq!(
+            list.iter().copied().collect::<Vec<_>>(),
+            vec![0, 20, 60, 80]
+        );
+
+        list.retain(|_| false);
+        assert_eq!(list, List::new());
+    }
+
+    #[test]
+    fn extract_if() {
+        let mut list = (0..10).collect::<List<_>>();
+
+        let evens = list.extract_if(|x| *x % 2 == 0).collect::<Vec<_>>();
+        assert_eq!(evens, vec![0, 2, 4, 6, 8]);
+        assert!(list.iter().copied().eq((1..10).step_by(2)));
+
+        // Stop early, the rest is not touched
+        assert_eq!(list.extract_if(|x| *x > 2).next(), Some(3));
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![1, 5, 7, 9]);
+
+        assert_eq!(list.extract_if(|_| true).count(), 4);
+        assert_eq!(list, List::new());
+    }
+
+    #[cfg(feature = "allocator_api")]
+    #[test]
+    fn bump_allocator() {
+        let bump = bumpalo::Bump::new();
+        let mut list = List::new_in(&bump);
+
+        for i in (0..5).rev() {
+            list.push(i);
+        }
+        list.push_back(5);
+        assert_eq!(list.insert(6, 6), Ok(()));
+        list.extend(7..10);
+
+        let used = bump.allocated_bytes();
+        assert!(used > 0);
+
+        // Nodes of the copy and of split off part live in the same arena
+        let mut cloned = list.clone();
+        let tail = cloned.split_off(5);
+        cloned.cursor_mut().insert_after(100);
+        assert!(bump.allocated_bytes() > used);
+
+        assert!(list.iter().copied().eq(0..10));
+        assert!(tail.into_iter().eq(5..10));
+        assert_eq!(
+            cloned.iter().copied().collect::<Vec<_>>(),
+            vec![0, 100, 1, 2, 3, 4]
+        );
+
+        list.sort_by(|a, b| b.cmp(a));
+        assert!(list.into_iter().eq((0..10).rev()));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() {
+        let list: List<i32> = (1..=3).collect();
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(json, "[1,2,3]");
+        assert_eq!(serde_json::from_str::<List<i32>>(&json).unwrap(), list);
+
+        assert!(serde_json::from_str::<List<i32>>("[1, 2, \"three\"]").is_err());
+
+        // Long list does not blow the stack, neither way
+        let list: List<i32> = (0..1_000_000).collect();
+        let json = serde_json::to_string(&list).unwrap();
+        let list = serde_json::from_str::<List<i32>>(&json).unwrap();
+        assert!(list.into_iter().eq(0..1_000_000));
+    }
+
+    #[test]
+    fn list_macro() {
+        let list: List<i32> = list![];
+        assert_eq!(list, List::new());
+
+        let mut list = list![1, 2, 3,];
+        assert_eq!(list.pop(), Some(1));
+        assert!(list.iter().eq(&[2, 3]));
+
+        let list = list![String::from("a"); 3];
+        assert!(list.into_iter().eq(vec!["a"; 3]));
+    }
+
+    #[test]
+    fn into_iterator_for_references() {
+        let mut list = list![1, 2, 3];
+
+        let mut sum = 0;
+        for &elem in &list {
+            sum += elem;
+        }
+        assert_eq!(sum, 6);
+
+        for elem in &mut list {
+            *elem *= 10;
+        }
+
+        let mut out = Vec::new();
+        for elem in list {
+            out.push(elem);
+        }
+        assert_eq!(out, vec![10, 20, 30]);
+    }
+
+    #[test]
+    fn len_is_tracked() {
+        let mut list: List<i32> = (0..10).collect();
+        assert_eq!(list.len(), 10);
+
+        list.push(-1);
+        list.push_back(10);
+        list.insert(3, 100).unwrap();
+        assert!(list.insert(100, 0).is_err());
+        assert_eq!(list.len(), 13);
+
+        list.pop();
+        list.remove(2);
+        list.retain(|elem| *elem != 2);
+        assert_eq!(list.len(), 10);
+        assert_eq!(list.len(), list.iter().count());
+
+        let tail = list.split_off(7);
+        assert_eq!((list.len(), tail.len()), (7, 3));
+
+        let mut cursor = list.cursor_mut();
+        cursor.move_next();
+        cursor.insert_before(-2);
+        cursor.insert_after(-3);
+        cursor.remove_current();
+        cursor.move_next();
+        let rest = cursor.split_after();
+        assert!(rest.iter().eq(&[4, 5, 6, 7]));
+        assert_eq!(rest.len(), 4);
+        assert!(list.iter().eq(&[0, -2, -3, 3]));
+        assert_eq!(list.len(), 4);
+
+        let odd: List<i32> = list.extract_if(|elem| *elem % 2 != 0).collect();
+        assert_eq!((list.len(), odd.len()), (2, 2));
+        assert_eq!(list.clone().len(), list.len());
+    }
+
+    #[test]
+    fn exact_size_iterators() {
+        let mut list: List<i32> = (0..5).collect();
+
+        let mut iter = list.iter();
+        assert_eq!(iter.len(), 5);
+        iter.next();
+        iter.next_back();
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.by_ref().count(), 3);
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.len(), 0);
+
+        let mut iter = list.iter_mut();
+        iter.next_back();
+        assert_eq!(iter.len(), 4);
+
+        let mut iter = list.into_iter();
+        iter.next();
+        assert_eq!(iter.len(), 4);
+        iter.next_back();
+        assert_eq!(iter.size_hint(), (3, Some(3)));
+    }
+
+    #[test]
+    fn pop_back() {
+        let mut list = list![1, 2, 3];
+        assert_eq!(list.peek_back(), Some(&3));
+
+        if let Some(elem) = list.peek_back_mut() {
+            *elem = 30;
+        }
+
+        assert_eq!(list.pop_back(), Some(30));
+        assert_eq!(list.pop_back(), Some(2));
+        assert_eq!(list.len(), 1);
+
+        list.push_back(4);
+        assert_eq!(list.pop_back(), Some(4));
+        assert_eq!(list.pop_back(), Some(1));
+        assert_eq!(list.pop_back(), None);
+        assert_eq!(list.peek_back(), None);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn get_and_index() {
+        let mut list = list![10, 20, 30];
+
+        assert_eq!(list.get(0), Some(&10));
+        assert_eq!(list.get(2), Some(&30));
+        assert_eq!(list.get(3), None);
+
+        *list.get_mut(1).unwrap() += 1;
+        assert_eq!(list.get_mut(5), None);
+
+        list[2] = 33;
+        assert_eq!((list[0], list[1], list[2]), (10, 21, 33));
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds: the len is 3 but the index is 3")]
+    fn index_out_of_bounds() {
+        let list = list![1, 2, 3];
+        let _ = list[3];
+    }
 
-        // Populate list
-        list.push(1);
-        list.push(2);
-        list.push(3);
+    #[test]
+    fn search() {
+        let list = list![3, 1, 4, 1, 5];
 
-        // Check normal removal
-        assert_eq!(list.pop(), Some(3));
-        assert_eq!(list.pop(), Some(2));
+        assert!(list.contains(&4));
+        assert!(!list.contains(&2));
 
-        // Push some more just to make sure nothing's corrupted
-        list.push(4);
-        list.push(5);
+        assert_eq!(list.find(|elem| *elem > 3), Some(&4));
+        assert_eq!(list.find(|elem| *elem > 5), None);
 
-        // Check normal removal
-        assert_eq!(list.pop(), Some(5));
-        assert_eq!(list.pop(), Some(4));
+        assert_eq!(list.position(|elem| *elem == 1), Some(1));
+        assert_eq!(list.position(|elem| *elem == 9), None);
+    }
 
-        // Check exhaustion
+    #[test]
+    fn remove_by_value() {
+        let mut list = list![1, 2, 1, 3, 1];
+
+        assert!(list.remove_first(&1));
+        assert!(list.iter().eq(&[2, 1, 3, 1]));
+        assert!(!list.remove_first(&5));
+
+        assert_eq!(list.remove_all(&1), 2);
+        assert!(list.iter().eq(&[2, 3]));
+        assert_eq!(list.remove_all(&1), 0);
+
+        assert!(list.remove_first(&3));
+        assert!(list.remove_first(&2));
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn dedup() {
+        let mut list = list![1, 1, 2, 3, 3, 3, 1, 4, 4];
+        list.dedup();
+        assert!(list.iter().eq(&[1, 2, 3, 1, 4]));
+        assert_eq!(list.len(), 5);
+
+        let mut list = list![10, 11, 20, 21, 22, 30];
+        list.dedup_by_key(|elem| *elem / 10);
+        assert!(list.iter().eq(&[10, 20, 30]));
+
+        let mut list = list!["a", "A", "b", "B", "b"];
+        list.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+        assert!(list.iter().eq(&["a", "b"]));
+
+        let mut list: List<i32> = List::new();
+        list.dedup();
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn merge() {
+        let mut list = list![1, 3, 5, 7];
+        list.merge(list![2, 3, 4, 8, 9]);
+        assert!(list.iter().eq(&[1, 2, 3, 3, 4, 5, 7, 8, 9]));
+        assert_eq!(list.len(), 9);
+
+        list.merge(List::new());
+        assert_eq!(list.len(), 9);
+
+        let mut empty = List::new();
+        empty.merge(list![1, 2]);
+        assert!(empty.iter().eq(&[1, 2]));
+
+        // Stable - ties keep elements of self in front
+        let mut list = list![(1, 'a'), (2, 'a')];
+        list.merge_by(list![(1, 'b'), (2, 'b')], |a, b| a.0.cmp(&b.0));
+        assert!(list.iter().eq(&[(1, 'a'), (1, 'b'), (2, 'a'), (2, 'b')]));
+    }
+
+    #[test]
+    fn split_when() {
+        let mut list = list![1, 2, 0, 3, 0, 4];
+
+        let frame = list.split_when(|elem| *elem == 0);
+        assert!(list.iter().eq(&[1, 2]));
+        assert!(frame.iter().eq(&[0, 3, 0, 4]));
+        assert_eq!((list.len(), frame.len()), (2, 4));
+
+        // No match, nothing split off
+        let rest = list.split_when(|elem| *elem > 10);
+        assert!(rest.is_empty());
+        assert_eq!(list.len(), 2);
+
+        // Match on the head takes everything
+        let all = list.split_when(|_| true);
+        assert!(list.is_empty());
+        assert!(all.iter().eq(&[1, 2]));
+    }
+
+    #[test]
+    fn rotate() {
+        let mut list: List<i32> = (0..5).collect();
+
+        list.rotate_left(2);
+        assert!(list.iter().eq(&[2, 3, 4, 0, 1]));
+
+        list.rotate_right(2);
+        assert!(list.iter().eq(&[0, 1, 2, 3, 4]));
+
+        list.rotate_left(0);
+        list.rotate_right(5);
+        assert!(list.iter().eq(&[0, 1, 2, 3, 4]));
+        assert_eq!(list.len(), 5);
+
+        // Round robin
+        let mut order = Vec::new();
+        for _ in 0..7 {
+            order.push(*list.peek().unwrap());
+            list.rotate_left(1);
+        }
+        assert_eq!(order, vec![0, 1, 2, 3, 4, 0, 1]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rotate_out_of_bounds() {
+        let mut list = list![1, 2];
+        list.rotate_left(3);
+    }
+
+    #[test]
+    fn bulk_constructors() {
+        let list = List::from(vec![1, 2, 3]);
+        assert!(list.iter().eq(&[1, 2, 3]));
+        assert_eq!(list.len(), 3);
+
+        let list: List<_> = ["a", "b"].into();
+        assert!(list.iter().eq(&["a", "b"]));
+        assert_eq!(list.len(), 2);
+
+        let list = List::from_fn(4, |i| i * i);
+        assert!(list.iter().eq(&[0, 1, 4, 9]));
+        assert_eq!(list.len(), 4);
+
+        assert!(List::from_fn(0, |i| i).is_empty());
+    }
+
+    #[test]
+    fn into_std_collections() {
+        let list = list![String::from("a"), String::from("b")];
+        let vec = list.into_vec();
+        assert_eq!(vec, vec!["a", "b"]);
+        assert_eq!(vec.capacity(), 2);
+
+        let deque = VecDeque::from(list![1, 2, 3]);
+        assert_eq!(deque, [1, 2, 3]);
+
+        let linked: LinkedList<_> = list![1, 2, 3].into();
+        assert!(linked.into_iter().eq(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn ord_and_hash() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::collections::HashSet;
+
+        let hash = |list: &List<i32>| {
+            let mut hasher = DefaultHasher::new();
+            list.hash(&mut hasher);
+            hasher.finish()
+        };
+
+        assert!(list![1, 2] < list![1, 3]);
+        assert!(list![1, 2] < list![1, 2, 0]);
+        assert!(List::new() < list![0]);
+        assert_eq!(list![1, 2].cmp(&list![1, 2]), Ordering::Equal);
+
+        let mut lists = vec![list![2], list![1, 5], list![1]];
+        lists.sort();
+        assert_eq!(lists, vec![list![1], list![1, 5], list![2]]);
+
+        assert_eq!(hash(&list![1, 2, 3]), hash(&list![1, 2, 3]));
+
+        let set: HashSet<_> = vec![list![1], list![1], list![2, 3]].into_iter().collect();
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(&list![2, 3]));
+    }
+
+    #[test]
+    fn debug_and_display() {
+        let list = list![1, 2, 3];
+        assert_eq!(format!("{:?}", list), "[1, 2, 3]");
+        assert_eq!(format!("{:?}", List::<i32>::new()), "[]");
+        assert_eq!(format!("{:?}", list![list!["a"]]), "[[\"a\"]]");
+
+        assert_eq!(format!("{}", list), "1, 2, 3");
+        assert_eq!(format!("{}", list.display(" -> ")), "1 -> 2 -> 3");
+        assert_eq!(format!("{:02}", list.display("|")), "01|02|03");
+        assert_eq!(format!("{}", List::<i32>::new().display("|")), "");
+
+        // Long list does not overflow the stack
+        let list: List<i32> = (0..100_000).collect();
+        assert!(format!("{:?}", list).ends_with("99999]"));
+    }
+
+    #[test]
+    fn to_dot() {
+        let dot = list!["a", "b"].to_dot();
+        assert_eq!(dot.matches("->").count(), 2);
+        assert!(dot.contains(r#"[label="\"a\""]"#));
+    }
+
+    #[cfg(feature = "proptest")]
+    proptest::proptest! {
+        #[test]
+        fn arbitrary_matches_vec(elems: Vec<i32>) {
+            let list: List<i32> = elems.iter().copied().collect();
+            proptest::prop_assert_eq!(list.into_vec(), elems);
+        }
+
+        #[test]
+        fn reverse_twice(list: List<i32>) {
+            let mut reversed = List::new();
+            for elem in list.iter() {
+                reversed.push(*elem);
+            }
+
+            let mut back = List::new();
+            for elem in reversed.iter() {
+                back.push(*elem);
+            }
+            proptest::prop_assert_eq!(back, list);
+        }
+    }
+
+    #[test]
+    fn pool_reuses_nodes() {
+        let mut list = List::with_pool_capacity(2);
+        list.push(1);
+        let node = list.peek().unwrap() as *const i32;
+
+        // Popped node goes to the pool, and comes back with the next push
         assert_eq!(list.pop(), Some(1));
-        assert_eq!(list.pop(), None);
+        list.push_back(2);
+        assert_eq!(list.peek().unwrap() as *const i32, node);
+
+        // No more than capacity is kept
+        list.extend(3..10);
+        while list.pop().is_some() {}
+        assert_eq!(list.pool.available(), 2);
+
+        list.insert(0, 10).unwrap();
+        list.insert(1, 11).unwrap();
+        list.insert(1, 12).unwrap();
+        assert_eq!(list.pool.available(), 0);
+        assert_eq!(list.remove(1), Some(12));
+        assert_eq!(list.pop_back(), Some(11));
+        assert_eq!(list.pool.available(), 2);
+        assert!(list.iter().eq(&[10]));
+
+        // Elements are dropped when popped, not when the node is reused
+        let elem = std::rc::Rc::new(());
+        let mut list = List::with_pool_capacity(1);
+        list.push(elem.clone());
+        list.pop();
+        assert_eq!(std::rc::Rc::strong_count(&elem), 1);
     }
 
+    #[cfg(feature = "allocator_api")]
     #[test]
-    fn break_the_stack() {
-        {
-            let mut list = List::new();
+    fn pool_saves_allocations() {
+        use std::alloc::{AllocError, Allocator, Global, Layout};
+        use std::cell::Cell;
+        use std::ptr::NonNull;
+
+        #[derive(Default)]
+        struct Counting {
+            allocations: Cell<usize>,
+            live: Cell<isize>,
+        }
 
-            for i in 1..100000 {
-                list.push(i);
+        unsafe impl Allocator for &Counting {
+            fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+                self.allocations.set(self.allocations.get() + 1);
+                self.live.set(self.live.get() + 1);
+                Global.allocate(layout)
+            }
+
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                self.live.set(self.live.get() - 1);
+                Global.deallocate(ptr, layout)
             }
-            println!("Leaving, call dtor");
-            // Without custom Drop implementation, compiler does:
-            // List.drop -> Link.drop() -> Box.drop() -> Node.drop() -> next.drop() (Link.drop())
-            //                ^---------------------------------------------------------|
-            // Recursive call! Tail recursion cannot be applied here
-            // Having list with huge amount of elements will blow up the stack
         }
 
-        println!("Still alive!");
+        let push_pop = |list: &mut List<i32, &Counting>| {
+            for _ in 0..100 {
+                for i in 0..4 {
+                    list.push(i);
+                }
+                while list.pop().is_some() {}
+            }
+        };
+
+        let counting = Counting::default();
+        push_pop(&mut List::new_in(&counting));
+        assert_eq!(counting.allocations.get(), 400);
+
+        let counting = Counting::default();
+        push_pop(&mut List::with_pool_capacity_in(4, &counting));
+        assert_eq!(counting.allocations.get(), 4);
+        // Pooled nodes are freed with the list
+        assert_eq!(counting.live.get(), 0);
     }
 
     #[test]
-    fn peek() {
-        let mut list = List::new();
-        assert_eq!(list.peek(), None);
-        assert_eq!(list.peek_mut(), None);
-        list.push(1);
-        list.push(2);
-        list.push(3);
+    fn peek_next() {
+        let mut list = list![1, 3, 5, 7];
+
+        // Pairwise smoothing, each element becomes the mean of itself and the next one
+        let mut iter = list.iter_mut();
+        while let Some(elem) = iter.next() {
+            if let Some(next) = iter.peek_next() {
+                *elem = (*elem + *next) / 2;
+            }
+        }
+        assert_eq!(list, list![2, 4, 6, 7]);
+
+        // Elements moved to back by next_back are still peeked in order
+        let mut iter = list.iter_mut();
+        assert_eq!(iter.next_back(), Some(&mut 7));
+        assert_eq!(iter.peek_next(), Some(&2));
+        iter.next();
+        iter.next();
+        assert_eq!(iter.peek_next(), Some(&6));
+        iter.next();
+        assert_eq!(iter.peek_next(), None);
+    }
 
-        assert_eq!(list.peek(), Some(&3));
-        assert_eq!(list.peek_mut(), Some(&mut 3));
+    #[test]
+    fn windows_mut() {
+        let mut list = list![1, 2, 3, 4];
 
-        // |value| is of type &mut i32
-        // |&mut value| is a pattern matching, resulting if type for value to be i32
-        list.peek_mut().map(|value| *value = 42);
+        let mut windows = list.windows_mut(2);
+        while let Some(mut window) = windows.next() {
+            assert_eq!(window.len(), 2);
+            window[1] += window[0];
+        }
+        // Running sum, each window sees the previous one's update
+        assert_eq!(list, list![1, 3, 6, 10]);
 
-        assert_eq!(list.peek(), Some(&42));
-        assert_eq!(list.pop(), Some(42));
+        let mut windows = list.windows_mut(3);
+        let mut seen = vec![];
+        while let Some(window) = windows.next() {
+            seen.push((window[0], window[1], window[2]));
+        }
+        assert_eq!(seen, vec![(1, 3, 6), (3, 6, 10)]);
+        assert!(windows.next().is_none());
+
+        // Window larger than the list - nothing
+        assert!(list.windows_mut(5).next().is_none());
     }
 
     #[test]
-    fn into_iter() {
-        let mut list = List::new();
-        list.push(1);
-        list.push(2);
-        list.push(3);
+    #[should_panic(expected = "window size must be non-zero")]
+    fn windows_mut_zero() {
+        list![1].windows_mut(0);
+    }
 
-        // let mut iter = list.into_iter();
+    #[test]
+    fn list_builder() {
+        let mut builder = ListBuilder::new();
+        assert!(builder.is_empty());
+
+        builder.append(list![2, 3, 4]);
+        builder.append(List::new());
+        builder.push_back(5);
+        builder.prepend(list![1]);
+        builder.push_front(0);
+        builder.append(list![6, 7, 8]);
+        assert_eq!(builder.len(), 9);
+
+        let list = builder.build();
+        assert_eq!(list, (0..9).collect());
+        assert_eq!(list.len(), 9);
+
+        assert!(ListBuilder::<i32>::new().build().is_empty());
+        let mut builder = ListBuilder::new();
+        builder.push_front(1);
+        assert_eq!(builder.build(), list![1]);
+
+        // Many small pieces, linear
+        let mut builder = ListBuilder::new();
+        for i in 0..10_000 {
+            builder.append(list![i, i]);
+        }
+        assert_eq!(builder.build().len(), 20_000);
+    }
 
-        let mut expected = 3;
-        for el in list {
-            assert_eq!(el, expected);
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn rayon() {
+        use rayon::prelude::*;
 
-            expected -= 1;
+        let mut list: List<u64> = (0..10_000).collect();
+        assert_eq!(
+            list.par_iter().map(|x| x * x).sum::<u64>(),
+            (0..10_000u64).map(|x| x * x).sum::<u64>()
+        );
+
+        list.par_iter_mut().for_each(|x| *x *= 2);
+        assert!(list.iter().copied().eq((0..10_000).map(|x| x * 2)));
+
+        // Order is kept both ways
+        let evens: List<u64> = list.into_par_iter().filter(|x| x % 4 == 0).collect();
+        assert!(evens.iter().copied().eq((0..20_000).step_by(4)));
+        assert_eq!(evens.len(), 5000);
+    }
+
+    #[test]
+    fn push_many_and_pop_n() {
+        let mut list = list![4, 5];
+        list.push_many(vec![1, 2, 3]);
+        assert_eq!(list, list![1, 2, 3, 4, 5]);
+        assert_eq!(list.len(), 5);
+
+        list.push_many(None);
+        assert_eq!(list.len(), 5);
+
+        let front = list.pop_n(2);
+        assert_eq!(front, list![1, 2]);
+        assert_eq!(list, list![3, 4, 5]);
+
+        // n past the end takes everything
+        assert_eq!(list.pop_n(10), list![3, 4, 5]);
+        assert!(list.is_empty());
+        assert!(list.pop_n(1).is_empty());
+
+        // Inverse of each other
+        list.push_many(0..1000);
+        assert!(list.pop_n(1000).into_iter().eq(0..1000));
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn push_many_panic() {
+        let mut list = list![1, 2];
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            list.push_many((0..5).map(|i| if i < 3 { i } else { panic!("boom") }));
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(list, list![1, 2]);
+        assert_eq!(list.len(), 2);
+    }
+
+    #[test]
+    fn drain() {
+        let mut list = list![1, 2, 3];
+        assert!(list.drain().eq(vec![1, 2, 3]));
+        assert!(list.is_empty());
+
+        // The list can be used again right away
+        list.push(4);
+        assert_eq!(list, list![4]);
+    }
+
+    #[test]
+    fn drain_range() {
+        let mut list: List<_> = (0..10).collect();
+
+        let drained: Vec<_> = list.drain_range(2..5).collect();
+        assert_eq!(drained, vec![2, 3, 4]);
+        assert_eq!(list, list![0, 1, 5, 6, 7, 8, 9]);
+        assert_eq!(list.len(), 7);
+
+        // Both ends, the tail is still where push_back finds it
+        assert!(list.drain_range(5..).eq(vec![8, 9]));
+        list.push_back(10);
+        assert!(list.drain_range(..=1).rev().eq(vec![1, 0]));
+        assert_eq!(list, list![5, 6, 7, 10]);
+
+        // Empty range takes nothing
+        assert_eq!(list.drain_range(2..2).len(), 0);
+        assert_eq!(list.len(), 4);
+
+        // Elements left in a dropped Drain are dropped, the list is not touched
+        let elem = std::rc::Rc::new(());
+        let mut list: List<_> = (0..5).map(|_| elem.clone()).collect();
+        let mut drain = list.drain_range(1..4);
+        drain.next();
+        drop(drain);
+        assert_eq!(std::rc::Rc::strong_count(&elem), 3);
+        assert_eq!(list.len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn drain_range_out_of_bounds() {
+        let mut list = list![1, 2, 3];
+        list.drain_range(1..4);
+    }
+
+    #[test]
+    #[should_panic(expected = "overflows usize")]
+    fn drain_range_inclusive_max() {
+        let mut list: List<_> = (0..5).collect();
+        list.drain_range(..=usize::MAX);
+    }
+
+    #[test]
+    fn swap() {
+        let mut list: List<_> = (0..5).collect();
+        list.swap(1, 3);
+        assert_eq!(list, list![0, 3, 2, 1, 4]);
+        list.swap(4, 0);
+        assert_eq!(list, list![4, 3, 2, 1, 0]);
+        list.swap(2, 2);
+        list.swap(0, 1);
+        assert_eq!(list, list![3, 4, 2, 1, 0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn swap_out_of_bounds() {
+        let mut list = list![1, 2];
+        list.swap(0, 2);
+    }
+
+    #[test]
+    fn swap_adjacent() {
+        let mut list = list![5, 1, 4, 2, 3];
+
+        // Bubble sort with node swaps only
+        loop {
+            let mut swapped = false;
+            let mut cursor = list.cursor_mut();
+            loop {
+                let current = *cursor.current().unwrap();
+                // The next one is current after the swap, and swapping it back puts it next
+                if !cursor.swap_adjacent() {
+                    break;
+                }
+                if *cursor.current().unwrap() < current {
+                    swapped = true;
+                } else {
+                    cursor.swap_adjacent();
+                }
+                cursor.move_next();
+            }
+            if !swapped {
+                break;
+            }
         }
+        assert_eq!(list, list![1, 2, 3, 4, 5]);
+        assert_eq!(list.len(), 5);
+
+        // Nothing to swap with at the end and on the ghost
+        let mut cursor = list.cursor_mut();
+        for _ in 0..4 {
+            cursor.move_next();
+        }
+        assert!(!cursor.swap_adjacent());
+        assert_eq!(cursor.current(), Some(&mut 5));
+        cursor.move_next();
+        assert!(!cursor.swap_adjacent());
+        assert_eq!(list, list![1, 2, 3, 4, 5]);
     }
 
     #[test]
-    fn iter() {
+    fn truncate() {
+        let mut list: List<_> = (0..5).collect();
+        list.truncate(10);
+        assert_eq!(list.len(), 5);
+
+        list.truncate(2);
+        assert_eq!(list, list![0, 1]);
+        assert_eq!(list.len(), 2);
+        list.push_back(2);
+        assert_eq!(list, list![0, 1, 2]);
+
+        list.truncate(0);
+        assert!(list.is_empty());
+
+        // Long tails are dropped in a loop
+        let mut list: List<_> = (0..1_000_000).collect();
+        list.truncate(1);
+        assert_eq!(list, list![0]);
+    }
+
+    #[test]
+    fn resize_with() {
+        let mut list = list![1, 2];
+        let mut next = 10;
+        list.resize_with(5, || {
+            next += 1;
+            next
+        });
+        assert_eq!(list, list![1, 2, 11, 12, 13]);
+        assert_eq!(list.len(), 5);
+
+        list.resize_with(3, || unreachable!());
+        assert_eq!(list, list![1, 2, 11]);
+
+        let mut list = List::new();
+        list.resize_with(3, Default::default);
+        assert_eq!(list, list![0, 0, 0]);
+    }
+
+    #[test]
+    fn front_and_back() {
+        let mut list = list![1, 2, 3];
+
+        // Through a shared reference
+        let shared = &list;
+        assert_eq!(shared.peek(), Some(&1));
+        assert_eq!(shared.front(), Some(&1));
+        assert_eq!(shared.back(), Some(&3));
+
+        *list.front_mut().unwrap() = 10;
+        *list.back_mut().unwrap() = 30;
+        assert_eq!(list, list![10, 2, 30]);
+
+        let empty: List<i32> = List::new();
+        assert_eq!(empty.front(), None);
+        assert_eq!(empty.back(), None);
+    }
+
+    #[test]
+    fn const_new() {
+        static EMPTY: List<i32> = List::new();
+        assert!(EMPTY.is_empty());
+        assert_eq!(EMPTY.iter().next(), None);
+
         let mut list = List::new();
         list.push(1);
+        assert_ne!(list, EMPTY);
+    }
+
+    #[test]
+    fn heap_size() {
+        let node = std::mem::size_of::<Node<u64, Global>>();
+
+        let mut list = List::with_pool_capacity(2);
+        assert_eq!(list.heap_size_in_bytes(), 0);
+        list.push(1u64);
         list.push(2);
         list.push(3);
+        assert_eq!(list.heap_size_in_bytes(), 3 * node);
+
+        // Popped nodes kept by the pool are still allocated
+        list.pop();
+        list.pop();
+        assert_eq!(list.heap_size_in_bytes(), 3 * node);
+        list.pop();
+        assert_eq!(list.heap_size_in_bytes(), 2 * node);
+    }
 
-        {
-            let mut iter = list.iter();
-            assert_eq!(iter.next(), Some(&3));
-            assert_eq!(iter.next(), Some(&2));
-            assert_eq!(iter.next(), Some(&1));
+    #[test]
+    fn split_first_and_last() {
+        assert!(List::<i32>::new().split_first().is_none());
+        assert!(List::<i32>::new().split_last().is_none());
+
+        let (first, rest) = list![1, 2, 3].split_first().unwrap();
+        assert_eq!(first, 1);
+        assert_eq!(rest, list![2, 3]);
+        assert_eq!(rest.len(), 2);
+
+        let (last, rest) = rest.split_last().unwrap();
+        assert_eq!(last, 3);
+        assert_eq!(rest, list![2]);
+
+        let (only, rest) = rest.split_first().unwrap();
+        assert_eq!(only, 2);
+        assert!(rest.is_empty());
+
+        // Recursion over the list, no nodes in sight
+        fn sum(list: List<i32>) -> i32 {
+            match list.split_first() {
+                Some((head, tail)) => head + sum(tail),
+                None => 0,
+            }
         }
+        assert_eq!(sum(list![1, 2, 3, 4]), 10);
+    }
 
-        // Collection is not moved, nor altered
-        assert_eq!(list.peek(), Some(&3));
+    #[test]
+    fn bounded() {
+        let mut list = List::bounded(2);
+        assert_eq!(list.max_len(), Some(2));
+        assert_eq!(list.try_push(1), Ok(()));
+        assert_eq!(list.try_push(2), Ok(()));
+        assert!(list.is_full());
+        assert_eq!(list.try_push(3), Err(3));
+        assert_eq!(list, list![2, 1]);
+
+        list.pop();
+        assert!(!list.is_full());
+        assert_eq!(list.try_push(3), Ok(()));
+        assert_eq!(list.clone().try_push(4), Err(4));
+
+        // Zero means nothing gets in
+        assert_eq!(List::bounded(0).try_push(1), Err(1));
+
+        // No limit by default
+        let mut list = List::new();
+        assert_eq!(list.max_len(), None);
+        for i in 0..100 {
+            assert_eq!(list.try_push(i), Ok(()));
+        }
+        assert!(!list.is_full());
+    }
+
+    #[test]
+    fn group_by() {
+        let groups = list![1, 1, 2, 3, 3, 3, 1].group_by(|a, b| a == b);
+        assert_eq!(
+            groups,
+            list![list![1, 1], list![2], list![3, 3, 3], list![1]]
+        );
+        assert!(groups.iter().map(List::len).eq([2, 1, 3, 1]));
+
+        // Relation between neighbours, not with the first one in the group
+        let groups = list![1, 2, 3, 5, 6, 9].group_by(|a, b| a + 1 == *b);
+        assert_eq!(groups, list![list![1, 2, 3], list![5, 6], list![9]]);
+
+        assert!(List::<i32>::new().group_by(|_, _| true).is_empty());
+        assert_eq!(list![1, 2].group_by(|_, _| true), list![list![1, 2]]);
+        assert_eq!(
+            list![1, 2].group_by(|_, _| false),
+            list![list![1], list![2]]
+        );
+
+        // Elements are moved along with their nodes, nothing is cloned
+        let elem = std::rc::Rc::new(());
+        let list: List<_> = (0..10).map(|i| (i / 3, elem.clone())).collect();
+        let groups = list.group_by(|a, b| a.0 == b.0);
+        assert_eq!(groups.len(), 4);
+        assert_eq!(std::rc::Rc::strong_count(&elem), 11);
+        drop(groups);
+        assert_eq!(std::rc::Rc::strong_count(&elem), 1);
+    }
+
+    #[test]
+    fn zip_with() {
+        let zipped = list![1, 2, 3].zip_with(list![10, 20, 30, 40], |a, b| a + b);
+        assert_eq!(zipped, list![11, 22, 33]);
+        assert_eq!(zipped.len(), 3);
+
+        let names = list![String::from("a"), String::from("b")];
+        let zipped = names.zip_with(list![1, 2, 3], |name, i| format!("{}{}", name, i));
+        assert_eq!(zipped, list![String::from("a1"), String::from("b2")]);
+
+        assert!(List::<i32>::new()
+            .zip_with(list![1], |a, b| a * b)
+            .is_empty());
+    }
+
+    #[test]
+    fn partition() {
+        let (even, odd) = (1..=10).collect::<List<_>>().partition(|x| x % 2 == 0);
+        assert_eq!(even, list![2, 4, 6, 8, 10]);
+        assert_eq!(odd, list![1, 3, 5, 7, 9]);
+        assert_eq!((even.len(), odd.len()), (5, 5));
+
+        let (all, none) = list![1, 2].partition(|_| true);
+        assert_eq!((all, none), (list![1, 2], list![]));
+
+        let (yes, no) = List::<i32>::new().partition(|_| true);
+        assert!(yes.is_empty() && no.is_empty());
+
+        // Nodes are moved, elements are never cloned
+        let elem = std::rc::Rc::new(());
+        let list: List<_> = (0..6).map(|i| (i, elem.clone())).collect();
+        let (small, big) = list.partition(|(i, _)| *i < 2);
+        assert_eq!((small.len(), big.len()), (2, 4));
+        assert_eq!(std::rc::Rc::strong_count(&elem), 7);
+    }
+
+    #[test]
+    fn unzip() {
+        let (xs, ys) = list![(1, 'a'), (2, 'b'), (3, 'c')].unzip();
+        assert_eq!(xs, list![1, 2, 3]);
+        assert_eq!(ys, list!['a', 'b', 'c']);
+        assert_eq!((xs.len(), ys.len()), (3, 3));
+
+        // Inverse of zip_with
+        assert_eq!(xs.zip_with(ys, |x, y| (x, y)).unzip().0, list![1, 2, 3]);
+
+        let (xs, ys) = List::<(i32, i32)>::new().unzip();
+        assert!(xs.is_empty() && ys.is_empty());
+
+        let (xs, _) = (0..100_000).map(|i| (i, i)).collect::<List<_>>().unzip();
+        assert_eq!(xs.len(), 100_000);
+    }
+
+    #[test]
+    fn try_map() {
+        let parsed = list!["1", "2", "3"].try_map(|s| s.parse::<i32>());
+        assert_eq!(parsed, Ok(list![1, 2, 3]));
+        assert_eq!(parsed.unwrap().len(), 3);
+
+        let failed = list!["1", "x", "3", "y"].try_map(|s| s.parse::<i32>().map_err(|_| s));
+        assert_eq!(failed, Err("x"));
+
+        // Stops at the first error, everything is dropped
+        let elem = std::rc::Rc::new(());
+        let mut calls = 0;
+        let list: List<_> = (0..10).map(|i| (i, elem.clone())).collect();
+        let result = list.try_map(|(i, rc)| {
+            calls += 1;
+            if i < 3 {
+                Ok(rc)
+            } else {
+                Err(i)
+            }
+        });
+        assert_eq!(result.unwrap_err(), 3);
+        assert_eq!(calls, 4);
+        assert_eq!(std::rc::Rc::strong_count(&elem), 1);
+    }
+
+    #[test]
+    fn map_in_place() {
+        let mut list = list![String::from("a"), String::from("b")];
+        let first = list.peek().unwrap() as *const String;
+
+        list.map_in_place(|s| s + "!");
+        assert_eq!(list, list![String::from("a!"), String::from("b!")]);
+        assert_eq!(list.len(), 2);
+        // Same node as before
+        assert_eq!(list.peek().unwrap() as *const String, first);
+
+        let mut empty = List::<i32>::new();
+        empty.map_in_place(|x| x + 1);
+        assert!(empty.is_empty());
+
+        let mut list: List<_> = (0..100_000).collect();
+        list.map_in_place(|x| x * 2);
+        assert!(list.iter().copied().eq((0..100_000).map(|x| x * 2)));
+    }
+
+    #[test]
+    fn map_in_place_panic() {
+        let elem = std::rc::Rc::new(());
+        let mut list: List<_> = (0..5).map(|i| (i, elem.clone())).collect();
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            list.map_in_place(|(i, rc)| if i < 2 { (i * 10, rc) } else { panic!("boom") });
+        }));
+        assert!(result.is_err());
+
+        // Mapped ones are left, the rest is gone, nothing leaks
+        assert!(list.iter().map(|(i, _)| *i).eq([0, 10]));
+        assert_eq!(list.len(), 2);
+        assert_eq!(std::rc::Rc::strong_count(&elem), 3);
+    }
+
+    #[test]
+    fn nth_from_back() {
+        let mut list = list![1, 2, 3, 4];
+        assert_eq!(list.nth_from_back(0), Some(&4));
+        assert_eq!(list.nth_from_back(1), Some(&3));
+        assert_eq!(list.nth_from_back(3), Some(&1));
+        assert_eq!(list.nth_from_back(4), None);
+        assert_eq!(list.nth_from_back(usize::MAX), None);
+
+        *list.nth_from_back_mut(1).unwrap() = 30;
+        assert_eq!(list, list![1, 2, 30, 4]);
+        assert_eq!(list.nth_from_back_mut(4), None);
+        assert_eq!(list.nth_from_back_mut(usize::MAX), None);
+
+        let mut empty = List::<i32>::new();
+        assert_eq!(empty.nth_from_back(0), None);
+        assert_eq!(empty.nth_from_back_mut(0), None);
+
+        let list: List<_> = (0..100).collect();
+        for n in 0..100 {
+            assert_eq!(list.nth_from_back(n), Some(&(99 - n)));
+        }
+    }
+
+    #[test]
+    fn middle_and_split_middle() {
+        assert_eq!(List::<i32>::new().middle(), None);
+        assert_eq!(list![1].middle(), Some(&1));
+        assert_eq!(list![1, 2].middle(), Some(&2));
+        assert_eq!(list![1, 2, 3].middle(), Some(&2));
+        assert_eq!(list![1, 2, 3, 4].middle(), Some(&3));
+
+        for len in 0..20 {
+            let list: List<_> = (0..len).collect();
+            assert_eq!(list.middle(), list.get(len / 2));
+        }
+
+        let mut list = list![1, 2, 3, 4, 5];
+        let back = list.split_middle();
+        assert_eq!(list, list![1, 2]);
+        assert_eq!(back, list![3, 4, 5]);
+        assert_eq!((list.len(), back.len()), (2, 3));
+
+        let mut list = list![1, 2, 3, 4];
+        assert_eq!(list.split_middle(), list![3, 4]);
+        assert_eq!(list, list![1, 2]);
+
+        let mut empty = List::<i32>::new();
+        assert!(empty.split_middle().is_empty());
+    }
+
+    #[test]
+    fn rle() {
+        let list = list!['a', 'a', 'a', 'b', 'a', 'c', 'c'];
+        let runs = list.rle_encode();
+        assert_eq!(runs, list![('a', 3), ('b', 1), ('a', 1), ('c', 2)]);
+        assert_eq!(runs.len(), 4);
+        assert_eq!(runs.rle_decode(), list);
+        assert_eq!(runs.rle_decode().len(), 7);
+
+        assert!(List::<i32>::new().rle_encode().is_empty());
+        assert_eq!(list![(1, 0), (2, 2), (3, 0)].rle_decode(), list![2, 2]);
+
+        // Sparse data
+        let sparse: List<_> = (0..1000).map(|i| if i == 500 { 1 } else { 0 }).collect();
+        let runs = sparse.rle_encode();
+        assert_eq!(runs, list![(0, 500), (1, 1), (0, 499)]);
+        assert_eq!(runs.rle_decode(), sparse);
+    }
+
+    #[test]
+    fn extend_and_collect_copied() {
+        let elems = [1, 2, 3];
+        let mut list: List<i32> = elems.iter().collect();
+        assert_eq!(list, list![1, 2, 3]);
+
+        list.extend(&[4, 5]);
+        list.extend(&vec![6]);
+        list.extend(&list![7, 8]);
+        assert_eq!(list, list![1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(list.len(), 8);
+
+        // Copy of another list, no clone needed
+        let copy: List<i32> = list.iter().collect();
+        assert_eq!(copy, list);
     }
 }
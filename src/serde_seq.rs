@@ -0,0 +1,102 @@
+// Serde support shared by the lists, turned on with the "serde" feature.
+//
+// Every list goes out as a plain sequence, front first: [1, 2, 3], so the format
+// does not leak how nodes are linked, and lists of different kinds can read each other's data.
+//
+// Deserialize can't be derived either - derived impls follow the type, node after node,
+// which is a recursion as deep as the list (same problem as with Drop). Instead elements
+// come out of the sequence one by one, and each list appends them at its end, with
+// a cursor pointing at the last link.
+use core::fmt;
+use core::marker::PhantomData;
+
+use serde::de::{Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+
+pub(crate) fn serialize_seq<S, I>(serializer: S, len: usize, elems: I) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    I: IntoIterator,
+    I::Item: Serialize,
+{
+    let mut seq = serializer.serialize_seq(Some(len))?;
+    for elem in elems {
+        seq.serialize_element(&elem)?;
+    }
+    seq.end()
+}
+
+// build gets the elements as an iterator, and returns the list made of them.
+// If the data is broken, the iterator stops early, and the error is returned
+// instead of the (partial) list.
+pub(crate) fn deserialize_seq<'de, D, T, L, F>(
+    deserializer: D,
+    expecting: &'static str,
+    build: F,
+) -> Result<L, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+    F: FnOnce(&mut dyn Iterator<Item = T>) -> L,
+{
+    deserializer.deserialize_seq(SeqVisitor {
+        expecting,
+        build,
+        _marker: PhantomData,
+    })
+}
+
+struct SeqVisitor<T, F> {
+    expecting: &'static str,
+    build: F,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<'de, T, L, F> Visitor<'de> for SeqVisitor<T, F>
+where
+    T: Deserialize<'de>,
+    F: FnOnce(&mut dyn Iterator<Item = T>) -> L,
+{
+    type Value = L;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.expecting)
+    }
+
+    fn visit_seq<S: SeqAccess<'de>>(self, seq: S) -> Result<L, S::Error> {
+        let mut elems = Elems {
+            seq,
+            error: None,
+            _marker: PhantomData,
+        };
+
+        let list = (self.build)(&mut elems);
+
+        match elems.error {
+            Some(error) => Err(error),
+            None => Ok(list),
+        }
+    }
+}
+
+// SeqAccess as an Iterator, the first error ends it
+struct Elems<S, E, T> {
+    seq: S,
+    error: Option<E>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<'de, S: SeqAccess<'de>, T: Deserialize<'de>> Iterator for Elems<S, S::Error, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.error.is_some() {
+            return None;
+        }
+
+        self.seq.next_element().unwrap_or_else(|error| {
+            self.error = Some(error);
+            None
+        })
+    }
+}
@@ -0,0 +1,580 @@
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+use core::ops::{Bound, RangeBounds, RangeFull};
+use core::ptr::null_mut;
+
+// Skip list - sorted linked list, with "express lanes" on top of it:
+//
+// level 2: head ---------------------> 5 ---------------------> None
+// level 1: head ------> 2 -----------> 5 ------> 7 -----------> None
+// level 0: head -> 1 -> 2 -> 3 -> 4 -> 5 -> 6 -> 7 -> 8 -> 9 -> None
+//
+// Every node is on level 0, and on each next level with probability p (coin toss on insert).
+// Search starts at the top level, goes right while the next element is smaller than
+// the one we look for, then goes one level down. On average that's O(log n) steps,
+// like in a balanced tree, but without any rebalancing.
+//
+// Elements are unique, inserting an element which is already there does nothing (like in BTreeSet).
+pub struct SkipList<T> {
+    // First node on every level, head.len() is the max level
+    head: Vec<Link<T>>,
+    // Number of levels in use, levels above are empty
+    level: usize,
+    len: usize,
+    probability: f64,
+    // State of the xorshift generator, used to pick levels of new nodes
+    rng: u64,
+    _marker: PhantomData<Box<Node<T>>>,
+}
+
+// Null is the end of the level
+type Link<T> = *mut Node<T>;
+
+struct Node<T> {
+    elem: T,
+    // One link per level the node is on
+    next: Vec<Link<T>>,
+}
+
+pub struct Range<'a, T, R> {
+    next: Link<T>,
+    range: R,
+    _marker: PhantomData<&'a T>,
+}
+
+const DEFAULT_MAX_LEVEL: usize = 16;
+const DEFAULT_PROBABILITY: f64 = 0.5;
+
+// Nodes are owned by the list, same as with Box
+unsafe impl<T: Send> Send for SkipList<T> {}
+unsafe impl<T: Sync> Sync for SkipList<T> {}
+
+impl<T: Ord> Default for SkipList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Any random seed will do, take the one std uses for HashMaps
+#[cfg(feature = "std")]
+fn seed() -> u64 {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+
+    RandomState::new().build_hasher().finish()
+}
+
+// No randomness without std, levels are still spread right, just the same on every run
+#[cfg(not(feature = "std"))]
+fn seed() -> u64 {
+    0x2545_f491_4f6c_dd1d
+}
+
+impl<T: Ord> SkipList<T> {
+    pub fn new() -> Self {
+        Self::with_config(DEFAULT_MAX_LEVEL, DEFAULT_PROBABILITY)
+    }
+
+    // max_level of 16 with probability 1/2 is fine for ~2^16 elements, for more
+    // use more levels (or lower probability, to save memory on links)
+    pub fn with_config(max_level: usize, probability: f64) -> Self {
+        assert!(max_level > 0, "max_level has to be at least 1");
+        assert!(
+            probability > 0.0 && probability < 1.0,
+            "probability has to be in (0, 1)"
+        );
+
+        SkipList {
+            head: vec![null_mut(); max_level],
+            level: 0,
+            len: 0,
+            probability,
+            // xorshift gets stuck on 0
+            rng: seed() | 1,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn contains(&self, elem: &T) -> bool {
+        let node = self.walk(|e| e < elem, |_, _| {});
+
+        !node.is_null() && unsafe { &(*node).elem } == elem
+    }
+
+    // Returns false if the element is already there
+    pub fn insert(&mut self, elem: T) -> bool {
+        match self.entry(&elem) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(entry) => {
+                entry.insert(elem);
+                true
+            }
+        }
+    }
+
+    pub fn remove(&mut self, elem: &T) -> Option<T> {
+        match self.entry(elem) {
+            Entry::Occupied(entry) => Some(entry.remove()),
+            Entry::Vacant(_) => None,
+        }
+    }
+
+    // Finds the place of key, the entry then reads, inserts or removes there without
+    // walking again, like BTreeMap::entry. Elements can be looked up by a part of them
+    // (Borrow, as in BTreeSet::get), so
+    //
+    // list.entry("bob").or_insert_with(|| User::new("bob"))
+    //
+    // makes the User only if there is no "bob" yet.
+    pub fn entry<Q>(&mut self, key: &Q) -> Entry<'_, T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut update = vec![null_mut(); self.head.len()];
+        let node = self.walk(|e| e.borrow() < key, |level, node| update[level] = node);
+
+        if !node.is_null() && unsafe { (*node).elem.borrow() } == key {
+            Entry::Occupied(OccupiedEntry {
+                list: self,
+                update,
+                node,
+            })
+        } else {
+            Entry::Vacant(VacantEntry { list: self, update })
+        }
+    }
+
+    // update is what walk visited, the last node before elem on every level
+    fn link(&mut self, update: &[Link<T>], elem: T) -> Link<T> {
+        let level = self.random_level();
+        // Levels above self.level are empty, update already points to the head there
+        self.level = self.level.max(level);
+
+        let node = Box::into_raw(Box::new(Node {
+            elem,
+            next: vec![null_mut(); level],
+        }));
+
+        for (level, &prev) in update.iter().enumerate().take(level) {
+            unsafe {
+                (&mut (*node).next)[level] = self.next(prev, level);
+            }
+            self.set_next(prev, level, node);
+        }
+
+        self.len += 1;
+        node
+    }
+
+    fn unlink(&mut self, update: &[Link<T>], node: Link<T>) -> T {
+        let node = unsafe { Box::from_raw(node) };
+
+        // On every level the node is on, the last node before it, points to it
+        for (level, &next) in node.next.iter().enumerate() {
+            self.set_next(update[level], level, next);
+        }
+
+        while self.level > 0 && self.head[self.level - 1].is_null() {
+            self.level -= 1;
+        }
+
+        self.len -= 1;
+        node.elem
+    }
+
+    // Elements from the range, in order
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> Range<'_, T, R> {
+        let next = match range.start_bound() {
+            Bound::Included(start) => self.walk(|e| e < start, |_, _| {}),
+            Bound::Excluded(start) => self.walk(|e| e <= start, |_, _| {}),
+            Bound::Unbounded => self.head[0],
+        };
+
+        Range {
+            next,
+            range,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn iter(&self) -> Range<'_, T, RangeFull> {
+        self.range(..)
+    }
+
+    // Goes through the list as long as before(elem) is true. On every level calls
+    // visit(level, last node visited on that level) - null if it's the head.
+    // Returns the first node on level 0, for which before is false.
+    fn walk(
+        &self,
+        mut before: impl FnMut(&T) -> bool,
+        mut visit: impl FnMut(usize, Link<T>),
+    ) -> Link<T> {
+        let mut node = null_mut();
+
+        for level in (0..self.level).rev() {
+            loop {
+                let next = self.next(node, level);
+
+                if !next.is_null() && before(unsafe { &(*next).elem }) {
+                    node = next;
+                } else {
+                    break;
+                }
+            }
+
+            visit(level, node);
+        }
+
+        self.next(node, 0)
+    }
+
+    fn random_level(&mut self) -> usize {
+        let mut level = 1;
+
+        while level < self.head.len() && self.random() < self.probability {
+            level += 1;
+        }
+
+        level
+    }
+
+    // Uniform in [0, 1)
+    fn random(&mut self) -> f64 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+
+        (self.rng >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+pub enum Entry<'a, T> {
+    Occupied(OccupiedEntry<'a, T>),
+    Vacant(VacantEntry<'a, T>),
+}
+
+pub struct OccupiedEntry<'a, T> {
+    list: &'a mut SkipList<T>,
+    update: Vec<Link<T>>,
+    node: Link<T>,
+}
+
+pub struct VacantEntry<'a, T> {
+    list: &'a mut SkipList<T>,
+    update: Vec<Link<T>>,
+}
+
+// Elements are only given out as &T - changing one could move it out of order
+impl<'a, T: Ord> Entry<'a, T> {
+    pub fn or_insert(self, elem: T) -> &'a T {
+        self.or_insert_with(|| elem)
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> T>(self, f: F) -> &'a T {
+        match self {
+            Entry::Occupied(entry) => entry.into_ref(),
+            Entry::Vacant(entry) => entry.insert(f()),
+        }
+    }
+}
+
+impl<'a, T: Ord> OccupiedEntry<'a, T> {
+    pub fn get(&self) -> &T {
+        unsafe { &(*self.node).elem }
+    }
+
+    pub fn into_ref(self) -> &'a T {
+        unsafe { &(*self.node).elem }
+    }
+
+    pub fn remove(self) -> T {
+        self.list.unlink(&self.update, self.node)
+    }
+}
+
+impl<'a, T: Ord> VacantEntry<'a, T> {
+    // elem has to have the key the entry was made for, or at least go in the same place.
+    // Panics if it doesn't, the list would be out of order otherwise.
+    pub fn insert(self, elem: T) -> &'a T {
+        let prev = self.update.first().copied().unwrap_or(null_mut());
+        let next = self.list.next(prev, 0);
+        assert!(
+            (prev.is_null() || unsafe { &(*prev).elem } < &elem)
+                && (next.is_null() || &elem < unsafe { &(*next).elem }),
+            "element doesn't belong in this entry"
+        );
+
+        let node = self.list.link(&self.update, elem);
+        unsafe { &(*node).elem }
+    }
+}
+
+impl<T> SkipList<T> {
+    // Null node means the head
+    fn next(&self, node: Link<T>, level: usize) -> Link<T> {
+        if node.is_null() {
+            self.head[level]
+        } else {
+            unsafe { (&(*node).next)[level] }
+        }
+    }
+
+    fn set_next(&mut self, node: Link<T>, level: usize, next: Link<T>) {
+        if node.is_null() {
+            self.head[level] = next;
+        } else {
+            unsafe {
+                (&mut (*node).next)[level] = next;
+            }
+        }
+    }
+}
+
+impl<T> Drop for SkipList<T> {
+    fn drop(&mut self) {
+        // Level 0 has all the nodes
+        let mut node = self.head[0];
+
+        while !node.is_null() {
+            let boxed = unsafe { Box::from_raw(node) };
+            node = boxed.next[0];
+        }
+    }
+}
+
+impl<'a, T: Ord, R: RangeBounds<T>> Iterator for Range<'a, T, R> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next.is_null() {
+            return None;
+        }
+
+        let node = unsafe { &*self.next };
+
+        let past_end = match self.range.end_bound() {
+            Bound::Included(end) => &node.elem > end,
+            Bound::Excluded(end) => &node.elem >= end,
+            Bound::Unbounded => false,
+        };
+
+        if past_end {
+            self.next = null_mut();
+            return None;
+        }
+
+        self.next = node.next[0];
+        Some(&node.elem)
+    }
+}
+
+// next is nulled at the end of the range, and stays null
+impl<'a, T: Ord, R: RangeBounds<T>> FusedIterator for Range<'a, T, R> {}
+
+#[cfg(test)]
+mod test {
+    use super::SkipList;
+
+    // Numbers 0..count, shuffled
+    fn shuffled(count: u64) -> Vec<u64> {
+        // Multiplying by a number coprime with count is a permutation of 0..count
+        (0..count).map(|i| i * 7919 % count).collect()
+    }
+
+    #[test]
+    fn basics() {
+        let mut list = SkipList::new();
+        assert!(list.is_empty());
+        assert!(!list.contains(&1));
+        assert_eq!(list.remove(&1), None);
+
+        assert!(list.insert(3));
+        assert!(list.insert(1));
+        assert!(list.insert(2));
+        assert!(!list.insert(2));
+        assert_eq!(list.len(), 3);
+
+        assert!(list.contains(&1));
+        assert!(list.contains(&3));
+        assert!(!list.contains(&4));
+
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &2, &3]);
+
+        assert_eq!(list.remove(&2), Some(2));
+        assert_eq!(list.remove(&2), None);
+        assert!(!list.contains(&2));
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &3]);
+
+        assert_eq!(list.remove(&1), Some(1));
+        assert_eq!(list.remove(&3), Some(3));
+        assert!(list.is_empty());
+        assert_eq!(list.iter().next(), None);
+    }
+
+    #[test]
+    fn range() {
+        let mut list = SkipList::new();
+        for elem in shuffled(100) {
+            list.insert(elem);
+        }
+
+        assert_eq!(
+            list.range(10..15).copied().collect::<Vec<_>>(),
+            vec![10, 11, 12, 13, 14]
+        );
+        assert_eq!(
+            list.range(10..=12).copied().collect::<Vec<_>>(),
+            vec![10, 11, 12]
+        );
+        assert_eq!(list.range(..3).copied().collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert_eq!(
+            list.range(97..).copied().collect::<Vec<_>>(),
+            vec![97, 98, 99]
+        );
+        assert_eq!(list.range(200..).next(), None);
+        assert_eq!(list.range(5..5).next(), None);
+
+        // Bounds don't have to be in the list
+        list.remove(&20);
+        list.remove(&23);
+        assert_eq!(
+            list.range(20..=23).copied().collect::<Vec<_>>(),
+            vec![21, 22]
+        );
+    }
+
+    #[test]
+    fn many_elements() {
+        let mut list = SkipList::new();
+
+        for elem in shuffled(10_000) {
+            assert!(list.insert(elem));
+        }
+        assert_eq!(list.len(), 10_000);
+        assert!(list.iter().copied().eq(0..10_000));
+
+        // Remove the odd ones
+        for elem in shuffled(10_000).into_iter().filter(|e| e % 2 == 1) {
+            assert_eq!(list.remove(&elem), Some(elem));
+        }
+
+        assert_eq!(list.len(), 5_000);
+        assert!(list.iter().copied().eq((0..10_000).step_by(2)));
+        assert!(list.contains(&5000));
+        assert!(!list.contains(&5001));
+    }
+
+    #[test]
+    fn config() {
+        // Single level is just a sorted linked list
+        let mut list = SkipList::with_config(1, 0.5);
+        for elem in shuffled(100) {
+            list.insert(elem);
+        }
+        assert!(list.iter().copied().eq(0..100));
+
+        let mut list = SkipList::with_config(32, 0.25);
+        for elem in shuffled(1000) {
+            list.insert(elem);
+        }
+        assert!(list.iter().copied().eq(0..1000));
+    }
+
+    #[test]
+    #[should_panic]
+    fn bad_probability() {
+        SkipList::<i32>::with_config(16, 1.0);
+    }
+
+    #[test]
+    fn entry() {
+        use super::Entry;
+        use std::borrow::Borrow;
+        use std::cmp::Ordering;
+
+        // Ordered by name only, looked up by &str
+        #[derive(Debug)]
+        struct User {
+            name: String,
+            visits: u32,
+        }
+        impl PartialEq for User {
+            fn eq(&self, other: &Self) -> bool {
+                self.name == other.name
+            }
+        }
+        impl Eq for User {}
+        impl PartialOrd for User {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+        impl Ord for User {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.name.cmp(&other.name)
+            }
+        }
+        impl Borrow<str> for User {
+            fn borrow(&self) -> &str {
+                &self.name
+            }
+        }
+        let user = |name: &str, visits| User {
+            name: name.to_string(),
+            visits,
+        };
+
+        let mut list = SkipList::new();
+        let mut made = 0;
+        for name in ["bob", "alice", "bob", "carol", "alice", "bob"] {
+            list.entry(name).or_insert_with(|| {
+                made += 1;
+                user(name, made)
+            });
+        }
+        assert_eq!(made, 3);
+        assert!(list
+            .iter()
+            .map(|user| (user.name.as_str(), user.visits))
+            .eq(vec![("alice", 2), ("bob", 1), ("carol", 3)]));
+
+        match list.entry("bob") {
+            Entry::Occupied(entry) => {
+                assert_eq!(entry.get().visits, 1);
+                assert_eq!(entry.remove().name, "bob");
+            }
+            Entry::Vacant(_) => panic!("bob is there"),
+        }
+        assert_eq!(list.len(), 2);
+        assert_eq!(list.entry("bob").or_insert(user("bob", 7)).visits, 7);
+        assert!(list
+            .iter()
+            .map(|user| user.name.as_str())
+            .eq(vec!["alice", "bob", "carol"]));
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't belong")]
+    fn entry_wrong_element() {
+        let mut list = SkipList::new();
+        list.insert(1);
+        list.insert(5);
+
+        if let super::Entry::Vacant(entry) = list.entry(&3) {
+            entry.insert(7);
+        }
+    }
+}
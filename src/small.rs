@@ -0,0 +1,338 @@
+use core::fmt;
+use core::iter::{FusedIterator, Rev};
+use core::slice;
+
+use crate::second;
+use crate::traits::Stack;
+
+// Stack-only small list: second::List's stack with the small-list optimization, same idea
+// as in SmallVec. The first K elements are kept inline, in the List struct itself, and only
+// when more come, elements move to heap allocated nodes. A list which stays small never
+// allocates.
+//
+// Only the stack part of second's API is here: push, pop, peek, peek_mut, iter, iter_mut,
+// len, plus the Stack trait. So it can replace second::List only where the code needs
+// no more than that (or just the Stack trait) - push_back, cursors, sort, drain, retain,
+// FromIterator and the rest are not there.
+//
+// Once spilled, the list stays on the heap, even when it shrinks back - same as SmallVec.
+// Moving elements back and forth every time len goes around K would cost more than it saves.
+pub struct List<T, const K: usize = 4> {
+    repr: Repr<T, K>,
+}
+
+enum Repr<T, const K: usize> {
+    // Bottom of the stack first, so push and pop work at the end, elems[len - 1] is the top.
+    // 0..len are Some, the rest is None.
+    Inline { elems: [Option<T>; K], len: usize },
+    Heap(second::List<T>),
+}
+
+impl<T, const K: usize> Default for List<T, K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const K: usize> List<T, K> {
+    pub const fn new() -> Self {
+        List {
+            repr: Repr::Inline {
+                elems: [const { None }; K],
+                len: 0,
+            },
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.repr {
+            Repr::Inline { len, .. } => *len,
+            Repr::Heap(list) => list.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Whether the elements moved to the heap
+    pub fn spilled(&self) -> bool {
+        matches!(self.repr, Repr::Heap(_))
+    }
+
+    // Inline elements are in the List itself, 0 until it spills
+    pub fn heap_size_in_bytes(&self) -> usize {
+        match &self.repr {
+            Repr::Inline { .. } => 0,
+            Repr::Heap(list) => list.heap_size_in_bytes(),
+        }
+    }
+
+    pub fn push(&mut self, elem: T) {
+        match &mut self.repr {
+            Repr::Inline { elems, len } if *len < K => {
+                elems[*len] = Some(elem);
+                *len += 1;
+            }
+            Repr::Inline { elems, .. } => {
+                // No more room, all of them go to nodes. Bottom is pushed first,
+                // so the top ends up at the head, where second::List keeps it.
+                let mut list = second::List::new();
+                for slot in elems.iter_mut() {
+                    list.push(slot.take().unwrap());
+                }
+                list.push(elem);
+
+                self.repr = Repr::Heap(list);
+            }
+            Repr::Heap(list) => list.push(elem),
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        match &mut self.repr {
+            Repr::Inline { elems, len } => {
+                *len = len.checked_sub(1)?;
+                elems[*len].take()
+            }
+            Repr::Heap(list) => list.pop(),
+        }
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.iter().next()
+    }
+
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self.iter_mut().next()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter(match &self.repr {
+            Repr::Inline { elems, len } => IterRepr::Inline(elems[..*len].iter().rev()),
+            Repr::Heap(list) => IterRepr::Heap(list.iter()),
+        })
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut(match &mut self.repr {
+            Repr::Inline { elems, len } => IterMutRepr::Inline(elems[..*len].iter_mut().rev()),
+            Repr::Heap(list) => IterMutRepr::Heap(list.iter_mut()),
+        })
+    }
+}
+
+impl<T, const K: usize> Stack<T> for List<T, K> {
+    fn push(&mut self, elem: T) {
+        self.push(elem);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.pop()
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.peek()
+    }
+}
+
+impl<T: fmt::Debug, const K: usize> fmt::Debug for List<T, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+pub struct IntoIter<T, const K: usize>(List<T, K>);
+
+impl<T, const K: usize> IntoIterator for List<T, K> {
+    type Item = T;
+    type IntoIter = IntoIter<T, K>;
+
+    fn into_iter(self) -> IntoIter<T, K> {
+        IntoIter(self)
+    }
+}
+
+impl<T, const K: usize> Iterator for IntoIter<T, K> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len(), Some(self.0.len()))
+    }
+}
+
+impl<T, const K: usize> ExactSizeIterator for IntoIter<T, K> {}
+
+impl<T, const K: usize> FusedIterator for IntoIter<T, K> {}
+
+// Either of the two iterators underneath, inline elements are walked backwards,
+// from the top of the stack
+pub struct Iter<'a, T>(IterRepr<'a, T>);
+
+enum IterRepr<'a, T> {
+    Inline(Rev<slice::Iter<'a, Option<T>>>),
+    Heap(second::Iter<'a, T>),
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            // Only the Some part of the array is walked
+            IterRepr::Inline(iter) => iter.next().map(|elem| elem.as_ref().unwrap()),
+            IterRepr::Heap(iter) => iter.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.0 {
+            IterRepr::Inline(iter) => iter.size_hint(),
+            IterRepr::Heap(iter) => iter.size_hint(),
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> FusedIterator for Iter<'a, T> {}
+
+pub struct IterMut<'a, T>(IterMutRepr<'a, T>);
+
+enum IterMutRepr<'a, T> {
+    Inline(Rev<slice::IterMut<'a, Option<T>>>),
+    Heap(second::IterMut<'a, T>),
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            IterMutRepr::Inline(iter) => iter.next().map(|elem| elem.as_mut().unwrap()),
+            IterMutRepr::Heap(iter) => iter.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.0 {
+            IterMutRepr::Inline(iter) => iter.size_hint(),
+            IterMutRepr::Heap(iter) => iter.size_hint(),
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+
+impl<'a, T> FusedIterator for IterMut<'a, T> {}
+
+impl<'a, T, const K: usize> IntoIterator for &'a List<T, K> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T, const K: usize> IntoIterator for &'a mut List<T, K> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::List;
+    use std::rc::Rc;
+
+    #[test]
+    fn basics() {
+        let mut list: List<i32, 2> = List::new();
+        assert_eq!(list.pop(), None);
+        assert_eq!(list.peek(), None);
+
+        list.push(1);
+        list.push(2);
+        assert!(!list.spilled());
+        assert_eq!(list.peek(), Some(&2));
+
+        // Third one does not fit
+        list.push(3);
+        assert!(list.spilled());
+        assert_eq!(list.len(), 3);
+        assert!(list.iter().eq(&[3, 2, 1]));
+
+        assert_eq!(list.pop(), Some(3));
+        assert_eq!(list.pop(), Some(2));
+        assert_eq!(list.pop(), Some(1));
+        assert_eq!(list.pop(), None);
+        // Stays on the heap
+        assert!(list.spilled());
+    }
+
+    #[test]
+    fn inline() {
+        let mut list: List<i32> = List::new();
+        for i in 0..4 {
+            list.push(i);
+        }
+        assert!(!list.spilled());
+
+        for elem in &mut list {
+            *elem *= 10;
+        }
+        *list.peek_mut().unwrap() += 1;
+
+        assert_eq!(format!("{:?}", list), "[31, 20, 10, 0]");
+        assert_eq!(list.iter().len(), 4);
+        assert!(list.into_iter().eq(vec![31, 20, 10, 0]));
+    }
+
+    #[test]
+    fn zero_inline() {
+        let mut list: List<i32, 0> = List::new();
+        list.push(1);
+        assert!(list.spilled());
+        assert_eq!(list.pop(), Some(1));
+    }
+
+    #[test]
+    fn drops_elements() {
+        let elem = Rc::new(());
+
+        {
+            let mut list: List<_, 3> = List::new();
+            list.push(elem.clone());
+            list.push(elem.clone());
+            list.pop();
+        }
+        {
+            let mut list: List<_, 3> = List::new();
+            for _ in 0..5 {
+                list.push(elem.clone());
+            }
+        }
+
+        assert_eq!(Rc::strong_count(&elem), 1);
+    }
+
+    #[test]
+    fn heap_size() {
+        let mut list: List<u64, 2> = List::new();
+        list.push(1);
+        list.push(2);
+        assert_eq!(list.heap_size_in_bytes(), 0);
+
+        list.push(3);
+        assert!(list.spilled());
+        assert_eq!(list.heap_size_in_bytes(), 3 * 16);
+    }
+}
@@ -0,0 +1,244 @@
+use core::cmp::Ordering;
+use core::fmt;
+use core::iter::FromIterator;
+
+use crate::second::{self, List};
+
+// second::List that is always sorted, smallest first. Every operation keeps the order,
+// and uses it - walks stop as soon as they pass the place the element would be at.
+//
+// insert is O(n) (walks to the right place), so for many elements at once
+// from_unsorted / collect is better: one merge sort, O(n log n).
+pub struct SortedList<T: Ord> {
+    list: List<T>,
+}
+
+impl<T: Ord> Default for SortedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> SortedList<T> {
+    pub const fn new() -> Self {
+        SortedList { list: List::new() }
+    }
+
+    pub fn from_unsorted<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list: List<T> = iter.into_iter().collect();
+        list.sort();
+        SortedList { list }
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    // Goes after the elements equal to it, so equal elements keep the insertion order
+    pub fn insert(&mut self, elem: T) {
+        let mut cursor = self.list.cursor_mut();
+        while cursor.current().is_some_and(|current| *current <= elem) {
+            cursor.move_next();
+        }
+
+        cursor.insert_before(elem);
+    }
+
+    pub fn contains(&self, x: &T) -> bool {
+        for elem in self.list.iter() {
+            match elem.cmp(x) {
+                Ordering::Less => continue,
+                Ordering::Equal => return true,
+                // Everything further is even bigger
+                Ordering::Greater => return false,
+            }
+        }
+
+        false
+    }
+
+    // Removes the first element equal to x
+    pub fn remove(&mut self, x: &T) -> bool {
+        let mut cursor = self.list.cursor_mut();
+        while cursor.current().is_some_and(|current| *current < *x) {
+            cursor.move_next();
+        }
+
+        if cursor.current().is_some_and(|current| *current == *x) {
+            cursor.remove_current();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.list.peek()
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.list.peek_back()
+    }
+
+    pub fn pop_first(&mut self) -> Option<T> {
+        self.list.pop()
+    }
+
+    // O(n + m), nodes of other are relinked, nothing is allocated
+    pub fn merge(&mut self, other: SortedList<T>) {
+        self.list.merge(other.list);
+    }
+
+    pub fn iter(&self) -> second::Iter<'_, T> {
+        self.list.iter()
+    }
+
+    pub fn into_list(self) -> List<T> {
+        self.list
+    }
+}
+
+impl<T: Ord> FromIterator<T> for SortedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_unsorted(iter)
+    }
+}
+
+impl<T: Ord> Extend<T> for SortedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.merge(Self::from_unsorted(iter));
+    }
+}
+
+// From references to Copy elements, same as second::List
+impl<'a, T: Ord + Copy + 'a> FromIterator<&'a T> for SortedList<T> {
+    fn from_iter<I: IntoIterator<Item = &'a T>>(iter: I) -> Self {
+        iter.into_iter().copied().collect()
+    }
+}
+
+impl<'a, T: Ord + Copy + 'a> Extend<&'a T> for SortedList<T> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<T: Ord> IntoIterator for SortedList<T> {
+    type Item = T;
+    type IntoIter = second::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.list.into_iter()
+    }
+}
+
+impl<'a, T: Ord> IntoIterator for &'a SortedList<T> {
+    type Item = &'a T;
+    type IntoIter = second::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: Ord + fmt::Debug> fmt::Debug for SortedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.list.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SortedList;
+    use core::cmp::Ordering;
+
+    #[test]
+    fn basics() {
+        let mut list = SortedList::new();
+        assert_eq!(list.first(), None);
+        assert!(!list.contains(&1));
+
+        for elem in [5, 1, 4, 1, 3] {
+            list.insert(elem);
+        }
+        assert_eq!(list.len(), 5);
+        assert!(list.iter().copied().eq(vec![1, 1, 3, 4, 5]));
+        assert_eq!(list.first(), Some(&1));
+        assert_eq!(list.last(), Some(&5));
+
+        assert!(list.contains(&4));
+        assert!(!list.contains(&2));
+        assert!(!list.contains(&6));
+
+        assert!(list.remove(&1));
+        assert!(list.remove(&5));
+        assert!(!list.remove(&2));
+        assert!(list.iter().copied().eq(vec![1, 3, 4]));
+
+        assert_eq!(list.pop_first(), Some(1));
+        assert_eq!(list.pop_first(), Some(3));
+        assert_eq!(list.pop_first(), Some(4));
+        assert_eq!(list.pop_first(), None);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn insert_is_stable() {
+        // Compare by key only
+        #[derive(Debug)]
+        struct Entry(u8, &'static str);
+        impl PartialEq for Entry {
+            fn eq(&self, other: &Self) -> bool {
+                self.0 == other.0
+            }
+        }
+        impl Eq for Entry {}
+        impl PartialOrd for Entry {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+        impl Ord for Entry {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.0.cmp(&other.0)
+            }
+        }
+
+        let mut list = SortedList::new();
+        list.insert(Entry(2, "a"));
+        list.insert(Entry(1, "b"));
+        list.insert(Entry(2, "c"));
+        list.insert(Entry(1, "d"));
+
+        assert!(list
+            .iter()
+            .map(|entry| entry.1)
+            .eq(vec!["b", "d", "a", "c"]));
+    }
+
+    #[test]
+    fn from_unsorted_and_merge() {
+        let mut list: SortedList<_> = vec![9, 3, 7, 1].into_iter().collect();
+        assert!(list.iter().copied().eq(vec![1, 3, 7, 9]));
+
+        list.merge(SortedList::from_unsorted(vec![8, 2, 10]));
+        assert!(list.iter().copied().eq(vec![1, 2, 3, 7, 8, 9, 10]));
+
+        list.extend(vec![5, 0]);
+        assert_eq!(format!("{:?}", list), "[0, 1, 2, 3, 5, 7, 8, 9, 10]");
+        assert_eq!(list.len(), 9);
+
+        assert!(list.into_iter().eq(vec![0, 1, 2, 3, 5, 7, 8, 9, 10]));
+    }
+
+    #[test]
+    fn extend_and_collect_copied() {
+        let mut list: SortedList<i32> = [3, 1].iter().collect();
+        list.extend(&[4, 2]);
+        assert!(list.into_iter().eq(1..=4));
+    }
+}
@@ -0,0 +1,397 @@
+use std::cell::Cell;
+use std::error::Error;
+use std::fmt;
+use std::ptr::null_mut;
+use std::sync::PoisonError;
+
+use crate::sync::thread::{self, Thread};
+use crate::sync::{fence, Arc, AtomicBool, AtomicPtr, Mutex, Ordering};
+
+// Single producer, single consumer channel. Underneath there is the same queue as in
+// ms_queue.rs (list starting with a dummy node), but with only one thread on each end
+// it gets much simpler:
+// - only the Sender touches tail, only the Receiver touches head, so they are plain pointers
+// - the only shared thing is the next link of the last node, Sender writes it, Receiver reads it
+// - Receiver frees nodes right away - Sender never looks back at nodes behind the tail
+//
+// head and tail live in different cache lines, otherwise every write of the Sender to tail
+// would invalidate the line with head on the Receiver's core (false sharing).
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let dummy = Node::new(None);
+
+    let shared = Arc::new(Shared {
+        head: CachePadded(Cell::new(dummy)),
+        tail: CachePadded(Cell::new(dummy)),
+        disconnected: AtomicBool::new(false),
+        receiver_waiting: AtomicBool::new(false),
+        receiver: Mutex::new(None),
+    });
+
+    (
+        Sender {
+            shared: shared.clone(),
+        },
+        Receiver { shared },
+    )
+}
+
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct RecvError;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TryRecvError {
+    Empty,
+    Disconnected,
+}
+
+#[repr(align(64))]
+struct CachePadded<T>(T);
+
+struct Shared<T> {
+    // Receiver's end
+    head: CachePadded<Cell<*mut Node<T>>>,
+    // Sender's end
+    tail: CachePadded<Cell<*mut Node<T>>>,
+    // Set when either side is dropped
+    disconnected: AtomicBool,
+    // Receiver is about to park (or already parked), and has to be woken up
+    receiver_waiting: AtomicBool,
+    receiver: Mutex<Option<Thread>>,
+}
+
+struct Node<T> {
+    // None in the dummy
+    elem: Option<T>,
+    next: AtomicPtr<Node<T>>,
+}
+
+impl<T> Node<T> {
+    fn new(elem: Option<T>) -> *mut Self {
+        Box::into_raw(Box::new(Node {
+            elem,
+            next: AtomicPtr::new(null_mut()),
+        }))
+    }
+}
+
+// Cells are touched by one side only (Sender and Receiver are not Clone, and their
+// methods take &mut self), elements are moved from one thread to the other
+unsafe impl<T: Send> Send for Shared<T> {}
+unsafe impl<T: Send> Sync for Shared<T> {}
+
+impl<T> Sender<T> {
+    // Fails only if the Receiver is gone, elem is given back
+    pub fn send(&mut self, elem: T) -> Result<(), SendError<T>> {
+        let shared = &*self.shared;
+
+        if shared.disconnected.load(Ordering::SeqCst) {
+            return Err(SendError(elem));
+        }
+
+        let node = Node::new(Some(elem));
+
+        unsafe {
+            // Publish the node, SeqCst pairs with the check of receiver_waiting below
+            (*shared.tail.0.get()).next.store(node, Ordering::SeqCst);
+        }
+        shared.tail.0.set(node);
+
+        shared.wake_receiver();
+
+        Ok(())
+    }
+}
+
+impl<T> Receiver<T> {
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let shared = &*self.shared;
+
+        // Check disconnected before looking at the list - Sender sets it after its last send
+        let disconnected = shared.disconnected.load(Ordering::SeqCst);
+
+        let head = shared.head.0.get();
+        let next = unsafe { (*head).next.load(Ordering::SeqCst) };
+
+        if next.is_null() {
+            return Err(if disconnected {
+                TryRecvError::Disconnected
+            } else {
+                TryRecvError::Empty
+            });
+        }
+
+        // next becomes the new dummy, old one is not reachable for the Sender anymore
+        shared.head.0.set(next);
+
+        unsafe {
+            drop(Box::from_raw(head));
+            Ok((*next).elem.take().unwrap())
+        }
+    }
+
+    // Blocks until there is something to receive, fails when the channel is empty and
+    // the Sender is gone
+    pub fn recv(&mut self) -> Result<T, RecvError> {
+        loop {
+            match self.try_recv() {
+                Ok(elem) => return Ok(elem),
+                Err(TryRecvError::Disconnected) => return Err(RecvError),
+                Err(TryRecvError::Empty) => {}
+            }
+
+            let shared = &*self.shared;
+
+            *shared
+                .receiver
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner) = Some(thread::current());
+            shared.receiver_waiting.store(true, Ordering::SeqCst);
+            // Pairs with the fence in wake_receiver, see there
+            fence(Ordering::SeqCst);
+
+            // Check again, Sender could have sent before it noticed we are waiting.
+            // Either we see the element here, or the Sender sees receiver_waiting == true,
+            // so the wake up is not lost
+            let head = shared.head.0.get();
+            let empty = unsafe { (*head).next.load(Ordering::SeqCst).is_null() };
+
+            if empty && !shared.disconnected.load(Ordering::SeqCst) {
+                // If unpark was called already, park returns immediately
+                thread::park();
+            }
+
+            shared.receiver_waiting.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+impl<T> Iterator for Receiver<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.recv().ok()
+    }
+}
+
+impl<T> Shared<T> {
+    fn wake_receiver(&self) {
+        // Each side stores its flag (next link or disconnected here, receiver_waiting in recv)
+        // and then loads the other one. SeqCst on the accesses alone is enough for that
+        // in the C++ model, but the fences on both sides make it obvious, and they are
+        // what loom understands - it treats SeqCst accesses as AcqRel, and reports
+        // a lost wake up without them.
+        fence(Ordering::SeqCst);
+
+        if self.receiver_waiting.load(Ordering::SeqCst) {
+            let receiver = self.receiver.lock().unwrap_or_else(PoisonError::into_inner);
+
+            if let Some(thread) = &*receiver {
+                thread.unpark();
+            }
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.shared.disconnected.store(true, Ordering::SeqCst);
+        self.shared.wake_receiver();
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.shared.disconnected.store(true, Ordering::SeqCst);
+    }
+}
+
+impl<T> Drop for Shared<T> {
+    fn drop(&mut self) {
+        // Both sides are gone, free whatever was not received, dummy included
+        let mut node = self.head.0.get();
+
+        while !node.is_null() {
+            let boxed = unsafe { Box::from_raw(node) };
+            node = boxed.next.load(Ordering::Relaxed);
+        }
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sending on a disconnected channel")
+    }
+}
+
+impl<T: fmt::Debug> Error for SendError<T> {}
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "receiving on an empty and disconnected channel")
+    }
+}
+
+impl Error for RecvError {}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryRecvError::Empty => write!(f, "receiving on an empty channel"),
+            TryRecvError::Disconnected => {
+                write!(f, "receiving on an empty and disconnected channel")
+            }
+        }
+    }
+}
+
+impl Error for TryRecvError {}
+
+#[cfg(all(test, not(loom)))]
+mod test {
+    use super::{channel, RecvError, SendError, TryRecvError};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn basics() {
+        let (mut tx, mut rx) = channel();
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+
+        assert_eq!(rx.try_recv(), Ok(1));
+        assert_eq!(rx.recv(), Ok(2));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+    }
+
+    #[test]
+    fn disconnect() {
+        let (mut tx, mut rx) = channel();
+        tx.send(1).unwrap();
+        drop(tx);
+
+        // What was sent before disconnecting is still delivered
+        assert_eq!(rx.recv(), Ok(1));
+        assert_eq!(rx.recv(), Err(RecvError));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+
+        let (mut tx, rx) = channel();
+        drop(rx);
+        assert_eq!(tx.send(1), Err(SendError(1)));
+    }
+
+    #[test]
+    fn drop_frees_elements() {
+        let elem = Arc::new(());
+
+        {
+            let (mut tx, mut rx) = channel();
+            for _ in 0..10 {
+                tx.send(elem.clone()).unwrap();
+            }
+            rx.recv().unwrap();
+        }
+
+        assert_eq!(Arc::strong_count(&elem), 1);
+    }
+
+    #[test]
+    fn recv_waits_for_sender() {
+        let (mut tx, rx) = channel();
+
+        let receiver = thread::spawn(move || rx.collect::<Vec<_>>());
+
+        thread::sleep(Duration::from_millis(20));
+        tx.send(1).unwrap();
+        thread::sleep(Duration::from_millis(20));
+        tx.send(2).unwrap();
+        drop(tx);
+
+        assert_eq!(receiver.join().unwrap(), vec![1, 2]);
+    }
+
+    #[test]
+    fn stress() {
+        const COUNT: usize = 100_000;
+
+        let (mut tx, rx) = channel();
+
+        let sender = thread::spawn(move || {
+            for i in 0..COUNT {
+                tx.send(i).unwrap();
+            }
+        });
+
+        let receiver = thread::spawn(move || rx.eq(0..COUNT));
+
+        sender.join().unwrap();
+        assert!(receiver.join().unwrap());
+    }
+}
+
+#[cfg(all(test, loom))]
+mod loom_test {
+    use super::{channel, RecvError};
+    use loom::thread;
+
+    #[test]
+    fn send_recv() {
+        loom::model(|| {
+            let (mut tx, mut rx) = channel();
+
+            let sender = thread::spawn(move || {
+                tx.send(1).unwrap();
+                tx.send(2).unwrap();
+            });
+
+            // recv parks when the channel is empty, if a wake up got lost,
+            // loom reports a deadlock
+            assert_eq!(rx.recv(), Ok(1));
+            assert_eq!(rx.recv(), Ok(2));
+            assert_eq!(rx.recv(), Err(RecvError));
+
+            sender.join().unwrap();
+        });
+    }
+
+    #[test]
+    fn disconnect_while_waiting() {
+        loom::model(|| {
+            let (tx, mut rx) = channel::<i32>();
+
+            let sender = thread::spawn(move || drop(tx));
+
+            assert_eq!(rx.recv(), Err(RecvError));
+            sender.join().unwrap();
+        });
+    }
+
+    #[test]
+    fn drop_receiver_while_sending() {
+        loom::model(|| {
+            let (mut tx, rx) = channel();
+
+            let receiver = thread::spawn(move || drop(rx));
+
+            // Either goes through, or comes back, but is never lost - loom checks
+            // that the Arc with the element gets freed
+            let elem = loom::sync::Arc::new(1);
+            let _ = tx.send(elem);
+
+            receiver.join().unwrap();
+        });
+    }
+}
@@ -0,0 +1,276 @@
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use core::cell::{Cell, OnceCell};
+use core::iter::FusedIterator;
+
+// Lazy list: a persistent list like third::List, but the rest of the list after each node is
+// not there yet - there is a function computing it (thunk). It's called the first time
+// someone asks for the tail, and the result is kept (memoized) in the node, so it's called
+// only once, no matter how many times, and by how many streams sharing the node,
+// the stream is walked.
+//
+// Only the part which is looked at is ever computed, so streams can be infinite:
+// Stream::iterate(0, |x| x + 1).take(10)
+pub struct Stream<T> {
+    head: Option<Rc<Node<T>>>,
+}
+
+// Gets the element of its node, iterate needs it to compute the next one
+type Thunk<T> = Box<dyn FnOnce(&T) -> Stream<T>>;
+
+struct Node<T> {
+    elem: T,
+    // Set once, from thunk
+    next: OnceCell<Stream<T>>,
+    // Taken out, and called, when next is needed the first time
+    thunk: Cell<Option<Thunk<T>>>,
+}
+
+impl<T> Node<T> {
+    fn next(&self) -> &Stream<T> {
+        self.next.get_or_init(|| {
+            let thunk = self
+                .thunk
+                .take()
+                .expect("stream needs its own tail to compute it");
+            thunk(&self.elem)
+        })
+    }
+}
+
+impl<T> Default for Stream<T> {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+// Shares the nodes, what one of the clones computes, the others get for free
+impl<T> Clone for Stream<T> {
+    fn clone(&self) -> Self {
+        Stream {
+            head: self.head.clone(),
+        }
+    }
+}
+
+impl<T> Stream<T> {
+    pub fn empty() -> Self {
+        Stream { head: None }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn head(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.elem)
+    }
+
+    // Computes the tail if it wasn't yet
+    pub fn tail(&self) -> Stream<T> {
+        match &self.head {
+            Some(node) => node.next().clone(),
+            None => Stream::empty(),
+        }
+    }
+
+    // Computes tails only as far as the iterator gets - for an infinite stream
+    // it never ends, use take
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_deref(),
+            started: false,
+        }
+    }
+}
+
+// Thunks are stored as Box<dyn FnOnce>, which is 'static, so is everything they capture
+impl<T: 'static> Stream<T> {
+    // elem followed by whatever rest returns, rest is not called until the tail is needed
+    pub fn cons<F>(elem: T, rest: F) -> Self
+    where
+        F: FnOnce() -> Stream<T> + 'static,
+    {
+        Self::cons_with(elem, move |_| rest())
+    }
+
+    fn cons_with<F>(elem: T, rest: F) -> Self
+    where
+        F: FnOnce(&T) -> Stream<T> + 'static,
+    {
+        Stream {
+            head: Some(Rc::new(Node {
+                elem,
+                next: OnceCell::new(),
+                thunk: Cell::new(Some(Box::new(rest))),
+            })),
+        }
+    }
+
+    // seed, f(seed), f(f(seed)), ... infinite
+    pub fn iterate<F>(seed: T, f: F) -> Self
+    where
+        F: Fn(&T) -> T + 'static,
+    {
+        fn iterate_rc<T: 'static, F: Fn(&T) -> T + 'static>(seed: T, f: Rc<F>) -> Stream<T> {
+            Stream::cons_with(seed, move |prev| iterate_rc(f(prev), f))
+        }
+
+        iterate_rc(seed, Rc::new(f))
+    }
+
+    // elem, elem, elem, ... infinite
+    pub fn repeat(elem: T) -> Self
+    where
+        T: Clone,
+    {
+        Self::iterate(elem, T::clone)
+    }
+
+    // First n elements, lazily as well - nothing is computed until the result is walked.
+    // Elements are copied, the new stream has its own nodes, with a different end.
+    pub fn take(&self, n: usize) -> Stream<T>
+    where
+        T: Clone,
+    {
+        match self.head() {
+            Some(elem) if n > 0 => {
+                let rest = self.clone();
+                Stream::cons(elem.clone(), move || rest.tail().take(n - 1))
+            }
+            _ => Stream::empty(),
+        }
+    }
+
+    // Lazy too, f is called for an element when the new stream gets to it
+    pub fn map<U: 'static, F>(&self, f: F) -> Stream<U>
+    where
+        F: Fn(&T) -> U + 'static,
+    {
+        fn map_rc<T: 'static, U: 'static, F: Fn(&T) -> U + 'static>(
+            stream: &Stream<T>,
+            f: Rc<F>,
+        ) -> Stream<U> {
+            match stream.head() {
+                Some(elem) => {
+                    let rest = stream.clone();
+                    Stream::cons(f(elem), move || map_rc(&rest.tail(), f))
+                }
+                None => Stream::empty(),
+            }
+        }
+
+        map_rc(self, Rc::new(f))
+    }
+}
+
+// Same as in third.rs, a long (computed) stream would drop its nodes recursively
+impl<T> Drop for Stream<T> {
+    fn drop(&mut self) {
+        let mut head = self.head.take();
+
+        while let Some(node) = head {
+            match Rc::try_unwrap(node) {
+                Ok(mut node) => head = node.next.take().and_then(|mut next| next.head.take()),
+                // Someone else holds the rest
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+    // The tail of the current node is computed only when the next element is asked for,
+    // not when the current one is returned
+    started: bool,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.started {
+            let node = self.next?;
+            self.next = node.next().head.as_deref();
+        }
+        self.started = true;
+
+        self.next.map(|node| &node.elem)
+    }
+}
+
+impl<'a, T> FusedIterator for Iter<'a, T> {}
+
+impl<'a, T> IntoIterator for &'a Stream<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Stream;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn basics() {
+        let stream = Stream::cons(1, || Stream::cons(2, Stream::empty));
+        assert_eq!(stream.head(), Some(&1));
+        assert_eq!(stream.tail().head(), Some(&2));
+        assert!(stream.tail().tail().is_empty());
+        assert!(stream.iter().eq(&[1, 2]));
+
+        assert!(Stream::<i32>::empty().iter().next().is_none());
+    }
+
+    #[test]
+    fn infinite() {
+        let naturals = Stream::iterate(0, |x| x + 1);
+        assert!(naturals.take(10).iter().copied().eq(0..10));
+
+        let squares = naturals.map(|x| x * x);
+        assert!(squares.iter().take(4).eq(&[0, 1, 4, 9]));
+
+        assert!(Stream::repeat('a').take(3).iter().eq(&['a', 'a', 'a']));
+        assert!(naturals.take(0).is_empty());
+    }
+
+    #[test]
+    fn memoized() {
+        let calls = Rc::new(Cell::new(0));
+
+        let stream = {
+            let calls = calls.clone();
+            Stream::iterate(0, move |x| {
+                calls.set(calls.get() + 1);
+                x + 1
+            })
+        };
+
+        // Nothing computed yet
+        assert_eq!(calls.get(), 0);
+
+        assert_eq!(stream.iter().nth(4), Some(&4));
+        assert_eq!(calls.get(), 4);
+
+        // Second walk, and walks of clones, reuse what's computed
+        assert_eq!(stream.iter().nth(4), Some(&4));
+        assert_eq!(stream.clone().tail().iter().nth(3), Some(&4));
+        assert_eq!(calls.get(), 4);
+
+        // Iterator doesn't compute more than it returns
+        stream.iter().take(6).count();
+        assert_eq!(calls.get(), 5);
+    }
+
+    #[test]
+    fn long_stream_drop() {
+        let stream = Stream::iterate(0u64, |x| x + 1);
+        assert_eq!(stream.iter().nth(1_000_000), Some(&1_000_000));
+    }
+}
@@ -0,0 +1,25 @@
+// Atomics used by the lock-free lists. Built with RUSTFLAGS="--cfg loom", they are
+// swapped for loom's versions, which let loom explore every possible interleaving
+// of the threads in the tests:
+// RUSTFLAGS="--cfg loom" cargo test --release
+#[cfg(loom)]
+pub(crate) use loom::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+
+#[cfg(not(loom))]
+pub(crate) use core::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+
+// spsc.rs mixes atomics with a Mutex and parking, loom has to see all of them,
+// otherwise it can't tell a lost wake up from a thread that is just slow
+#[cfg(all(loom, feature = "std"))]
+pub(crate) use loom::{
+    sync::atomic::{fence, AtomicBool},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+#[cfg(all(not(loom), feature = "std"))]
+pub(crate) use std::{
+    sync::atomic::{fence, AtomicBool},
+    sync::{Arc, Mutex},
+    thread,
+};
@@ -0,0 +1,166 @@
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use crate::fifth::List;
+
+// fifth::List guarded by a Mutex, with a Condvar to wake up consumers waiting for elements.
+// Simple work queue between threads, when lock-free ms_queue::Queue is an overkill, or
+// when consumers need to block instead of spinning.
+pub struct SyncQueue<T> {
+    list: Mutex<List<T>>,
+    not_empty: Condvar,
+}
+
+impl<T> Default for SyncQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> SyncQueue<T> {
+    pub const fn new() -> Self {
+        SyncQueue {
+            list: Mutex::new(List::new()),
+            not_empty: Condvar::new(),
+        }
+    }
+
+    // Mutex gets poisoned when a thread panics while holding the lock, but no operation
+    // here can leave the list half modified, so it's safe to carry on
+    fn lock(&self) -> MutexGuard<'_, List<T>> {
+        self.list.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().peek().is_none()
+    }
+
+    pub fn push(&self, elem: T) {
+        self.lock().push(elem);
+
+        // Lock is already released, woken up thread does not have to wait for it
+        self.not_empty.notify_one();
+    }
+
+    pub fn try_pop(&self) -> Option<T> {
+        self.lock().pop()
+    }
+
+    // Blocks until there is something to pop
+    pub fn pop_blocking(&self) -> T {
+        // wait_while releases the lock while sleeping, and takes care of spurious wake ups
+        let mut list = self
+            .not_empty
+            .wait_while(self.lock(), |list| list.peek().is_none())
+            .unwrap_or_else(PoisonError::into_inner);
+
+        list.pop().unwrap()
+    }
+
+    // Blocks for at most timeout, None if the queue is still empty
+    pub fn pop_timeout(&self, timeout: Duration) -> Option<T> {
+        let (mut list, _) = self
+            .not_empty
+            .wait_timeout_while(self.lock(), timeout, |list| list.peek().is_none())
+            .unwrap_or_else(PoisonError::into_inner);
+
+        list.pop()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SyncQueue;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn basics() {
+        let queue = SyncQueue::new();
+        assert!(queue.is_empty());
+        assert_eq!(queue.try_pop(), None);
+
+        queue.push(1);
+        queue.push(2);
+        assert!(!queue.is_empty());
+
+        assert_eq!(queue.try_pop(), Some(1));
+        assert_eq!(queue.pop_blocking(), 2);
+        assert_eq!(queue.try_pop(), None);
+    }
+
+    #[test]
+    fn pop_timeout() {
+        let queue = SyncQueue::new();
+
+        let start = Instant::now();
+        assert_eq!(queue.pop_timeout(Duration::from_millis(50)), None);
+        assert!(start.elapsed() >= Duration::from_millis(50));
+
+        queue.push(1);
+        assert_eq!(queue.pop_timeout(Duration::from_secs(10)), Some(1));
+    }
+
+    #[test]
+    fn pop_blocking_waits_for_producer() {
+        let queue = Arc::new(SyncQueue::new());
+
+        let consumer = {
+            let queue = queue.clone();
+            thread::spawn(move || (queue.pop_blocking(), queue.pop_blocking()))
+        };
+
+        thread::sleep(Duration::from_millis(20));
+        queue.push(1);
+        queue.push(2);
+
+        assert_eq!(consumer.join().unwrap(), (1, 2));
+    }
+
+    #[test]
+    fn work_queue() {
+        const PRODUCERS: usize = 4;
+        const CONSUMERS: usize = 4;
+        const PER_PRODUCER: usize = 1000;
+
+        let queue = Arc::new(SyncQueue::new());
+
+        let producers = (0..PRODUCERS)
+            .map(|p| {
+                let queue = queue.clone();
+                thread::spawn(move || {
+                    for i in 0..PER_PRODUCER {
+                        queue.push(Some(p * PER_PRODUCER + i));
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let consumers = (0..CONSUMERS)
+            .map(|_| {
+                let queue = queue.clone();
+                thread::spawn(move || {
+                    let mut sum = 0;
+                    // None is the stop signal
+                    while let Some(elem) = queue.pop_blocking() {
+                        sum += elem;
+                    }
+                    sum
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for producer in producers {
+            producer.join().unwrap();
+        }
+
+        for _ in 0..CONSUMERS {
+            queue.push(None);
+        }
+
+        let sum: usize = consumers.into_iter().map(|c| c.join().unwrap()).sum();
+        let n = PRODUCERS * PER_PRODUCER;
+        assert_eq!(sum, n * (n - 1) / 2);
+    }
+}
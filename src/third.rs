@@ -1,89 +1,1074 @@
-use std::{ops::Deref, rc::Rc};
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::rc::{Rc, Weak};
+use alloc::string::String;
+use alloc::sync::Arc;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::iter::{FromIterator, FusedIterator};
+
+use crate::dot::Dot;
+use crate::traits::Stack;
 
 pub struct List<T> {
     head: Link<T>,
 }
 
-type Link<T> = Option<Rc<Node<T>>>;
+type Link<T> = Option<Rc<Node<T>>>;
+
+struct Node<T> {
+    elem: T,
+    // Length of the list starting at this node. Nodes are immutable and the suffix
+    // is shared by everyone, so it's the same no matter which list we look from.
+    len: usize,
+    next: Link<T>,
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Cloning just shares the head, O(1), no element is copied
+impl<T> Clone for List<T> {
+    fn clone(&self) -> Self {
+        List {
+            head: self.head.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T: proptest::arbitrary::Arbitrary> proptest::arbitrary::Arbitrary for List<T> {
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <T as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = crate::arbitrary::ListStrategy<T, Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::arbitrary::list_strategy(args, |elems| {
+            elems
+                .into_iter()
+                .rev()
+                .fold(List::new(), |list, elem| list.prepend(elem))
+        })
+    }
+}
+
+// Lists sharing a tail, the way they end up after prepending to the same list a few times:
+// one common tail, and every list has its own (possibly empty) prefix in front of it.
+// For tests which care about nodes shared between lists, not only about the elements.
+// Gives back the tail too, to know which part is shared.
+#[cfg(feature = "proptest")]
+pub fn arbitrary_shared<T: proptest::arbitrary::Arbitrary>(
+    lists: impl Into<proptest::collection::SizeRange>,
+) -> impl proptest::strategy::Strategy<Value = (List<T>, alloc::vec::Vec<List<T>>)> {
+    use proptest::arbitrary::any;
+    use proptest::collection::vec;
+    use proptest::strategy::Strategy;
+
+    (any::<List<T>>(), vec(vec(any::<T>(), 0..8), lists)).prop_map(|(tail, prefixes)| {
+        let lists = prefixes
+            .into_iter()
+            .map(|prefix| {
+                prefix
+                    .into_iter()
+                    .rev()
+                    .fold(tail.clone(), |list, elem| list.prepend(elem))
+            })
+            .collect();
+        (tail, lists)
+    })
+}
+
+// Graphviz graphs, see dot.rs. Each node is labeled with its Rc count: how many lists
+// (and clones, and other nodes) hold it. Anything above 1 means the node is shared.
+impl<T: fmt::Debug> List<T> {
+    pub fn to_dot(&self) -> String {
+        Self::to_dot_all(&[self])
+    }
+
+    // All the lists on one graph, so the nodes they share are drawn once
+    pub fn to_dot_all(lists: &[&List<T>]) -> String {
+        let mut dot = Dot::new();
+        let mut drawn = BTreeSet::new();
+
+        for (i, list) in lists.iter().enumerate() {
+            let mut cur = list.head.as_ref();
+            dot.root(&format!("list {}", i), cur.map(Rc::as_ptr));
+
+            while let Some(node) = cur {
+                // Rest of the list was drawn already, with one of the previous lists
+                if !drawn.insert(Rc::as_ptr(node)) {
+                    break;
+                }
+
+                let count = format!("rc={}", Rc::strong_count(node));
+                dot.node(Rc::as_ptr(node), &node.elem, &count);
+                if let Some(next) = &node.next {
+                    dot.edge(Rc::as_ptr(node), Rc::as_ptr(next), "");
+                }
+                cur = node.next.as_ref();
+            }
+        }
+
+        dot.finish()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+// Same as in second.rs. Lists sharing the head are not equal without looking at
+// elements, NaN != NaN, see ptr_eq for that.
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+// Whether both point at the very same node (or both are empty)
+fn same_link<T>(a: &Link<T>, b: &Link<T>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+impl<T: PartialOrd> PartialOrd for List<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T: Ord> Ord for List<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<T: Hash> Hash for List<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for elem in self.iter() {
+            elem.hash(state);
+        }
+    }
+}
+
+impl<T> List<T> {
+    // Empty list is just None, no Rc yet, so it can be a const. Not a static though,
+    // Rc is not Sync - that's what SyncList is for.
+    pub const fn new() -> Self {
+        List { head: None }
+    }
+
+    pub fn prepend(&self, elem: T) -> Self {
+        List {
+            head: Some(Rc::new(Node {
+                elem,
+                len: self.len() + 1,
+                // This will increase a reference count of that node
+                next: self.head.clone(),
+            })),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.head.as_ref().map_or(0, |node| node.len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    // O(1), whether both are the very same list, nodes shared from the head on
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        same_link(&self.head, &other.head)
+    }
+
+    // Every node the list reaches, shared or not, see heap_size.rs. Lists sharing a tail
+    // all count it, so adding these up counts the tail many times.
+    pub fn heap_size_in_bytes(&self) -> usize {
+        self.len() * crate::heap_size::rc_allocation::<Node<T>>()
+    }
+
+    // Only the nodes this list holds alone, the ones dropping it would free. The first
+    // node with strong count above 1 is held by someone else as well (another list,
+    // or a node of one), and so is everything after it, so the walk stops there.
+    pub fn unshared_heap_size_in_bytes(&self) -> usize {
+        let mut nodes = 0;
+        let mut link = &self.head;
+        while let Some(node) = link {
+            if Rc::strong_count(node) > 1 {
+                break;
+            }
+            nodes += 1;
+            link = &node.next;
+        }
+
+        nodes * crate::heap_size::rc_allocation::<Node<T>>()
+    }
+
+    // Bounds check is O(1), but getting to the element is still O(n)
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len() {
+            return None;
+        }
+
+        self.iter().nth(index)
+    }
+
+    pub fn head(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.elem)
+    }
+
+    pub fn tail(&self) -> Self {
+        // and_then is the same as map, but it does not wrap result into Option,
+        // This is delegated to closure
+        // and_then returns f(x), where f: x -> Some(y)
+        let _b = self.head.as_ref().and_then(|e|
+            /* e.next is option itself, so no need to wrap it again */
+
+            // need to state .clone(), because Rc does not implement Copy, so move will occur.
+            // Implement Copy if you want your type to be copied instead of moving (like i32)
+            // Implement Clone if you want to give 'copy' capabilities on demand,
+            // having still default move semantics
+            e.next.clone());
+
+        // map returns Some(f(x)), where f: x -> y
+        let c = self.head.as_ref().map(|e| {
+            e.next.clone()
+            // no matter what you will do here, map will wrap it up into Option.
+            // In particular Option::None will become Some(None)
+
+            // We need to return what's inside e.next (which is an Option itself)
+        });
+
+        // use flatten to reduce nesting of Option, so Some(None) becomes None, Some(Some(x)) -> Some(x)
+        let c = c.flatten();
+
+        List { head: c }
+    }
+}
+
+// Same as for second::List, Rc derefs to the node, so as_deref works here too.
+// Iterator only borrows the nodes, reference counts are not touched
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+}
+
+impl<T> List<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_deref(),
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| {
+            self.next = node.next.as_deref();
+            &node.elem
+        })
+    }
+
+    // Every node knows how many are left from it on
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.next.map_or(0, |node| node.len);
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> FusedIterator for Iter<'a, T> {}
+
+// Lists are built front to back, that's the only order which keeps elements in place
+// without reversing twice. Just created node is not shared with anyone yet, so
+// Rc::get_mut succeeds, and we can still fill in its next link.
+// Takes the empty link at the end of the list under construction, appends elem there,
+// and returns the next empty link.
+fn push_link<T>(tail: &mut Link<T>, elem: T) -> &mut Link<T> {
+    let node = tail.insert(Rc::new(Node {
+        elem,
+        // Not known yet, depends on what is linked after it, see fill_lengths
+        len: 0,
+        next: None,
+    }));
+
+    &mut Rc::get_mut(node).unwrap().next
+}
+
+// Once the list is complete, sets lengths of first `fresh` nodes (made by push_link,
+// so still unique), len is the length of the whole list.
+fn fill_lengths<T>(mut link: &mut Link<T>, mut len: usize, fresh: usize) {
+    for _ in 0..fresh {
+        let node = Rc::get_mut(link.as_mut().unwrap()).unwrap();
+        node.len = len;
+        len -= 1;
+        link = &mut node.next;
+    }
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// Elements stay in the order they come, the first one is the head. Built front to back
+// with push_link, no prepending in reverse. The list under construction is a List
+// already, so if iter panics, whatever was built is dropped by List's Drop, no recursion.
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = List::new();
+        let mut tail = &mut list.head;
+        let mut len = 0;
+
+        for elem in iter {
+            tail = push_link(tail, elem);
+            len += 1;
+        }
+
+        fill_lengths(&mut list.head, len, len);
+        list
+    }
+}
+
+// Nodes can't change, so there is no Extend, but collecting from references to Copy
+// elements works, same as for second::List
+impl<'a, T: Copy + 'a> FromIterator<&'a T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = &'a T>>(iter: I) -> Self {
+        iter.into_iter().copied().collect()
+    }
+}
+
+// plist![1, 2, 3] is List::new().prepend(3).prepend(2).prepend(1), written the way
+// it reads, head first
+#[macro_export]
+macro_rules! plist {
+    () => {
+        $crate::third::List::new()
+    };
+    ($($elem:expr),+ $(,)?) => {
+        ::core::iter::IntoIterator::into_iter([$($elem),+])
+            .rev()
+            .fold($crate::third::List::new(), |list, elem| list.prepend(elem))
+    };
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for List<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::serde_seq::serialize_seq(serializer, self.len(), self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for List<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_seq::deserialize_seq(deserializer, "a list", |elems| elems.collect())
+    }
+}
+
+// All operations return new lists, self is untouched. No recursion, so they work
+// on long lists too.
+impl<T> List<T> {
+    pub fn rev(&self) -> List<T>
+    where
+        T: Clone,
+    {
+        // prepending while walking from the front gives reversed order for free,
+        // nothing can be shared
+        self.iter()
+            .fold(List::new(), |list, elem| list.prepend(elem.clone()))
+    }
+
+    // Elements of self have to be copied (last node of self cannot point to other
+    // without modifying self), but other is shared wholesale
+    pub fn concat(&self, other: &List<T>) -> List<T>
+    where
+        T: Clone,
+    {
+        let mut head = None;
+        let mut tail = &mut head;
+
+        for elem in self.iter() {
+            tail = push_link(tail, elem.clone());
+        }
+
+        *tail = other.head.clone();
+        fill_lengths(&mut head, self.len() + other.len(), self.len());
+
+        List { head }
+    }
+
+    // Path copying: nodes up to index are copied (they have to point at a new node),
+    // the one at index gets f(old element), and everything after it is shared.
+    // None if index is out of bounds.
+    pub fn update<F>(&self, index: usize, f: F) -> Option<List<T>>
+    where
+        T: Clone,
+        F: FnOnce(&T) -> T,
+    {
+        if index >= self.len() {
+            return None;
+        }
+
+        let mut head = None;
+        let mut tail = &mut head;
+        let mut next = self.head.as_ref();
+
+        for _ in 0..index {
+            let node = next.unwrap();
+            tail = push_link(tail, node.elem.clone());
+            next = node.next.as_ref();
+        }
+
+        let node = next.unwrap();
+        tail = push_link(tail, f(&node.elem));
+        *tail = node.next.clone();
+        fill_lengths(&mut head, self.len(), index + 1);
+
+        Some(List { head })
+    }
+
+    pub fn set(&self, index: usize, elem: T) -> Option<List<T>>
+    where
+        T: Clone,
+    {
+        self.update(index, |_| elem)
+    }
+
+    // List without its first n elements - it's a suffix of self, so it's just shared:
+    // O(n) to walk there, nothing is allocated. Empty if n >= len.
+    pub fn drop(&self, n: usize) -> List<T> {
+        let mut next = self.head.as_ref();
+
+        for _ in 0..n {
+            next = match next {
+                Some(node) => node.next.as_ref(),
+                None => break,
+            };
+        }
+
+        List {
+            head: next.cloned(),
+        }
+    }
+
+    // First n elements. The last of them must not point at the rest, so they are copied,
+    // unless n covers the whole list - then it's self, shared.
+    pub fn take(&self, n: usize) -> List<T>
+    where
+        T: Clone,
+    {
+        self.split_at(n).0
+    }
+
+    // (take(n), drop(n)) in one pass
+    pub fn split_at(&self, n: usize) -> (List<T>, List<T>)
+    where
+        T: Clone,
+    {
+        if n >= self.len() {
+            return (self.clone(), List::new());
+        }
+
+        let mut head = None;
+        let mut tail = &mut head;
+        let mut next = self.head.as_ref();
+
+        for _ in 0..n {
+            let node = next.unwrap();
+            tail = push_link(tail, node.elem.clone());
+            next = node.next.as_ref();
+        }
+
+        fill_lengths(&mut head, n, n);
+
+        (
+            List { head },
+            List {
+                head: next.cloned(),
+            },
+        )
+    }
+
+    pub fn map<U, F>(&self, mut f: F) -> List<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        let mut head = None;
+        let mut tail = &mut head;
+
+        for elem in self.iter() {
+            tail = push_link(tail, f(elem));
+        }
+
+        fill_lengths(&mut head, self.len(), self.len());
+
+        List { head }
+    }
+
+    // f(a, b) for elements at the same positions, as long as the shorter list. Both lists
+    // stay as they are, elements are only borrowed, so nothing has to be Clone.
+    pub fn zip_with<U, V, F>(&self, other: &List<U>, mut f: F) -> List<V>
+    where
+        F: FnMut(&T, &U) -> V,
+    {
+        let mut head = None;
+        let mut tail = &mut head;
+
+        for (a, b) in self.iter().zip(other.iter()) {
+            tail = push_link(tail, f(a, b));
+        }
+
+        let len = self.len().min(other.len());
+        fill_lengths(&mut head, len, len);
+
+        List { head }
+    }
+
+    // Nodes before the last rejected element have to be copied, but everything
+    // after it passes the predicate, so that suffix is shared
+    pub fn filter<F>(&self, mut pred: F) -> List<T>
+    where
+        T: Clone,
+        F: FnMut(&T) -> bool,
+    {
+        let mut head = None;
+        let mut tail = &mut head;
+        let mut fresh = 0;
+
+        // First node of the current run of elements passing the predicate
+        let mut run = self.head.as_ref();
+        let mut next = self.head.as_ref();
+
+        while let Some(node) = next {
+            if !pred(&node.elem) {
+                // Run is over and it's not a suffix, copy it
+                while let Some(kept) = run.filter(|kept| !Rc::ptr_eq(kept, node)) {
+                    tail = push_link(tail, kept.elem.clone());
+                    fresh += 1;
+                    run = kept.next.as_ref();
+                }
+
+                run = node.next.as_ref();
+            }
+
+            next = node.next.as_ref();
+        }
+
+        *tail = run.cloned();
+        fill_lengths(&mut head, fresh + run.map_or(0, |node| node.len), fresh);
+
+        List { head }
+    }
+}
+
+// A position inside the list, to make a few edits around it without rebuilding
+// the list after each of them. Everything after the focus is just shared, so edits
+// at the focus are O(1), the nodes before it are copied once, by to_list - that's
+// unavoidable, they point at the old suffix and nodes can't change.
+//
+// let list = plist![1, 2, 3, 4];
+// let mut zipper = list.zipper();
+// zipper.move_next();
+// zipper.replace(20);
+// zipper.to_list() // [1, 20, 3, 4], shares 3, 4 with list, list is untouched
+pub struct Zipper<T> {
+    // Nodes passed so far, nearest first. It's a persistent list as well, so moving
+    // around and cloning the zipper are O(1), and nothing gets copied.
+    before: List<Rc<Node<T>>>,
+    // Focus is the head, None past the last element
+    after: List<T>,
+}
+
+impl<T> Clone for Zipper<T> {
+    fn clone(&self) -> Self {
+        Zipper {
+            before: self.before.clone(),
+            after: self.after.clone(),
+        }
+    }
+}
+
+// before holds every passed node strong, and each of them owns the rest of the list.
+// Released nearest first, as List::drop would do it, the first node goes last, and if
+// the source list is gone it frees the whole chain through Node's recursive drop.
+// So release them from the far end, where List::drop can take over one node at a time.
+impl<T> Drop for Zipper<T> {
+    fn drop(&mut self) {
+        let mut before = core::mem::take(&mut self.before).rev();
+        while let Some(node) = before.head().cloned() {
+            before = before.tail();
+            drop(List { head: Some(node) });
+        }
+    }
+}
+
+impl<T> List<T> {
+    // Focus on the first element
+    pub fn zipper(&self) -> Zipper<T> {
+        Zipper {
+            before: List::new(),
+            after: self.clone(),
+        }
+    }
+}
+
+impl<T> Zipper<T> {
+    pub fn focus(&self) -> Option<&T> {
+        self.after.head()
+    }
+
+    pub fn index(&self) -> usize {
+        self.before.len()
+    }
+
+    // Moves to the next element, or past the last one, false if already there
+    pub fn move_next(&mut self) -> bool {
+        let node = match &self.after.head {
+            Some(node) => node.clone(),
+            None => return false,
+        };
+
+        self.after = self.after.tail();
+        self.before = self.before.prepend(node);
+        true
+    }
+
+    // Back to the previous element, false if at the first one.
+    // The node we leave is put back in front of the focus, shared if the focus
+    // is still where it was, copied if it was changed.
+    pub fn move_prev(&mut self) -> bool
+    where
+        T: Clone,
+    {
+        let node = match self.before.head() {
+            Some(node) => node.clone(),
+            None => return false,
+        };
+
+        self.before = self.before.tail();
+        self.after = restore(&node, &self.after);
+        true
+    }
+
+    // Replaces the focused element, gives elem back past the last one
+    pub fn replace(&mut self, elem: T) -> Result<(), T> {
+        if self.after.is_empty() {
+            return Err(elem);
+        }
+
+        self.after = self.after.tail().prepend(elem);
+        Ok(())
+    }
+
+    // Inserts elem before the focus, it becomes the new focus
+    pub fn insert(&mut self, elem: T) {
+        self.after = self.after.prepend(elem);
+    }
+
+    // Removes the focused element, the next one becomes the focus,
+    // false if there was nothing to remove
+    pub fn remove(&mut self) -> bool {
+        if self.after.is_empty() {
+            return false;
+        }
+
+        self.after = self.after.tail();
+        true
+    }
+
+    // The list with all the edits. Nodes before the first edit are shared too,
+    // if there were no edits at all, it's the very same list the zipper came from.
+    pub fn to_list(&self) -> List<T>
+    where
+        T: Clone,
+    {
+        self.before
+            .iter()
+            .fold(self.after.clone(), |after, node| restore(node, &after))
+    }
+}
+
+// Node followed by after: the node itself, if it's already linked to after,
+// a copy of it otherwise
+fn restore<T: Clone>(node: &Rc<Node<T>>, after: &List<T>) -> List<T> {
+    if same_link(&node.next, &after.head) {
+        List {
+            head: Some(node.clone()),
+        }
+    } else {
+        after.prepend(node.elem.clone())
+    }
+}
+
+// Read only position that can walk back, without holding on to what it walked past.
+//
+// Nodes can't point back: a shared suffix has a different previous node in every list
+// that shares it, and a strong prev link would make a cycle with next, which Rc never
+// frees. So back links live in the cursor, and they are Weak - the cursor keeps the
+// rest of the list alive (current node and everything after it), but not the nodes
+// before it. Those belong to the list; once it's dropped the cursor can't go back
+// anymore, move_prev says so, and nothing leaks.
+//
+// Zipper holds the nodes before the focus strong, as it has to rebuild the list from them.
+pub struct Cursor<T> {
+    // Nodes passed so far, nearest first, O(1) clone as in Zipper
+    before: List<Weak<Node<T>>>,
+    // Current element is the head, empty past the last element. A List and not a Link,
+    // so when the cursor is the last owner its Drop frees the nodes, without recursion.
+    current: List<T>,
+}
+
+impl<T> Clone for Cursor<T> {
+    fn clone(&self) -> Self {
+        Cursor {
+            before: self.before.clone(),
+            current: self.current.clone(),
+        }
+    }
+}
+
+impl<T> List<T> {
+    // At the first element
+    pub fn cursor(&self) -> Cursor<T> {
+        Cursor {
+            before: List::new(),
+            current: self.clone(),
+        }
+    }
+}
+
+impl<T> Cursor<T> {
+    pub fn current(&self) -> Option<&T> {
+        self.current.head()
+    }
+
+    pub fn index(&self) -> usize {
+        self.before.len()
+    }
+
+    // Moves to the next element, or past the last one, false if already there
+    pub fn move_next(&mut self) -> bool {
+        let node = match &self.current.head {
+            Some(node) => Rc::downgrade(node),
+            None => return false,
+        };
+
+        self.current = self.current.tail();
+        self.before = self.before.prepend(node);
+        true
+    }
+
+    // Back to the previous element, false if at the first one, or if the previous
+    // node is gone (no list holds it any more). Nodes before a gone one are gone too,
+    // each of them held the next one, so the cursor forgets the way back then.
+    pub fn move_prev(&mut self) -> bool {
+        let prev = match self.before.head() {
+            Some(prev) => prev.upgrade(),
+            None => return false,
+        };
+
+        match prev {
+            Some(prev) => {
+                self.before = self.before.tail();
+                self.current = List { head: Some(prev) };
+                true
+            }
+            None => {
+                self.before = List::new();
+                false
+            }
+        }
+    }
+
+    // The list from the current element on, shared
+    pub fn rest(&self) -> List<T> {
+        self.current.clone()
+    }
+}
+
+// Adapter for code written against Stack: push and pop don't change any node, they
+// replace self with a new list, sharing the rest. pop can move the element out only when
+// nobody else holds the node, otherwise it has to clone it, hence T: Clone.
+impl<T: Clone> Stack<T> for List<T> {
+    fn push(&mut self, elem: T) {
+        *self = self.prepend(elem);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        let node = self.head.take()?;
+
+        match Rc::try_unwrap(node) {
+            Ok(node) => {
+                self.head = node.next;
+                Some(node.elem)
+            }
+            Err(node) => {
+                self.head = node.next.clone();
+                Some(node.elem.clone())
+            }
+        }
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.head()
+    }
+}
+
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        let mut head = self.head.take();
+        while let Some(node) = head {
+            // try_unwrap will return Ok(content) if this is last owner of that data
+            if let Ok(mut node) = Rc::try_unwrap(node) {
+                // set cursor to next element on the list
+                head = node.next.take();
+
+                // node goes out of scope, will call Drop
+                // there is node.next = None (because we called .take())
+                // so no recursive stack abuse threat here
+            } else {
+                // There is another list somewhere, which has this node as an element
+                // Don't delete it yet
+                break;
+            }
+        }
+    }
+}
+
+// Rc counts references with plain integers, so it's neither Send nor Sync, and
+// neither is the List. Arc does the same with atomic counters, swapping one for another
+// gives a list which can be shared between threads.
+// Send + Sync are auto traits - Arc<Node<T>> is Send + Sync when T: Send + Sync,
+// so the compiler figures out the rest, no unsafe impl needed.
+pub struct SyncList<T> {
+    head: SyncLink<T>,
+}
+
+type SyncLink<T> = Option<Arc<SyncNode<T>>>;
+
+struct SyncNode<T> {
+    elem: T,
+    next: SyncLink<T>,
+}
+
+impl<T> Default for SyncList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for SyncList<T> {
+    fn clone(&self) -> Self {
+        SyncList {
+            head: self.head.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T: proptest::arbitrary::Arbitrary> proptest::arbitrary::Arbitrary for SyncList<T> {
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <T as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = crate::arbitrary::ListStrategy<T, Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::arbitrary::list_strategy(args, |elems| {
+            elems
+                .into_iter()
+                .rev()
+                .fold(SyncList::new(), |list, elem| list.prepend(elem))
+        })
+    }
+}
+
+// Same as for List, with Arc counts
+impl<T: fmt::Debug> SyncList<T> {
+    pub fn to_dot(&self) -> String {
+        Self::to_dot_all(&[self])
+    }
 
-struct Node<T> {
-    elem: T,
-    next: Link<T>,
+    pub fn to_dot_all(lists: &[&SyncList<T>]) -> String {
+        let mut dot = Dot::new();
+        let mut drawn = BTreeSet::new();
+
+        for (i, list) in lists.iter().enumerate() {
+            let mut cur = list.head.as_ref();
+            dot.root(&format!("list {}", i), cur.map(Arc::as_ptr));
+
+            while let Some(node) = cur {
+                if !drawn.insert(Arc::as_ptr(node)) {
+                    break;
+                }
+
+                let count = format!("arc={}", Arc::strong_count(node));
+                dot.node(Arc::as_ptr(node), &node.elem, &count);
+                if let Some(next) = &node.next {
+                    dot.edge(Arc::as_ptr(node), Arc::as_ptr(next), "");
+                }
+                cur = node.next.as_ref();
+            }
+        }
+
+        dot.finish()
+    }
 }
 
-impl<T> List<T> {
-    pub fn new() -> Self {
-        List { head: None }
+impl<T: fmt::Debug> fmt::Debug for SyncList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for SyncList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for SyncList<T> {}
+
+impl<T: PartialOrd> PartialOrd for SyncList<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T: Ord> Ord for SyncList<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+// No lengths in the nodes, so count them first
+impl<T: Hash> Hash for SyncList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.iter().count().hash(state);
+        for elem in self.iter() {
+            elem.hash(state);
+        }
+    }
+}
+
+impl<T> SyncList<T> {
+    pub const fn new() -> Self {
+        SyncList { head: None }
     }
 
     pub fn prepend(&self, elem: T) -> Self {
-        List {
-            head: Some(Rc::new(Node {
-                elem: elem,
-                // This will increase a reference count of that node
+        SyncList {
+            head: Some(Arc::new(SyncNode {
+                elem,
                 next: self.head.clone(),
             })),
         }
     }
 
     pub fn head(&self) -> Option<&T> {
-        self.head.as_ref().map(|node| &node.elem )
+        self.head.as_ref().map(|node| &node.elem)
     }
 
     pub fn tail(&self) -> Self {
-        // and_then is the same as map, but it does not wrap result into Option,
-        // This is delegated to closure
-        // and_then returns f(x), where f: x -> Some(y)
-        let b = self.head.as_ref().and_then(|e| 
-            /* e.next is option itself, so no need to wrap it again */ 
-
-            // need to state .clone(), because Rc does not implement Copy, so move will occur.
-            // Implement Copy if you want your type to be copied instead of moving (like i32)
-            // Implement Clone if you want to give 'copy' capabilities on demand,
-            // having still default move semantics
-            e.next.clone());
-
-        // map returns Some(f(x)), where f: x -> y
-        let c = self.head.as_ref().map(
-            |e| {
-                e.next.clone()
-                // no matter what you will do here, map will wrap it up into Option.
-                // In particular Option::None will become Some(None)
-                   
-                // We need to return what's inside e.next (which is an Option itself)
-            });
-                
-        // use flatten to reduce nesting of Option, so Some(None) becomes None, Some(Some(x)) -> Some(x)
-        let c = c.flatten();
+        SyncList {
+            head: self.head.as_ref().and_then(|node| node.next.clone()),
+        }
+    }
 
-        List { head: c }
-    }   
+    pub fn iter(&self) -> SyncIter<'_, T> {
+        SyncIter {
+            next: self.head.as_deref(),
+        }
+    }
 }
 
-impl<T> Drop for List<T> {
+impl<T> Drop for SyncList<T> {
     fn drop(&mut self) {
         let mut head = self.head.take();
         while let Some(node) = head {
-            // try_unwrap will return Ok(content) if this is last owner of that data
-            if let Ok(mut node) = Rc::try_unwrap(node) {
-                // set cursor to next element on the list
+            // Cannot use try_unwrap as in List - two threads dropping lists sharing
+            // the node at the same time could both fail, and the last drop of the Arc
+            // would then free the rest of the list recursively.
+            // into_inner guarantees exactly one of them gets the node.
+            if let Some(mut node) = Arc::into_inner(node) {
                 head = node.next.take();
-
-                // node goes out of scope, will call Drop
-                // there is node.next = None (because we called .take())
-                // so no recursive stack abuse threat here
             } else {
-                // There is another list somewhere, which has this node as an element
-                // Don't delete it yet
                 break;
             }
         }
     }
 }
 
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for SyncList<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::serde_seq::serialize_seq(serializer, self.iter().count(), self.iter())
+    }
+}
+
+// Same as List, front to back, fresh nodes are unique so Arc::get_mut gives the next link
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for SyncList<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_seq::deserialize_seq(deserializer, "a list", |elems| {
+            let mut head = None;
+            let mut tail = &mut head;
+
+            for elem in elems {
+                let node = tail.insert(Arc::new(SyncNode { elem, next: None }));
+                tail = &mut Arc::get_mut(node).unwrap().next;
+            }
+
+            SyncList { head }
+        })
+    }
+}
+
+pub struct SyncIter<'a, T> {
+    next: Option<&'a SyncNode<T>>,
+}
+
+impl<'a, T> Iterator for SyncIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| {
+            self.next = node.next.as_deref();
+            &node.elem
+        })
+    }
+}
+
+// No lengths in SyncNode, so the size is unknown, but None is final
+impl<'a, T> FusedIterator for SyncIter<'a, T> {}
+
 #[cfg(test)]
 mod test {
-    use super::List;
+    use super::{List, SyncList};
+    use std::rc::Rc;
+    use std::thread;
 
     #[test]
     fn basics() {
@@ -105,7 +1090,6 @@ mod test {
         // Make sure empty tail works
         let list = list.tail();
         assert_eq!(list.head(), None);
-
     }
 
     #[test]
@@ -127,5 +1111,596 @@ mod test {
         println!("Still alive!");
     }
 
-}
+    #[test]
+    fn iter() {
+        let list = List::new().prepend(1).prepend(2).prepend(3);
+
+        let mut iter = list.iter();
+        assert_eq!(iter.next(), Some(&3));
+        assert_eq!(iter.next(), Some(&2));
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next(), None);
+
+        // Shared tail is iterated from both lists
+        let other = list.tail().prepend(20);
+        assert_eq!(
+            other.iter().map(|x| x * 2).collect::<Vec<_>>(),
+            vec![40, 4, 2]
+        );
+        assert_eq!(list.iter().filter(|&&x| x != 2).count(), 2);
+        assert_eq!(
+            list.iter()
+                .fold(String::new(), |acc, x| acc + &x.to_string()),
+            "321"
+        );
+    }
+
+    #[test]
+    fn rev() {
+        let list = List::new().prepend(1).prepend(2).prepend(3);
+
+        assert!(list.rev().iter().copied().eq(1..4));
+        assert_eq!(List::<i32>::new().rev().head(), None);
+
+        // Original is untouched
+        assert!(list.iter().copied().eq((1..4).rev()));
+    }
+
+    #[test]
+    fn concat() {
+        let first = List::new().prepend(2).prepend(1);
+        let second = List::new().prepend(4).prepend(3);
+
+        let list = first.concat(&second);
+        assert!(list.iter().copied().eq(1..5));
+        assert!(first.iter().copied().eq(1..3));
+
+        // Second list is shared, not copied
+        let shared = list.tail().tail();
+        assert!(Rc::ptr_eq(
+            shared.head.as_ref().unwrap(),
+            second.head.as_ref().unwrap()
+        ));
+
+        assert!(List::new().concat(&second).iter().copied().eq(3..5));
+        assert!(first.concat(&List::new()).iter().copied().eq(1..3));
+    }
+
+    #[test]
+    fn map() {
+        let list = List::new().prepend(1).prepend(2).prepend(3);
+        let mapped = list.map(|x| x.to_string());
+
+        assert_eq!(
+            mapped.iter().cloned().collect::<Vec<_>>(),
+            vec!["3", "2", "1"]
+        );
+    }
+
+    #[test]
+    fn filter() {
+        // 5 4 3 2 1
+        let list = (1..6).fold(List::new(), |list, i| list.prepend(i));
+
+        let odd = list.filter(|x| x % 2 == 1);
+        assert_eq!(odd.iter().copied().collect::<Vec<_>>(), vec![5, 3, 1]);
+
+        // Everything after 4 passes, so 3 2 1 are shared
+        let filtered = list.filter(|&x| x != 4);
+        assert_eq!(
+            filtered.iter().copied().collect::<Vec<_>>(),
+            vec![5, 3, 2, 1]
+        );
+        let suffix = list.tail().tail();
+        assert!(Rc::ptr_eq(
+            filtered.tail().head.as_ref().unwrap(),
+            suffix.head.as_ref().unwrap()
+        ));
+
+        // All pass - whole list is shared
+        let all = list.filter(|_| true);
+        assert!(Rc::ptr_eq(
+            all.head.as_ref().unwrap(),
+            list.head.as_ref().unwrap()
+        ));
+
+        assert_eq!(list.filter(|_| false).head(), None);
+    }
+
+    #[test]
+    fn operations_do_not_break_the_stack() {
+        let list = (0..100000).fold(List::new(), |list, i| list.prepend(i));
+
+        let list = list
+            .rev()
+            .concat(&list)
+            .map(|x| x + 1)
+            .filter(|x| x % 2 == 0);
+        assert_eq!(list.iter().count(), 100000);
+    }
+
+    #[test]
+    fn len_and_get() {
+        let empty = List::new();
+        assert_eq!(empty.len(), 0);
+        assert!(empty.is_empty());
+        assert_eq!(empty.get(0), None);
+
+        let list = empty.prepend(1).prepend(2).prepend(3);
+        assert_eq!(list.len(), 3);
+        assert!(!list.is_empty());
+        assert_eq!(list.tail().len(), 2);
+        assert_eq!(list.get(0), Some(&3));
+        assert_eq!(list.get(2), Some(&1));
+        assert_eq!(list.get(3), None);
+
+        // Lengths of lists built by the operations
+        let concat = list.concat(&list);
+        assert_eq!(concat.len(), 6);
+        assert_eq!(concat.tail().tail().tail().len(), 3);
+        assert_eq!(concat.get(5), Some(&1));
+        assert_eq!(list.map(|x| x * 2).tail().len(), 2);
+
+        let filtered = list.filter(|&x| x != 2);
+        assert_eq!(filtered.len(), 2);
+        assert_eq!(filtered.tail().len(), 1);
+        assert_eq!(list.rev().len(), 3);
+
+        // Every node has to be right, not only the head
+        let long = (0..100).fold(List::new(), |list, i| list.prepend(i));
+        let long = long.filter(|x| x % 3 != 0).concat(&long);
+        let mut node = long.head.clone();
+        let mut expected = long.len();
+        while let Some(current) = node {
+            assert_eq!(current.len, expected);
+            expected -= 1;
+            node = current.next.clone();
+        }
+        assert_eq!(expected, 0);
+    }
+
+    #[test]
+    fn sync_list_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        assert_send_sync::<SyncList<i32>>();
+    }
+
+    #[test]
+    fn from_iter_and_borrowing_for() {
+        let list: List<_> = (1..=4).collect();
+        assert!(list == plist![1, 2, 3, 4]);
+        assert_eq!(list.len(), 4);
+        assert_eq!(list.tail().len(), 3);
+
+        let mut sum = 0;
+        for elem in &list {
+            sum += elem;
+        }
+        assert_eq!(sum, 10);
+
+        let empty: List<i32> = None::<i32>.into_iter().collect();
+        assert!(empty.is_empty());
+
+        // Long one, dropped without recursion
+        let long: List<_> = (0..100000).collect();
+        assert_eq!(long.get(99999), Some(&99999));
+    }
+
+    #[test]
+    fn heap_size() {
+        // Two counters, the element, len and the next link
+        let node = 5 * std::mem::size_of::<usize>();
+
+        let tail = plist![3usize, 4];
+        let a = tail.prepend(2).prepend(1);
+        let b = tail.prepend(20);
+        assert_eq!(a.heap_size_in_bytes(), 4 * node);
+        assert_eq!(b.heap_size_in_bytes(), 3 * node);
+
+        // tail is held by a, b and itself
+        assert_eq!(a.unshared_heap_size_in_bytes(), 2 * node);
+        assert_eq!(b.unshared_heap_size_in_bytes(), node);
+        assert_eq!(tail.unshared_heap_size_in_bytes(), 0);
+
+        // A clone shares everything
+        let c = b.clone();
+        assert_eq!(c.unshared_heap_size_in_bytes(), 0);
+        drop(b);
+        drop(tail);
+        assert_eq!(c.unshared_heap_size_in_bytes(), node);
+        drop(a);
+        assert_eq!(c.unshared_heap_size_in_bytes(), 3 * node);
+    }
+
+    #[test]
+    fn const_new() {
+        const EMPTY: List<i32> = List::new();
+        static SHARED: SyncList<i32> = SyncList::new();
+
+        assert!(EMPTY.prepend(1) == plist![1]);
+        assert_eq!(SHARED.prepend(1).head(), Some(&1));
+        assert_eq!(SHARED.head(), None);
+    }
+
+    #[test]
+    fn sync_list_basics() {
+        let list = SyncList::new();
+        assert_eq!(list.head(), None);
+
+        let list = list.prepend(1).prepend(2).prepend(3);
+        assert_eq!(list.head(), Some(&3));
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![3, 2, 1]);
+
+        let list = list.tail().tail();
+        assert_eq!(list.head(), Some(&1));
+
+        let list = list.tail().tail();
+        assert_eq!(list.head(), None);
+    }
+
+    #[test]
+    fn sync_list_prepend_from_threads() {
+        let base = SyncList::new().prepend(1).prepend(0);
+
+        let handles = (0..8)
+            .map(|t| {
+                let base = base.clone();
+                thread::spawn(move || {
+                    let mut list = base;
+                    for i in 0..1000 {
+                        list = list.prepend(t * 1000 + i);
+                    }
+                    list
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for (t, handle) in handles.into_iter().enumerate() {
+            let list = handle.join().unwrap();
+            let t = t as i32;
+
+            assert_eq!(list.head(), Some(&(t * 1000 + 999)));
+            // Every thread built on top of the same shared base
+            assert!(list.iter().skip(1000).copied().eq(0..2));
+        }
+
+        assert!(base.iter().copied().eq(0..2));
+    }
+
+    #[test]
+    fn sync_list_break_the_stack() {
+        let mut list = SyncList::new();
+
+        for i in 1..100000 {
+            list = list.prepend(i);
+        }
+
+        // Drop copies of the same long list on multiple threads at once
+        let handles = (0..4)
+            .map(|_| {
+                let list = list.clone();
+                thread::spawn(move || drop(list))
+            })
+            .collect::<Vec<_>>();
+
+        drop(list);
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() {
+        let list = List::new().prepend(3).prepend(2).prepend(1);
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(json, "[1,2,3]");
+
+        let list = serde_json::from_str::<List<i32>>(&json).unwrap();
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &2, &3]);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.tail().len(), 2);
+
+        let list = SyncList::new().prepend(2).prepend(1);
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(json, "[1,2]");
+        let list = serde_json::from_str::<SyncList<i32>>(&json).unwrap();
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &2]);
+
+        assert!(serde_json::from_str::<List<i32>>("[1, null]").is_err());
+    }
+
+    #[test]
+    fn plist_macro() {
+        let list: List<i32> = plist![];
+        assert!(list.is_empty());
+
+        let list = plist![1, 2, 3];
+        assert_eq!(list.head(), Some(&1));
+        assert_eq!(list.len(), 3);
+        assert!(list.iter().eq(&[1, 2, 3]));
+    }
+
+    #[test]
+    fn exact_size_iter() {
+        let list = plist![1, 2, 3];
+        let mut iter = list.iter();
+        assert_eq!(iter.len(), 3);
+        iter.next();
+        assert_eq!(iter.len(), 2);
+        assert_eq!(iter.collect::<Vec<_>>(), vec![&2, &3]);
+    }
+
+    #[test]
+    fn eq_ord_hash() {
+        use std::collections::HashSet;
+
+        let shared = plist![2, 3];
+        let a = shared.prepend(1);
+        let b = plist![1, 2, 3];
+
+        assert!(a == b);
+        assert!(a.tail() == shared);
+        assert!(a != shared);
+        assert!(shared > a);
+        assert!(List::new() < a);
+
+        // Sharing nodes doesn't make lists equal, elements decide
+        let nan = plist![f64::NAN];
+        assert!(nan != nan.clone());
+        assert!(nan.ptr_eq(&nan.clone()));
+        assert!(!a.ptr_eq(&b));
+
+        let set: HashSet<_> = vec![a, b, shared].into_iter().collect();
+        assert_eq!(set.len(), 2);
+
+        let sync = SyncList::new().prepend(1);
+        assert!(sync == SyncList::new().prepend(1));
+        assert!(sync < SyncList::new().prepend(2));
+    }
 
+    #[test]
+    fn debug() {
+        assert_eq!(format!("{:?}", plist![1, 2, 3]), "[1, 2, 3]");
+        assert_eq!(format!("{:?}", SyncList::new().prepend(1)), "[1]");
+    }
+
+    #[test]
+    fn to_dot_shows_sharing() {
+        let shared = plist![2, 3];
+        let a = shared.prepend(1);
+        let b = shared.prepend(4);
+
+        let dot = List::to_dot_all(&[&a, &b]);
+        // 1, 4, 2, 3 - the tail once, not twice
+        assert_eq!(dot.matches("[label=").count(), 4);
+        // list 0 -> 1 -> 2 -> 3, list 1 -> 4 -> 2
+        assert_eq!(dot.matches("->").count(), 5);
+        // shared, a and b point at 2
+        assert!(dot.contains("2\\nrc=3"));
+        assert!(dot.contains("3\\nrc=1"));
+
+        assert!(SyncList::new().prepend(1).to_dot().contains("1\\narc=1"));
+    }
+
+    #[cfg(feature = "proptest")]
+    proptest::proptest! {
+        #[test]
+        fn arbitrary_shared_lists_share_tail((tail, lists) in super::arbitrary_shared::<u8>(2..5)) {
+            proptest::prop_assert!(lists.len() >= 2);
+
+            // Walked down to the length of the tail, every list has to be at the very
+            // same nodes, not just equal elements
+            for list in lists {
+                let mut list = list;
+                while list.len() > tail.len() {
+                    list = list.tail();
+                }
+
+                let same = match (&list.head, &tail.head) {
+                    (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+                    (None, None) => true,
+                    _ => false,
+                };
+                proptest::prop_assert!(same);
+            }
+        }
+    }
+
+    #[test]
+    fn zipper() {
+        let list = plist![1, 2, 3, 4];
+        let mut zipper = list.zipper();
+        assert_eq!(zipper.focus(), Some(&1));
+        assert!(!zipper.move_prev());
+
+        assert!(zipper.move_next());
+        assert_eq!(zipper.index(), 1);
+        zipper.replace(20).unwrap();
+        zipper.move_next();
+        zipper.insert(25);
+
+        let edited = zipper.to_list();
+        assert!(edited == plist![1, 20, 25, 3, 4]);
+        assert!(list == plist![1, 2, 3, 4]);
+
+        // 3 and 4 are shared, 1 had to be copied
+        let node = |list: &List<i32>, i: usize| {
+            let mut link = list.head.clone();
+            for _ in 0..i {
+                link = link.unwrap().next.clone();
+            }
+            link.unwrap()
+        };
+        assert!(Rc::ptr_eq(&node(&edited, 3), &node(&list, 2)));
+        assert!(!Rc::ptr_eq(&node(&edited, 0), &node(&list, 0)));
+
+        // Going back over the edits copies, going back over untouched nodes shares
+        zipper.move_prev();
+        zipper.move_prev();
+        assert_eq!(zipper.focus(), Some(&1));
+        assert!(zipper.to_list() == edited);
+
+        // No edits - the same list
+        let mut zipper = list.zipper();
+        while zipper.move_next() {}
+        assert_eq!(zipper.focus(), None);
+        assert_eq!(zipper.replace(5), Err(5));
+        assert!(!zipper.remove());
+        assert!(super::same_link(&zipper.to_list().head, &list.head));
+
+        // Edit at the end, everything before is copied
+        zipper.insert(5);
+        zipper.move_prev();
+        assert!(zipper.remove());
+        assert!(zipper.to_list() == plist![1, 2, 3, 5]);
+    }
+
+    #[test]
+    fn long_zipper_drop() {
+        let mut zipper = (0..1_000_000).collect::<List<_>>().zipper();
+        while zipper.move_next() {}
+
+        // Source list is gone, zipper is the last owner of all the nodes
+        drop(zipper);
+    }
+
+    #[test]
+    fn cursor() {
+        let list = plist![1, 2, 3, 4];
+        let mut cursor = list.cursor();
+        assert_eq!(cursor.current(), Some(&1));
+        assert!(!cursor.move_prev());
+
+        while cursor.move_next() {}
+        assert_eq!(cursor.index(), 4);
+        assert_eq!(cursor.current(), None);
+
+        assert!(cursor.move_prev());
+        assert!(cursor.move_prev());
+        assert_eq!(cursor.current(), Some(&3));
+        assert_eq!(cursor.index(), 2);
+        assert!(cursor.rest() == plist![3, 4]);
+
+        // Back links don't count as owners
+        let head = list.head.clone().unwrap();
+        assert_eq!(Rc::strong_count(&head), 2);
+        assert_eq!(Rc::weak_count(&head), 1);
+    }
+
+    #[test]
+    fn cursor_does_not_keep_prefix() {
+        let elem = Rc::new(());
+        let list = List::new()
+            .prepend(elem.clone())
+            .prepend(elem.clone())
+            .prepend(elem.clone());
+
+        let mut cursor = list.cursor();
+        cursor.move_next();
+        cursor.move_next();
+        let back = cursor.clone();
+
+        // The cursor holds the last node, the list goes away with the other two
+        drop(list);
+        assert_eq!(Rc::strong_count(&elem), 2);
+        assert!(!cursor.move_prev());
+        assert_eq!(cursor.index(), 0);
+        assert!(cursor.current().is_some());
+
+        // No cycles - once the cursors are gone, so is everything
+        drop(cursor);
+        drop(back);
+        assert_eq!(Rc::strong_count(&elem), 1);
+
+        // Last owner of a long list, drops it without recursion
+        let mut cursor = (0..100000)
+            .fold(List::new(), |list, i| list.prepend(i))
+            .cursor();
+        cursor.move_next();
+        drop(cursor);
+    }
+
+    #[test]
+    fn update_and_set() {
+        let list = plist![1, 2, 3, 4];
+
+        let updated = list.update(1, |elem| elem * 10).unwrap();
+        assert!(updated == plist![1, 20, 3, 4]);
+        assert_eq!(updated.len(), 4);
+        assert!(list == plist![1, 2, 3, 4]);
+
+        // 3 is pointed at by 2 in list and by 20 in updated, 1 and 2 are not shared
+        let third = list.tail().tail();
+        assert_eq!(Rc::strong_count(third.head.as_ref().unwrap()), 3);
+        assert_eq!(Rc::strong_count(list.head.as_ref().unwrap()), 1);
+
+        let set = updated.set(3, 40).unwrap();
+        assert!(set == plist![1, 20, 3, 40]);
+        assert_eq!(set.tail().len(), 3);
+
+        assert!(list.set(4, 5).is_none());
+        assert!(List::<i32>::new().update(0, |elem| *elem).is_none());
+    }
+
+    #[test]
+    fn take_drop_split_at() {
+        let list = plist![1, 2, 3, 4];
+
+        let rest = list.drop(2);
+        assert!(rest == plist![3, 4]);
+        // The very same nodes
+        assert!(super::same_link(&rest.head, &list.tail().tail().head));
+        assert!(list.drop(10).is_empty());
+        assert!(super::same_link(&list.drop(0).head, &list.head));
+
+        let first = list.take(2);
+        assert!(first == plist![1, 2]);
+        assert_eq!(first.tail().len(), 1);
+        assert!(list.take(0).is_empty());
+        // All of it - nothing to copy
+        assert!(super::same_link(&list.take(4).head, &list.head));
+
+        let (front, back) = list.split_at(3);
+        assert!(front == plist![1, 2, 3]);
+        assert!(back == plist![4]);
+        let (front, back) = list.split_at(7);
+        assert!(front == list);
+        assert!(back.is_empty());
+    }
+
+    #[test]
+    fn zip_with() {
+        let a: List<i32> = (1..=3).collect();
+        let b: List<i32> = (1..=5).map(|i| i * 10).collect();
+
+        let zipped = a.zip_with(&b, |a, b| a + b);
+        assert!(zipped.iter().copied().eq([11, 22, 33]));
+        assert_eq!(zipped.len(), 3);
+        assert_eq!(b.zip_with(&a, |b, a| b - a).len(), 3);
+
+        // A list and its own tail, both stay as they were
+        let tail = b.tail();
+        assert!(b.zip_with(&tail, |x, y| (*x, *y)).iter().eq(&[
+            (10, 20),
+            (20, 30),
+            (30, 40),
+            (40, 50)
+        ]));
+        assert_eq!(b.len(), 5);
+
+        assert!(a.zip_with(&List::<i32>::new(), |a, b| a + b).is_empty());
+    }
+
+    #[test]
+    fn collect_copied() {
+        let list: List<i32> = [1, 2, 3].iter().collect();
+        assert_eq!(list.len(), 3);
+        assert!(list.iter().copied().eq(1..=3));
+
+        let copy: List<i32> = list.iter().collect();
+        assert_eq!(copy, list);
+    }
+}
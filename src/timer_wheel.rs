@@ -0,0 +1,296 @@
+use alloc::vec::Vec;
+use core::cell::Cell;
+use core::pin::Pin;
+
+use crate::intrusive::{IntrusiveList, Link, Linked};
+
+// Hashed timer wheel - the usual way to keep lots of timeouts (network connections,
+// retries), where most of them are cancelled before they fire.
+//
+// Time is counted in ticks, whatever a tick means to the caller. There are a fixed number
+// of slots, a timer due at tick t goes into slot t % slots, which is an intrusive list.
+// Timers are owned by the caller, the wheel only links them in, so:
+// - schedule is O(1), push to the back of the slot, no allocation
+// - cancel is O(1), the pinned timer is its own handle, and knows its neighbours
+// - advance visits only the slots of the ticks that passed, timers in them that are due
+//   later (a lap of the wheel or more) stay where they are
+//
+// Like with IntrusiveList, timers are borrowed for 'a, they outlive the wheel. Dropping
+// the wheel unlinks all of them.
+pub struct TimerWheel<'a, T> {
+    slots: Vec<IntrusiveList<'a, Timer<T>>>,
+    // First tick which wasn't processed by advance yet
+    next_tick: u64,
+    len: usize,
+}
+
+pub struct Timer<T> {
+    data: T,
+    // Valid while the timer is linked
+    deadline: Cell<u64>,
+    link: Link<Timer<T>>,
+}
+
+unsafe impl<T> Linked for Timer<T> {
+    fn link(&self) -> &Link<Self> {
+        &self.link
+    }
+}
+
+impl<T> Timer<T> {
+    pub const fn new(data: T) -> Self {
+        Timer {
+            data,
+            deadline: Cell::new(0),
+            link: Link::new(),
+        }
+    }
+
+    pub fn data(&self) -> &T {
+        &self.data
+    }
+
+    pub fn is_scheduled(&self) -> bool {
+        self.link.is_linked()
+    }
+
+    pub fn deadline(&self) -> Option<u64> {
+        if self.is_scheduled() {
+            Some(self.deadline.get())
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T> TimerWheel<'a, T> {
+    // More slots means fewer timers per slot that aren't due yet, see advance
+    pub fn new(slots: usize) -> Self {
+        assert!(slots > 0, "wheel has to have at least one slot");
+
+        TimerWheel {
+            slots: (0..slots).map(|_| IntrusiveList::new()).collect(),
+            next_tick: 0,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Current time of the wheel: everything due at or before it has fired.
+    // u64::MAX - 1 at most, see the end of advance.
+    pub fn now(&self) -> Option<u64> {
+        self.next_tick.checked_sub(1)
+    }
+
+    fn slot(&self, tick: u64) -> usize {
+        (tick % self.slots.len() as u64) as usize
+    }
+
+    // Timer fires on the first advance to deadline or later. Deadlines in the past fire
+    // on the next advance. Panics if the timer is already scheduled (in this or any
+    // other wheel) - cancel it first.
+    pub fn schedule(&mut self, timer: Pin<&'a Timer<T>>, deadline: u64) {
+        let deadline = deadline.max(self.next_tick);
+        let slot = self.slot(deadline);
+
+        self.slots[slot].push_back(timer);
+        timer.deadline.set(deadline);
+        self.len += 1;
+    }
+
+    // O(1). Returns false if the timer is not scheduled in this wheel.
+    pub fn cancel(&mut self, timer: Pin<&'a Timer<T>>) -> bool {
+        if !timer.is_scheduled() {
+            return false;
+        }
+
+        let slot = self.slot(timer.deadline.get());
+        let removed = self.slots[slot].remove(timer);
+        if removed {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    // Moves the wheel to now, and returns the timers which fired, unlinked, so they can be
+    // scheduled again. Every slot is visited at most once, so after a jump longer than a lap
+    // timers come out in slot order, not exactly by deadline. Going back in time does nothing.
+    pub fn advance(&mut self, now: u64) -> Vec<Pin<&'a Timer<T>>> {
+        let mut fired = Vec::new();
+        if now < self.next_tick {
+            return fired;
+        }
+
+        let ticks = (now - self.next_tick).saturating_add(1);
+        let slots = ticks.min(self.slots.len() as u64);
+
+        // Inclusive, the last tick is at most now, so it can be u64::MAX without overflow
+        for tick in self.next_tick..=self.next_tick + (slots - 1) {
+            let slot = self.slot(tick);
+            let list = &mut self.slots[slot];
+
+            // Due ones go out, the rest goes back in the same order
+            for _ in 0..list.len() {
+                let timer = list.pop_front().unwrap();
+                if timer.deadline.get() <= now {
+                    fired.push(timer);
+                } else {
+                    list.push_back(timer);
+                }
+            }
+        }
+
+        // There is no tick after u64::MAX, the wheel stays at it, and every advance to it
+        // fires what was scheduled since
+        self.next_tick = now.saturating_add(1);
+        self.len -= fired.len();
+        fired
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Timer, TimerWheel};
+    use std::pin::Pin;
+
+    fn timers(n: u32) -> Vec<Pin<Box<Timer<u32>>>> {
+        (0..n).map(|i| Box::pin(Timer::new(i))).collect()
+    }
+
+    fn data(fired: Vec<Pin<&Timer<u32>>>) -> Vec<u32> {
+        fired.iter().map(|timer| *timer.data()).collect()
+    }
+
+    #[test]
+    fn basics() {
+        let timers = timers(4);
+        let mut wheel = TimerWheel::new(8);
+        assert!(wheel.is_empty());
+        assert_eq!(wheel.now(), None);
+
+        wheel.schedule(timers[0].as_ref(), 3);
+        wheel.schedule(timers[1].as_ref(), 1);
+        wheel.schedule(timers[2].as_ref(), 3);
+        // Same slot as 3, a lap later
+        wheel.schedule(timers[3].as_ref(), 11);
+        assert_eq!(wheel.len(), 4);
+        assert_eq!(timers[3].deadline(), Some(11));
+
+        assert!(data(wheel.advance(0)).is_empty());
+        assert_eq!(data(wheel.advance(2)), [1]);
+        assert_eq!(wheel.now(), Some(2));
+        assert!(!timers[1].is_scheduled());
+
+        assert_eq!(data(wheel.advance(3)), [0, 2]);
+        assert!(data(wheel.advance(10)).is_empty());
+        assert_eq!(data(wheel.advance(11)), [3]);
+        assert!(wheel.is_empty());
+
+        // Back in time, nothing happens
+        assert!(data(wheel.advance(5)).is_empty());
+        assert_eq!(wheel.now(), Some(11));
+    }
+
+    #[test]
+    fn cancel() {
+        let timers = timers(3);
+        let mut other = TimerWheel::new(4);
+        let mut wheel = TimerWheel::new(4);
+
+        assert!(!wheel.cancel(timers[0].as_ref()));
+
+        wheel.schedule(timers[0].as_ref(), 2);
+        wheel.schedule(timers[1].as_ref(), 2);
+        other.schedule(timers[2].as_ref(), 2);
+
+        assert!(wheel.cancel(timers[0].as_ref()));
+        assert!(!wheel.cancel(timers[0].as_ref()));
+        assert!(!wheel.cancel(timers[2].as_ref()));
+        assert_eq!(timers[0].deadline(), None);
+        assert_eq!(wheel.len(), 1);
+
+        assert_eq!(data(wheel.advance(2)), [1]);
+        assert_eq!(data(other.advance(2)), [2]);
+    }
+
+    #[test]
+    fn past_deadlines_and_long_jumps() {
+        let timers = timers(5);
+        let mut wheel = TimerWheel::new(4);
+        wheel.advance(10);
+
+        // Already due, fires on the next advance
+        wheel.schedule(timers[0].as_ref(), 3);
+        assert_eq!(timers[0].deadline(), Some(11));
+        assert_eq!(data(wheel.advance(11)), [0]);
+
+        // Many laps at once, every slot is visited once
+        for (i, timer) in timers.iter().enumerate() {
+            wheel.schedule(timer.as_ref(), 12 + 5 * i as u64);
+        }
+        let mut fired = data(wheel.advance(1000));
+        fired.sort();
+        assert_eq!(fired, [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn periodic() {
+        let timer = Box::pin(Timer::new(()));
+        let mut wheel = TimerWheel::new(3);
+        wheel.schedule(timer.as_ref(), 5);
+
+        let mut fired_at = Vec::new();
+        for now in 0..=20 {
+            for timer in wheel.advance(now) {
+                fired_at.push(now);
+                wheel.schedule(timer, now + 5);
+            }
+        }
+        assert_eq!(fired_at, [5, 10, 15, 20]);
+    }
+
+    #[test]
+    fn end_of_time() {
+        let timers = timers(3);
+        let mut wheel = TimerWheel::new(4);
+        wheel.schedule(timers[0].as_ref(), u64::MAX - 1);
+        wheel.schedule(timers[1].as_ref(), u64::MAX);
+        wheel.advance(u64::MAX - 2);
+
+        assert_eq!(data(wheel.advance(u64::MAX)), [0, 1]);
+
+        // Still in the right slot, fires on the next advance
+        wheel.schedule(timers[2].as_ref(), 5);
+        assert_eq!(timers[2].deadline(), Some(u64::MAX));
+        assert_eq!(data(wheel.advance(u64::MAX)), [2]);
+        assert!(wheel.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "already linked")]
+    fn schedule_twice() {
+        let timer = Box::pin(Timer::new(()));
+        let mut wheel = TimerWheel::new(3);
+        wheel.schedule(timer.as_ref(), 5);
+        wheel.schedule(timer.as_ref(), 6);
+    }
+
+    #[test]
+    fn drop_unlinks() {
+        let timer = Box::pin(Timer::new(()));
+        {
+            let mut wheel = TimerWheel::new(3);
+            wheel.schedule(timer.as_ref(), 5);
+        }
+
+        assert!(!timer.is_scheduled());
+        TimerWheel::new(3).schedule(timer.as_ref(), 1);
+    }
+}
@@ -0,0 +1,99 @@
+// What the lists have in common, so code can be written once, for any of them -
+// benchmarks (benches/lists.rs), tests, or a user who wants to pick the list later.
+//
+// Every list implements the traits next to its other impls. Method names are the same
+// as the inherent ones, which win in method calls, so the traits are only seen in
+// generic code: fn f<S: Stack<T>>(stack: &mut S).
+
+// Last in, first out
+pub trait Stack<T> {
+    fn push(&mut self, elem: T);
+    fn pop(&mut self) -> Option<T>;
+    // Top of the stack, what pop would return
+    fn peek(&self) -> Option<&T>;
+}
+
+// First in, first out
+pub trait Queue<T> {
+    fn enqueue(&mut self, elem: T);
+    fn dequeue(&mut self) -> Option<T>;
+    // What dequeue would return
+    fn front(&self) -> Option<&T>;
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Queue, Stack};
+    use crate::{arena, fifth, first, second, small, third, unrolled, xor_list};
+
+    fn check_stack<S: Stack<i32>>(mut stack: S) {
+        assert_eq!(stack.pop(), None);
+        assert_eq!(stack.peek(), None);
+
+        for i in 0..5 {
+            stack.push(i);
+        }
+        assert_eq!(stack.peek(), Some(&4));
+        assert_eq!(stack.pop(), Some(4));
+        assert_eq!(stack.pop(), Some(3));
+
+        stack.push(10);
+        assert_eq!(stack.peek(), Some(&10));
+        assert_eq!(stack.pop(), Some(10));
+        assert_eq!(stack.pop(), Some(2));
+        assert_eq!(stack.pop(), Some(1));
+        assert_eq!(stack.pop(), Some(0));
+        assert_eq!(stack.pop(), None);
+    }
+
+    fn check_queue<Q: Queue<i32>>(mut queue: Q) {
+        assert_eq!(queue.dequeue(), None);
+        assert_eq!(queue.front(), None);
+
+        for i in 0..5 {
+            queue.enqueue(i);
+        }
+        assert_eq!(queue.front(), Some(&0));
+        assert_eq!(queue.dequeue(), Some(0));
+        assert_eq!(queue.dequeue(), Some(1));
+
+        queue.enqueue(10);
+        assert_eq!(queue.front(), Some(&2));
+        assert_eq!(queue.dequeue(), Some(2));
+        assert_eq!(queue.dequeue(), Some(3));
+        assert_eq!(queue.dequeue(), Some(4));
+        assert_eq!(queue.dequeue(), Some(10));
+        assert_eq!(queue.dequeue(), None);
+    }
+
+    #[test]
+    fn stacks() {
+        check_stack(first::List::new());
+        check_stack(second::List::new());
+        check_stack(third::List::new());
+        check_stack(unrolled::List::<_, 2>::new());
+        check_stack(small::List::<_, 2>::new());
+    }
+
+    #[test]
+    fn queues() {
+        check_queue(fifth::List::new());
+        check_queue(arena::List::new());
+        check_queue(xor_list::List::new());
+    }
+
+    #[test]
+    fn third_shares_nodes() {
+        // Pop can't move an element out of a node other lists still use, it clones it
+        let mut stack: third::List<_> = third::List::new();
+        Stack::push(&mut stack, String::from("a"));
+        Stack::push(&mut stack, String::from("b"));
+        let copy = stack.clone();
+
+        assert_eq!(Stack::pop(&mut stack), Some(String::from("b")));
+        assert_eq!(Stack::pop(&mut stack), Some(String::from("a")));
+        assert_eq!(Stack::pop(&mut stack), None);
+        assert_eq!(copy.len(), 2);
+        assert_eq!(copy.head().map(String::as_str), Some("b"));
+    }
+}
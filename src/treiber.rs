@@ -0,0 +1,368 @@
+use alloc::boxed::Box;
+use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
+use core::ptr::{self, null_mut};
+
+use crate::sync::{AtomicPtr, AtomicUsize, Ordering};
+
+// Lock-free stack (Treiber stack), same idea as second::List, but head is an AtomicPtr,
+// and push/pop swap it with compare_exchange in a loop, until no one else
+// changed it in the meantime.
+//
+// The hard part is freeing popped nodes. Other threads in pop could have loaded the same
+// head, and still read its next pointer (their CAS fails later, but the read happens).
+// So the node cannot be freed right away. Nodes are freed only when no one else is in pop:
+// - threads_in_pop counts threads inside of pop
+// - if we are the only one there, nobody else can see our node, free it
+// - otherwise add it to to_be_deleted list, whoever is the last one in pop, frees the list
+// (the reference counting approach from "C++ Concurrency in Action", chapter 7)
+//
+// Since no node is freed while someone can still see it, its address cannot be reused
+// by a new node either, so compare_exchange cannot be fooled by the ABA problem.
+pub struct Stack<T> {
+    head: AtomicPtr<Node<T>>,
+    threads_in_pop: AtomicUsize,
+    to_be_deleted: AtomicPtr<Node<T>>,
+    // Raw pointers do not tell the compiler that we own T's (drop check)
+    _marker: PhantomData<Box<Node<T>>>,
+}
+
+struct Node<T> {
+    // Moved out by pop, before the node itself is freed
+    elem: ManuallyDrop<T>,
+    // Never changes once the node is pushed, so can be read without synchronization
+    next: *mut Node<T>,
+    // Link in the to_be_deleted list, separate from next, because other threads
+    // may still read next
+    next_to_delete: *mut Node<T>,
+}
+
+// Elements are moved in and out, never shared between threads, so T: Send is enough
+// (same as for Mutex<T>)
+unsafe impl<T: Send> Send for Stack<T> {}
+unsafe impl<T: Send> Sync for Stack<T> {}
+
+impl<T> Default for Stack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// loom's atomics can't be made in a const fn, so new is const (and a stack can be
+// a static) only with the real ones
+macro_rules! empty_stack {
+    () => {
+        Stack {
+            head: AtomicPtr::new(null_mut()),
+            threads_in_pop: AtomicUsize::new(0),
+            to_be_deleted: AtomicPtr::new(null_mut()),
+            _marker: PhantomData,
+        }
+    };
+}
+
+impl<T> Stack<T> {
+    #[cfg(not(loom))]
+    pub const fn new() -> Self {
+        empty_stack!()
+    }
+
+    #[cfg(loom)]
+    pub fn new() -> Self {
+        empty_stack!()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Ordering::Acquire).is_null()
+    }
+
+    pub fn push(&self, elem: T) {
+        let node = Box::into_raw(Box::new(Node {
+            elem: ManuallyDrop::new(elem),
+            next: null_mut(),
+            next_to_delete: null_mut(),
+        }));
+
+        let mut head = self.head.load(Ordering::Relaxed);
+
+        loop {
+            // Node is not visible to anyone yet
+            unsafe {
+                (*node).next = head;
+            }
+
+            // Release - whoever loads the node from head, sees its content.
+            // On failure we get the current head, just try again
+            match self
+                .head
+                .compare_exchange_weak(head, node, Ordering::Release, Ordering::Relaxed)
+            {
+                Ok(_) => return,
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        self.threads_in_pop.fetch_add(1, Ordering::SeqCst);
+
+        // SeqCst on head as well - when try_reclaim sees threads_in_pop == 1, any thread
+        // which enters pop later, must see the head already without our node
+        let mut head = self.head.load(Ordering::SeqCst);
+
+        loop {
+            if head.is_null() {
+                self.threads_in_pop.fetch_sub(1, Ordering::SeqCst);
+                return None;
+            }
+
+            // head is not freed, as long as we are counted in threads_in_pop
+            let next = unsafe { (*head).next };
+
+            match self
+                .head
+                .compare_exchange_weak(head, next, Ordering::SeqCst, Ordering::SeqCst)
+            {
+                Ok(_) => break,
+                Err(current) => head = current,
+            }
+        }
+
+        // We won, node is unlinked, nobody else touches elem
+        let elem = unsafe { ManuallyDrop::into_inner(ptr::read(&(*head).elem)) };
+
+        self.try_reclaim(head);
+
+        Some(elem)
+    }
+
+    fn try_reclaim(&self, old_head: *mut Node<T>) {
+        if self.threads_in_pop.load(Ordering::SeqCst) == 1 {
+            // We are alone, claim the pending list
+            let to_delete = self.to_be_deleted.swap(null_mut(), Ordering::SeqCst);
+
+            if self.threads_in_pop.fetch_sub(1, Ordering::SeqCst) == 1 {
+                // Still alone, nobody could see nodes from the list
+                unsafe {
+                    delete_nodes(to_delete);
+                }
+            } else if !to_delete.is_null() {
+                // Someone came in, in the meantime, and may have seen them, put them back
+                self.chain_pending_nodes(to_delete);
+            }
+
+            // Others, who came in after we unlinked old_head, can't see it anymore
+            unsafe {
+                drop(Box::from_raw(old_head));
+            }
+        } else {
+            self.chain_pending_nodes(old_head);
+            self.threads_in_pop.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    // Adds the list of nodes linked with next_to_delete to to_be_deleted
+    fn chain_pending_nodes(&self, first: *mut Node<T>) {
+        let mut last = first;
+
+        unsafe {
+            while !(*last).next_to_delete.is_null() {
+                last = (*last).next_to_delete;
+            }
+        }
+
+        let mut pending = self.to_be_deleted.load(Ordering::SeqCst);
+
+        loop {
+            unsafe {
+                (*last).next_to_delete = pending;
+            }
+
+            match self.to_be_deleted.compare_exchange_weak(
+                pending,
+                first,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(_) => return,
+                Err(current) => pending = current,
+            }
+        }
+    }
+}
+
+// Frees nodes linked with next_to_delete, elements were already moved out
+unsafe fn delete_nodes<T>(mut node: *mut Node<T>) {
+    while !node.is_null() {
+        let next = (*node).next_to_delete;
+        drop(Box::from_raw(node));
+        node = next;
+    }
+}
+
+impl<T> Drop for Stack<T> {
+    fn drop(&mut self) {
+        // &mut self - nobody else uses the stack anymore
+        while self.pop().is_some() {}
+
+        unsafe {
+            delete_nodes(self.to_be_deleted.load(Ordering::Relaxed));
+        }
+    }
+}
+
+#[cfg(all(test, not(loom)))]
+mod test {
+    use super::Stack;
+    use std::collections::HashSet;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn basics() {
+        let stack = Stack::new();
+        assert!(stack.is_empty());
+        assert_eq!(stack.pop(), None);
+
+        stack.push(1);
+        stack.push(2);
+        stack.push(3);
+        assert!(!stack.is_empty());
+
+        assert_eq!(stack.pop(), Some(3));
+        assert_eq!(stack.pop(), Some(2));
+
+        stack.push(4);
+        assert_eq!(stack.pop(), Some(4));
+        assert_eq!(stack.pop(), Some(1));
+        assert_eq!(stack.pop(), None);
+    }
+
+    #[test]
+    fn static_stack() {
+        // No Arc needed to share it
+        static STACK: Stack<usize> = Stack::new();
+
+        let handles: Vec<_> = (0..4)
+            .map(|i| thread::spawn(move || STACK.push(i)))
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let mut popped: Vec<_> = std::iter::from_fn(|| STACK.pop()).collect();
+        popped.sort();
+        assert_eq!(popped, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn drop_frees_elements() {
+        let elem = Arc::new(());
+
+        {
+            let stack = Stack::new();
+            for _ in 0..10 {
+                stack.push(elem.clone());
+            }
+            stack.pop();
+        }
+
+        assert_eq!(Arc::strong_count(&elem), 1);
+    }
+
+    #[test]
+    fn concurrent_push_pop() {
+        const THREADS: usize = 8;
+        const PER_THREAD: usize = 10_000;
+
+        let stack = Arc::new(Stack::new());
+
+        let handles = (0..THREADS)
+            .map(|t| {
+                let stack = stack.clone();
+                thread::spawn(move || {
+                    let mut popped = Vec::new();
+
+                    for i in 0..PER_THREAD {
+                        stack.push(t * PER_THREAD + i);
+
+                        if i % 2 == 0 {
+                            popped.extend(stack.pop());
+                        }
+                    }
+
+                    popped
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut seen = HashSet::new();
+
+        for handle in handles {
+            for elem in handle.join().unwrap() {
+                // Every element is popped exactly once
+                assert!(seen.insert(elem));
+            }
+        }
+
+        while let Some(elem) = stack.pop() {
+            assert!(seen.insert(elem));
+        }
+
+        assert_eq!(seen.len(), THREADS * PER_THREAD);
+    }
+}
+
+#[cfg(all(test, loom))]
+mod loom_test {
+    use super::Stack;
+    use loom::sync::Arc;
+    use loom::thread;
+
+    #[test]
+    fn concurrent_pop() {
+        loom::model(|| {
+            let stack = Arc::new(Stack::new());
+            stack.push(1);
+            stack.push(2);
+
+            let other = {
+                let stack = stack.clone();
+                thread::spawn(move || stack.pop())
+            };
+
+            let mine = stack.pop();
+            let other = other.join().unwrap();
+
+            let mut popped = vec![mine.unwrap(), other.unwrap()];
+            popped.sort();
+            assert_eq!(popped, vec![1, 2]);
+            assert_eq!(stack.pop(), None);
+        });
+    }
+
+    #[test]
+    fn push_while_popping() {
+        loom::model(|| {
+            let stack = Arc::new(Stack::new());
+            stack.push(1);
+
+            let pusher = {
+                let stack = stack.clone();
+                thread::spawn(move || stack.push(2))
+            };
+
+            let popper = {
+                let stack = stack.clone();
+                thread::spawn(move || stack.pop())
+            };
+
+            pusher.join().unwrap();
+            let first = popper.join().unwrap().unwrap();
+            let second = stack.pop().unwrap();
+
+            assert!(first == 1 && second == 2 || first == 2 && second == 1);
+            assert_eq!(stack.pop(), None);
+        });
+    }
+}
@@ -0,0 +1,405 @@
+use alloc::boxed::Box;
+use core::fmt;
+use core::iter::FusedIterator;
+use core::mem::MaybeUninit;
+use core::ptr;
+
+use crate::traits::Stack;
+
+// Unrolled linked list - a stack like second::List, but every node keeps up to N elements
+// in an array, instead of one. Iteration reads N elements in a row from one place in memory,
+// instead of jumping to a separate allocation for every element. That's one allocation
+// per N pushes too.
+//
+// Top of the stack is the last element of the first node:
+// head -> [1, 2, 3] -> [4, 5, 6, 7] -> None    (N = 4)
+// pop() gives 3, push() fills the first node, and allocates a new one only when it's full.
+// Nodes other than the first one are always full. Elements are only ever removed from the
+// top, so the list can't end up with half-empty nodes in the middle, and there's nothing
+// to compact - at most one node is partially used, ever.
+pub struct List<T, const N: usize = 16> {
+    head: Link<T, N>,
+}
+
+type Link<T, const N: usize> = Option<Box<Node<T, N>>>;
+
+struct Node<T, const N: usize> {
+    // elems[..len] are initialized
+    elems: [MaybeUninit<T>; N],
+    len: usize,
+    next: Link<T, N>,
+}
+
+impl<T, const N: usize> Node<T, N> {
+    fn new(next: Link<T, N>) -> Box<Self> {
+        Box::new(Node {
+            elems: [const { MaybeUninit::uninit() }; N],
+            len: 0,
+            next,
+        })
+    }
+
+    fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    fn as_slice(&self) -> &[T] {
+        unsafe { &*(&self.elems[..self.len] as *const [MaybeUninit<T>] as *const [T]) }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { &mut *(&mut self.elems[..self.len] as *mut [MaybeUninit<T>] as *mut [T]) }
+    }
+
+    fn push(&mut self, elem: T) {
+        self.elems[self.len].write(elem);
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> T {
+        self.len -= 1;
+        unsafe { self.elems[self.len].assume_init_read() }
+    }
+}
+
+impl<T, const N: usize> Drop for Node<T, N> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::drop_in_place(self.as_mut_slice());
+        }
+    }
+}
+
+impl<T, const N: usize> Default for List<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> List<T, N> {
+    pub const fn new() -> Self {
+        assert!(N > 0, "node capacity has to be at least 1");
+
+        List { head: None }
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.head
+            .as_ref()
+            .map(|node| node.as_slice().last().unwrap())
+    }
+
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self.head
+            .as_mut()
+            .map(|node| node.as_mut_slice().last_mut().unwrap())
+    }
+
+    pub fn push(&mut self, elem: T) {
+        match &mut self.head {
+            Some(node) if !node.is_full() => node.push(elem),
+            _ => {
+                let mut node = Node::new(self.head.take());
+                node.push(elem);
+                self.head = Some(node);
+            }
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        let node = self.head.as_mut()?;
+        let elem = node.pop();
+
+        // Nodes are never empty
+        if node.len == 0 {
+            let mut node = self.head.take().unwrap();
+            self.head = node.next.take();
+        }
+
+        Some(elem)
+    }
+
+    // Nodes are allocated whole, N elements each, however many of them are used,
+    // see heap_size.rs
+    pub fn heap_size_in_bytes(&self) -> usize {
+        let mut nodes = 0;
+        let mut link = &self.head;
+        while let Some(node) = link {
+            nodes += 1;
+            link = &node.next;
+        }
+
+        nodes * core::mem::size_of::<Node<T, N>>()
+    }
+}
+
+// Elements only, how they are split into nodes doesn't matter
+impl<T: fmt::Debug, const N: usize> fmt::Debug for List<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T: proptest::arbitrary::Arbitrary, const N: usize> proptest::arbitrary::Arbitrary
+    for List<T, N>
+{
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <T as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = crate::arbitrary::ListStrategy<T, Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::arbitrary::list_strategy(args, |elems| {
+            let mut list = List::new();
+            for elem in elems {
+                list.push(elem);
+            }
+            list
+        })
+    }
+}
+
+impl<T, const N: usize> Stack<T> for List<T, N> {
+    fn push(&mut self, elem: T) {
+        self.push(elem);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.pop()
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.peek()
+    }
+}
+
+impl<T, const N: usize> Drop for List<T, N> {
+    fn drop(&mut self) {
+        // Same as in second::List, no recursion over nodes
+        let mut cur_link = self.head.take();
+
+        while let Some(mut boxed_node) = cur_link {
+            cur_link = boxed_node.next.take();
+        }
+    }
+}
+
+pub struct IntoIter<T, const N: usize>(List<T, N>);
+
+impl<T, const N: usize> IntoIterator for List<T, N> {
+    type Item = T;
+    type IntoIter = IntoIter<T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop()
+    }
+}
+
+// Elements are not counted, so the size is unknown, but all iterators here are fused -
+// once nodes run out, they stay out
+impl<T, const N: usize> FusedIterator for IntoIter<T, N> {}
+
+pub struct Iter<'a, T, const N: usize> {
+    // What's left in the current node, from the top
+    elems: core::iter::Rev<core::slice::Iter<'a, T>>,
+    next: Option<&'a Node<T, N>>,
+}
+
+impl<T, const N: usize> List<T, N> {
+    pub fn iter(&self) -> Iter<'_, T, N> {
+        Iter {
+            elems: [].iter().rev(),
+            next: self.head.as_deref(),
+        }
+    }
+}
+
+impl<'a, T, const N: usize> Iterator for Iter<'a, T, N> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(elem) = self.elems.next() {
+                return Some(elem);
+            }
+
+            let node = self.next?;
+            self.elems = node.as_slice().iter().rev();
+            self.next = node.next.as_deref();
+        }
+    }
+}
+
+impl<'a, T, const N: usize> FusedIterator for Iter<'a, T, N> {}
+
+pub struct IterMut<'a, T, const N: usize> {
+    elems: core::iter::Rev<core::slice::IterMut<'a, T>>,
+    next: Option<&'a mut Node<T, N>>,
+}
+
+impl<T, const N: usize> List<T, N> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, N> {
+        IterMut {
+            elems: [].iter_mut().rev(),
+            next: self.head.as_deref_mut(),
+        }
+    }
+}
+
+impl<'a, T, const N: usize> Iterator for IterMut<'a, T, N> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(elem) = self.elems.next() {
+                return Some(elem);
+            }
+
+            let node = self.next.take()?;
+            // Split the borrow of the node, elements go to one place, next to the other
+            let elems =
+                unsafe { &mut *(&mut node.elems[..node.len] as *mut [MaybeUninit<T>] as *mut [T]) };
+            self.elems = elems.iter_mut().rev();
+            self.next = node.next.as_deref_mut();
+        }
+    }
+}
+
+impl<'a, T, const N: usize> FusedIterator for IterMut<'a, T, N> {}
+
+#[cfg(test)]
+mod test {
+    use super::List;
+    use std::rc::Rc;
+
+    #[test]
+    fn basics() {
+        let mut list: List<i32, 4> = List::new();
+
+        // Check empty list behaves right
+        assert_eq!(list.pop(), None);
+        assert_eq!(list.peek(), None);
+
+        // Populate list, more than fits into one node
+        for i in 1..=10 {
+            list.push(i);
+        }
+
+        // Check normal removal
+        assert_eq!(list.peek(), Some(&10));
+        assert_eq!(list.pop(), Some(10));
+        assert_eq!(list.pop(), Some(9));
+
+        // Push some more just to make sure nothing's corrupted
+        list.push(11);
+        assert_eq!(list.pop(), Some(11));
+
+        for i in (1..=8).rev() {
+            assert_eq!(list.pop(), Some(i));
+        }
+
+        // Check exhaustion
+        assert_eq!(list.pop(), None);
+        assert_eq!(list.peek(), None);
+    }
+
+    #[test]
+    fn peek_mut() {
+        let mut list: List<i32> = List::new();
+        list.push(1);
+        list.push(2);
+
+        if let Some(value) = list.peek_mut() {
+            *value = 42
+        }
+
+        assert_eq!(list.peek(), Some(&42));
+        assert_eq!(list.pop(), Some(42));
+    }
+
+    #[test]
+    fn iters() {
+        let mut list: List<i32, 3> = List::new();
+        for i in 0..10 {
+            list.push(i);
+        }
+
+        assert!(list.iter().copied().eq((0..10).rev()));
+
+        for elem in list.iter_mut() {
+            *elem *= 10;
+        }
+
+        assert!(list.into_iter().eq((0..10).rev().map(|i| i * 10)));
+    }
+
+    #[test]
+    fn drop_elements() {
+        let elem = Rc::new(());
+
+        {
+            let mut list: List<_, 4> = List::new();
+            for _ in 0..10 {
+                list.push(elem.clone());
+            }
+            list.pop();
+        }
+
+        assert_eq!(Rc::strong_count(&elem), 1);
+    }
+
+    #[test]
+    fn break_the_stack() {
+        let mut list: List<i32, 1> = List::new();
+
+        for i in 0..1_000_000 {
+            list.push(i);
+        }
+    }
+
+    #[test]
+    fn heap_size() {
+        let mut list: List<u64, 4> = List::new();
+        assert_eq!(list.heap_size_in_bytes(), 0);
+
+        // Elements, len and the next link
+        let node = 4 * 8 + 8 + 8;
+        list.push(1);
+        assert_eq!(list.heap_size_in_bytes(), node);
+        for i in 0..4 {
+            list.push(i);
+        }
+        assert_eq!(list.heap_size_in_bytes(), 2 * node);
+    }
+
+    #[test]
+    fn nodes_stay_dense() {
+        let mut list: List<u64, 4> = List::new();
+        let node = core::mem::size_of::<super::Node<u64, 4>>();
+
+        // Whatever the order of pushes and pops, len elements take ceil(len / N) nodes
+        let mut len: usize = 0;
+        for round in 0..20 {
+            for i in 0..(round % 7) {
+                list.push(i);
+                len += 1;
+            }
+            for _ in 0..(round % 5) {
+                if list.pop().is_some() {
+                    len -= 1;
+                }
+            }
+            assert_eq!(list.heap_size_in_bytes(), len.div_ceil(4) * node);
+        }
+    }
+}
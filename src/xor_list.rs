@@ -0,0 +1,719 @@
+use alloc::boxed::Box;
+use core::fmt;
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+use core::ptr::{self, null_mut};
+
+use crate::traits::Queue;
+
+// XOR linked list - doubly linked like fourth::List, but each node keeps a single word
+// instead of prev and next pointers:
+//
+//   link = address(prev) ^ address(next)
+//
+// Walking needs two nodes - where we are, and where we came from:
+//   next = link ^ address(prev), going forward
+//   prev = link ^ address(next), going backwards
+// Ends of the list XOR with 0 (null), so head.link is just the address of its next,
+// tail.link is the address of its prev.
+//
+// Saves one word per node, costs: no way to start walking from a node alone (you need its
+// neighbour), and the pointers are invisible to the compiler and tools.
+//
+// Unsafe contract:
+// - every node is allocated with Box::into_raw in insert_between, and freed exactly once
+//   with Box::from_raw in unlink, after it's taken out of the list
+// - for every node, link == address(prev) ^ address(next), where prev/next are null at the ends,
+//   head has no prev, tail has no next. Both functions above keep it, updating links
+//   of the neighbours, and nothing else writes to links
+// - addresses are turned back into pointers with with_exposed_provenance_mut, every address
+//   comes from expose_provenance of a pointer we got from Box::into_raw, so the
+//   pointer we get back is allowed to access the node (plain `as usize` and back would
+//   be UB under strict provenance, and miri would complain)
+// - references to nodes live only as long as the borrow of the list (peek, iter, cursors),
+//   and the list is never modified while they exist
+pub struct List<T> {
+    head: *mut Node<T>,
+    tail: *mut Node<T>,
+    len: usize,
+    _marker: PhantomData<Box<Node<T>>>,
+}
+
+struct Node<T> {
+    elem: T,
+    link: usize,
+}
+
+// Nodes are owned by the list, same as with Box
+unsafe impl<T: Send> Send for List<T> {}
+unsafe impl<T: Sync> Sync for List<T> {}
+
+fn addr<T>(node: *mut Node<T>) -> usize {
+    node.expose_provenance()
+}
+
+// Given one neighbour of the node, returns the other one
+unsafe fn other<T>(node: *mut Node<T>, neighbour: *mut Node<T>) -> *mut Node<T> {
+    ptr::with_exposed_provenance_mut((*node).link ^ addr(neighbour))
+}
+
+// Swaps neighbour old for new in the node's link (nothing to do for null)
+unsafe fn relink<T>(node: *mut Node<T>, old: *mut Node<T>, new: *mut Node<T>) {
+    if !node.is_null() {
+        (*node).link ^= addr(old) ^ addr(new);
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> List<T> {
+    pub const fn new() -> Self {
+        List {
+            head: null_mut(),
+            tail: null_mut(),
+            len: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Walks the list both ways and panics if it's broken: head and tail disagree, links
+    // don't lead from one end to the other, nodes form a cycle, or len is off.
+    // Same as fifth::List::debug_validate, for tests and fuzzers, no-op in release builds.
+    pub fn debug_validate(&self) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+
+        assert_eq!(
+            self.head.is_null(),
+            self.tail.is_null(),
+            "only one of head and tail is set"
+        );
+        // Going back checks the other half of every link
+        self.validate_walk(self.head, self.tail);
+        self.validate_walk(self.tail, self.head);
+    }
+
+    fn validate_walk(&self, from: *mut Node<T>, to: *mut Node<T>) {
+        let mut count = 0;
+        let mut prev = null_mut();
+        let mut cur = from;
+        while !cur.is_null() {
+            count += 1;
+            // A cycle would go on forever, stop as soon as there are more nodes than len
+            assert!(
+                count <= self.len,
+                "more than len = {} nodes, cycle?",
+                self.len
+            );
+            let next = unsafe { other(cur, prev) };
+            prev = cur;
+            cur = next;
+        }
+
+        assert_eq!(count, self.len, "len doesn't match the number of nodes");
+        assert_eq!(prev, to, "walk didn't end at the other end of the list");
+    }
+
+    // prev and next are neighbours (null for the ends), puts a new node between them
+    fn insert_between(&mut self, prev: *mut Node<T>, next: *mut Node<T>, elem: T) -> *mut Node<T> {
+        let node = Box::into_raw(Box::new(Node {
+            elem,
+            link: addr(prev) ^ addr(next),
+        }));
+
+        unsafe {
+            relink(prev, next, node);
+            relink(next, prev, node);
+        }
+
+        if prev.is_null() {
+            self.head = node;
+        }
+        if next.is_null() {
+            self.tail = node;
+        }
+
+        self.len += 1;
+        node
+    }
+
+    // prev is the neighbour of the node on the head side (null if node is the head)
+    unsafe fn unlink(&mut self, prev: *mut Node<T>, node: *mut Node<T>) -> T {
+        let next = other(node, prev);
+
+        relink(prev, node, next);
+        relink(next, node, prev);
+
+        if prev.is_null() {
+            self.head = next;
+        }
+        if next.is_null() {
+            self.tail = prev;
+        }
+
+        self.len -= 1;
+        Box::from_raw(node).elem
+    }
+
+    pub fn push_front(&mut self, elem: T) {
+        self.insert_between(null_mut(), self.head, elem);
+    }
+
+    pub fn push_back(&mut self, elem: T) {
+        self.insert_between(self.tail, null_mut(), elem);
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.head.is_null() {
+            return None;
+        }
+
+        unsafe { Some(self.unlink(null_mut(), self.head)) }
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.tail.is_null() {
+            return None;
+        }
+
+        unsafe {
+            let prev = other(self.tail, null_mut());
+            Some(self.unlink(prev, self.tail))
+        }
+    }
+
+    pub fn peek_front(&self) -> Option<&T> {
+        unsafe { self.head.as_ref().map(|node| &node.elem) }
+    }
+
+    pub fn peek_back(&self) -> Option<&T> {
+        unsafe { self.tail.as_ref().map(|node| &node.elem) }
+    }
+
+    pub fn peek_front_mut(&mut self) -> Option<&mut T> {
+        unsafe { self.head.as_mut().map(|node| &mut node.elem) }
+    }
+
+    pub fn peek_back_mut(&mut self) -> Option<&mut T> {
+        unsafe { self.tail.as_mut().map(|node| &mut node.elem) }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T: proptest::arbitrary::Arbitrary> proptest::arbitrary::Arbitrary for List<T> {
+    type Parameters = (
+        proptest::collection::SizeRange,
+        <T as proptest::arbitrary::Arbitrary>::Parameters,
+    );
+    type Strategy = crate::arbitrary::ListStrategy<T, Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        crate::arbitrary::list_strategy(args, |elems| {
+            let mut list = List::new();
+            for elem in elems {
+                list.push_back(elem);
+            }
+            list
+        })
+    }
+}
+
+impl<T> Queue<T> for List<T> {
+    fn enqueue(&mut self, elem: T) {
+        self.push_back(elem);
+    }
+
+    fn dequeue(&mut self) -> Option<T> {
+        self.pop_front()
+    }
+
+    fn front(&self) -> Option<&T> {
+        self.peek_front()
+    }
+}
+
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+}
+
+pub struct IntoIter<T>(List<T>);
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
+// Walks from both ends, every end remembers where it came from
+pub struct Iter<'a, T> {
+    front: *mut Node<T>,
+    front_prev: *mut Node<T>,
+    back: *mut Node<T>,
+    back_next: *mut Node<T>,
+    // Ends meet when it drops to 0
+    len: usize,
+    _marker: PhantomData<&'a T>,
+}
+
+impl<T> List<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            front: self.head,
+            front_prev: null_mut(),
+            back: self.tail,
+            back_next: null_mut(),
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+
+        unsafe {
+            let node = self.front;
+            self.front = other(node, self.front_prev);
+            self.front_prev = node;
+
+            Some(&(*node).elem)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+
+        unsafe {
+            let node = self.back;
+            self.back = other(node, self.back_next);
+            self.back_next = node;
+
+            Some(&(*node).elem)
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> FusedIterator for Iter<'a, T> {}
+
+// Cursors point at a node, or at the "ghost" between the tail and the head (null),
+// and move in both directions. Besides the current node, they remember the previous one,
+// needed to decode the link.
+pub struct Cursor<'a, T> {
+    list: &'a List<T>,
+    prev: *mut Node<T>,
+    current: *mut Node<T>,
+}
+
+pub struct CursorMut<'a, T> {
+    list: &'a mut List<T>,
+    prev: *mut Node<T>,
+    current: *mut Node<T>,
+}
+
+impl<T> List<T> {
+    pub fn cursor_front(&self) -> Cursor<'_, T> {
+        Cursor {
+            list: self,
+            prev: null_mut(),
+            current: self.head,
+        }
+    }
+
+    pub fn cursor_back(&self) -> Cursor<'_, T> {
+        let mut cursor = Cursor {
+            list: self,
+            prev: null_mut(),
+            current: null_mut(),
+        };
+        cursor.move_prev();
+        cursor
+    }
+
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        let current = self.head;
+
+        CursorMut {
+            list: self,
+            prev: null_mut(),
+            current,
+        }
+    }
+
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        let mut cursor = CursorMut {
+            list: self,
+            prev: null_mut(),
+            current: null_mut(),
+        };
+        cursor.move_prev();
+        cursor
+    }
+}
+
+// Shared by both cursors, returns new (prev, current)
+fn step_next<T>(
+    list: &List<T>,
+    prev: *mut Node<T>,
+    current: *mut Node<T>,
+) -> (*mut Node<T>, *mut Node<T>) {
+    if current.is_null() {
+        // Ghost -> head
+        (null_mut(), list.head)
+    } else {
+        // Past the tail, lands on the ghost
+        (current, unsafe { other(current, prev) })
+    }
+}
+
+fn step_prev<T>(
+    list: &List<T>,
+    prev: *mut Node<T>,
+    current: *mut Node<T>,
+) -> (*mut Node<T>, *mut Node<T>) {
+    if current.is_null() {
+        // Ghost -> tail, its only neighbour is the prev one
+        let tail = list.tail;
+        let before_tail = if tail.is_null() {
+            null_mut()
+        } else {
+            unsafe { other(tail, null_mut()) }
+        };
+
+        (before_tail, tail)
+    } else if prev.is_null() {
+        // Before the head is the ghost
+        (null_mut(), null_mut())
+    } else {
+        (unsafe { other(prev, current) }, prev)
+    }
+}
+
+impl<'a, T> Cursor<'a, T> {
+    pub fn move_next(&mut self) {
+        let (prev, current) = step_next(self.list, self.prev, self.current);
+        self.prev = prev;
+        self.current = current;
+    }
+
+    pub fn move_prev(&mut self) {
+        let (prev, current) = step_prev(self.list, self.prev, self.current);
+        self.prev = prev;
+        self.current = current;
+    }
+
+    // None on the ghost
+    pub fn current(&self) -> Option<&'a T> {
+        unsafe { self.current.as_ref().map(|node| &node.elem) }
+    }
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    pub fn move_next(&mut self) {
+        let (prev, current) = step_next(self.list, self.prev, self.current);
+        self.prev = prev;
+        self.current = current;
+    }
+
+    pub fn move_prev(&mut self) {
+        let (prev, current) = step_prev(self.list, self.prev, self.current);
+        self.prev = prev;
+        self.current = current;
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        unsafe { self.current.as_mut().map(|node| &mut node.elem) }
+    }
+
+    // On the ghost, inserts at the back
+    pub fn insert_before(&mut self, elem: T) {
+        if self.current.is_null() {
+            self.list.push_back(elem);
+        } else {
+            // New node becomes our prev
+            self.prev = self.list.insert_between(self.prev, self.current, elem);
+        }
+    }
+
+    // On the ghost, inserts at the front
+    pub fn insert_after(&mut self, elem: T) {
+        if self.current.is_null() {
+            self.list.push_front(elem);
+        } else {
+            let next = unsafe { other(self.current, self.prev) };
+            self.list.insert_between(self.current, next, elem);
+        }
+    }
+
+    // Cursor moves to the next node (or the ghost)
+    pub fn remove_current(&mut self) -> Option<T> {
+        if self.current.is_null() {
+            return None;
+        }
+
+        unsafe {
+            let next = other(self.current, self.prev);
+            let elem = self.list.unlink(self.prev, self.current);
+            self.current = next;
+
+            // Removing the tail lands on the ghost, prev does not matter there
+            Some(elem)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::List;
+    use std::rc::Rc;
+
+    #[test]
+    fn basics() {
+        let mut list = List::new();
+
+        // Check empty list behaves right
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.pop_back(), None);
+        assert!(list.is_empty());
+
+        // Populate list
+        list.push_front(2);
+        list.push_front(1);
+        list.push_back(3);
+        list.push_back(4);
+        assert_eq!(list.len(), 4);
+
+        assert_eq!(list.peek_front(), Some(&1));
+        assert_eq!(list.peek_back(), Some(&4));
+
+        // Check normal removal
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pop_back(), Some(4));
+
+        // Push some more just to make sure nothing's corrupted
+        list.push_back(5);
+        if let Some(elem) = list.peek_front_mut() {
+            *elem = 20;
+        }
+        assert_eq!(list.pop_front(), Some(20));
+        assert_eq!(list.pop_front(), Some(3));
+        assert_eq!(list.pop_back(), Some(5));
+
+        // Check exhaustion
+        assert_eq!(list.pop_back(), None);
+        assert_eq!(list.peek_back(), None);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn iter() {
+        let mut list = List::new();
+        for i in 0..10 {
+            list.push_back(i);
+        }
+
+        assert!(list.iter().copied().eq(0..10));
+        assert!(list.iter().rev().copied().eq((0..10).rev()));
+
+        // Both ends meet in the middle
+        let mut iter = list.iter();
+        assert_eq!(iter.next(), Some(&0));
+        assert_eq!(iter.next_back(), Some(&9));
+        assert_eq!(iter.len(), 8);
+        assert!(iter.copied().eq(1..9));
+
+        assert!(list.into_iter().rev().eq((0..10).rev()));
+    }
+
+    #[test]
+    fn cursor() {
+        let mut list = List::new();
+        for i in 1..=3 {
+            list.push_back(i);
+        }
+
+        let mut cursor = list.cursor_front();
+        assert_eq!(cursor.current(), Some(&1));
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&3));
+        cursor.move_next();
+        assert_eq!(cursor.current(), None);
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&1));
+
+        // And back
+        cursor.move_prev();
+        assert_eq!(cursor.current(), None);
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&3));
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&2));
+
+        let mut cursor = list.cursor_back();
+        assert_eq!(cursor.current(), Some(&3));
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&2));
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&3));
+    }
+
+    #[test]
+    fn cursor_mut() {
+        let mut list = List::new();
+        for i in 1..=3 {
+            list.push_back(i);
+        }
+
+        let mut cursor = list.cursor_front_mut();
+        cursor.move_next();
+        *cursor.current().unwrap() = 20;
+
+        cursor.insert_before(15);
+        cursor.insert_after(25);
+        assert_eq!(cursor.current(), Some(&mut 20));
+
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&mut 15));
+        assert_eq!(cursor.remove_current(), Some(15));
+        assert_eq!(cursor.current(), Some(&mut 20));
+
+        // Insert at both ends through the ghost
+        let mut cursor = list.cursor_back_mut();
+        cursor.move_next();
+        cursor.insert_before(4);
+        cursor.insert_after(0);
+        assert!(list.iter().copied().eq(vec![0, 1, 20, 25, 3, 4]));
+
+        // Remove everything going backwards
+        let mut cursor = list.cursor_back_mut();
+        assert_eq!(cursor.remove_current(), Some(4));
+        assert_eq!(cursor.current(), None);
+        cursor.move_prev();
+        assert_eq!(cursor.remove_current(), Some(3));
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.remove_current(), Some(20));
+        assert_eq!(cursor.current(), Some(&mut 25));
+        assert!(list.iter().copied().eq(vec![0, 1, 25]));
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.pop_back(), Some(25));
+        assert_eq!(list.pop_back(), Some(1));
+        assert_eq!(list.pop_back(), Some(0));
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn drop_elements() {
+        let elem = Rc::new(());
+
+        {
+            let mut list = List::new();
+            for _ in 0..10 {
+                list.push_back(elem.clone());
+            }
+            list.pop_front();
+        }
+
+        assert_eq!(Rc::strong_count(&elem), 1);
+    }
+
+    #[test]
+    fn debug_validate() {
+        let mut list = List::new();
+        list.debug_validate();
+
+        for i in 0..6 {
+            if i % 2 == 0 {
+                list.push_front(i);
+            } else {
+                list.push_back(i);
+            }
+            list.debug_validate();
+        }
+
+        let mut cursor = list.cursor_front_mut();
+        cursor.move_next();
+        cursor.insert_before(10);
+        cursor.remove_current();
+        list.debug_validate();
+
+        list.pop_back();
+        list.pop_front();
+        list.debug_validate();
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "len doesn't match")]
+    fn debug_validate_catches_bad_len() {
+        let mut list = List::new();
+        list.push_back(1);
+        list.push_back(2);
+        list.len = 3;
+        list.debug_validate();
+        // Nothing is leaked, Drop stops at null, not after len nodes
+    }
+}
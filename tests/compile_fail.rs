@@ -0,0 +1,10 @@
+// Things the lists must NOT let through. Send/Sync impls, PhantomData markers and
+// brands (ghost.rs) are promises the compiler can't check for us, every file in ui/
+// is a program that has to be rejected, with the error in the .stderr next to it.
+//
+// After a compiler update changes the messages: TRYBUILD=overwrite cargo test --test compile_fail
+#[test]
+fn compile_fail() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
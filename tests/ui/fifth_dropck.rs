@@ -0,0 +1,9 @@
+// Dropping the list drops its elements, so borrowed elements have to outlive it -
+// here elem is declared later, so it's dropped first
+use lists::fifth::List;
+
+fn main() {
+    let mut list = List::new();
+    let elem = String::from("dangling");
+    list.push(&elem);
+}
@@ -0,0 +1,21 @@
+// List is covariant in T, but IterMut hands out &mut T, which has to be invariant -
+// otherwise a short lived &str could be written into a list of &'static str
+use lists::fifth::{IterMut, List};
+
+fn shorten<'i, 'a>(iter: IterMut<'i, &'static str>) -> IterMut<'i, &'a str> {
+    iter
+}
+
+fn main() {
+    let mut list: List<&'static str> = List::new();
+    list.push("static");
+
+    {
+        let local = String::from("local");
+        if let Some(elem) = shorten(list.iter_mut()).next() {
+            *elem = &local;
+        }
+    }
+
+    println!("{:?}", list.peek());
+}
@@ -0,0 +1,12 @@
+// Rc is not Send, so neither is a list of them
+use lists::fifth::List;
+use std::rc::Rc;
+
+fn main() {
+    let mut list = List::new();
+    list.push(Rc::new(1));
+
+    std::thread::spawn(move || {
+        list.pop();
+    });
+}
@@ -0,0 +1,13 @@
+// Cell is not Sync, &List<Cell<_>> gives out &Cell, so it can't be shared
+use lists::fifth::List;
+use std::cell::Cell;
+
+fn main() {
+    let mut list = List::new();
+    list.push(Cell::new(1));
+    let list = &list;
+
+    std::thread::scope(|s| {
+        s.spawn(move || list.peek().map(|cell| cell.set(2)));
+    });
+}
@@ -0,0 +1,13 @@
+// Every GhostToken::new makes a new brand, a token can't open cells of another one
+use lists::ghost::{GhostToken, List};
+
+fn main() {
+    GhostToken::new(|mut token_a| {
+        let mut list = List::new();
+        list.push_front(1, &mut token_a);
+
+        GhostToken::new(|token_b| {
+            list.peek_front(&token_b);
+        });
+    });
+}
@@ -0,0 +1,25 @@
+// The item under a LinkGuard may live shorter than the list, so the list can't be
+// touched while the guard is alive - pop_front would hand the item out for too long
+use lists::intrusive::{IntrusiveList, Link, Linked};
+use std::pin::pin;
+
+struct Task {
+    link: Link<Task>,
+}
+
+unsafe impl Linked for Task {
+    fn link(&self) -> &Link<Self> {
+        &self.link
+    }
+}
+
+fn main() {
+    let mut list = IntrusiveList::new();
+    let escaped;
+    {
+        let mut task = pin!(Task { link: Link::new() });
+        let _guard = list.push_back_guard(task.as_mut());
+        escaped = list.pop_front();
+    }
+    drop(escaped);
+}